@@ -5,6 +5,7 @@ pub struct Config {
     pub database_url: String,
     pub port: u16,
     pub host: String,
+    pub auto_migrate: bool,
 }
 
 impl Config {
@@ -23,10 +24,16 @@ impl Config {
         let host = env::var("API_HOST")
             .unwrap_or_else(|_| "0.0.0.0".to_string());
 
+        // Apply pending migrations on startup instead of requiring `pm-api migrate`
+        let auto_migrate = env::var("AUTO_MIGRATE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()?;
+
         Ok(Config {
             database_url,
             port,
             host,
+            auto_migrate,
         })
     }
 }
@@ -3,6 +3,7 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use pm_shared::store::StoreError;
 use serde_json::json;
 use std::fmt;
 
@@ -10,6 +11,8 @@ use std::fmt;
 pub enum ApiError {
     NotFound(String),
     BadRequest(String),
+    // Kept for handlers that fail outside the store; none do yet
+    #[allow(dead_code)]
     InternalError(String),
     DatabaseError(String),
 }
@@ -53,4 +56,13 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+impl From<StoreError> for ApiError {
+    fn from(err: StoreError) -> Self {
+        match err {
+            StoreError::NotFound => ApiError::NotFound("Resource not found".to_string()),
+            StoreError::Database(err) => ApiError::DatabaseError(err.to_string()),
+        }
+    }
+}
+
 pub type ApiResult<T> = Result<T, ApiError>;
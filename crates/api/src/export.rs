@@ -0,0 +1,300 @@
+use arrow_array::{ArrayRef, Decimal128Array, RecordBatch, StringArray, TimestampMicrosecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use axum::body::Bytes;
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use pm_shared::store::StoreError;
+use pm_shared::{Decimal, ExportFormat, HistoryExportRow, AMOUNT_DP, PRICE_DP};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Bytes of CSV/JSON Lines buffered before a chunk is sent
+const CHUNK_BYTES: usize = 64 * 1024;
+
+/// Rows per Parquet row group; each group is sent as soon as it is written
+const ROW_GROUP_ROWS: usize = 16 * 1024;
+
+const CSV_HEADER: [&str; 9] = [
+    "market_id",
+    "source",
+    "source_id",
+    "recorded_at",
+    "yes_price",
+    "no_price",
+    "volume",
+    "volume_24h",
+    "liquidity",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExportError {
+    #[error(transparent)]
+    Store(#[from] StoreError),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Arrow(#[from] ArrowError),
+
+    #[error(transparent)]
+    Parquet(#[from] ParquetError),
+}
+
+/// Encode `rows` as `format`, yielding chunks as they fill so the export is
+/// never held in memory as a whole
+pub fn encode(
+    rows: BoxStream<'static, Result<HistoryExportRow, StoreError>>,
+    format: ExportFormat,
+) -> BoxStream<'static, Result<Bytes, ExportError>> {
+    match format {
+        ExportFormat::Csv => encode_csv(rows),
+        ExportFormat::Jsonl => encode_jsonl(rows),
+        ExportFormat::Parquet => encode_parquet(rows),
+    }
+}
+
+fn encode_csv(
+    mut rows: BoxStream<'static, Result<HistoryExportRow, StoreError>>,
+) -> BoxStream<'static, Result<Bytes, ExportError>> {
+    Box::pin(async_stream::try_stream! {
+        let buffer = SharedBuffer::default();
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(false)
+            .from_writer(buffer.clone());
+        writer.write_record(CSV_HEADER)?;
+
+        while let Some(row) = rows.try_next().await? {
+            writer.serialize(&row)?;
+
+            if buffer.len() >= CHUNK_BYTES {
+                yield buffer.take();
+            }
+        }
+
+        writer.flush().map_err(csv::Error::from)?;
+        yield buffer.take();
+    })
+}
+
+fn encode_jsonl(
+    mut rows: BoxStream<'static, Result<HistoryExportRow, StoreError>>,
+) -> BoxStream<'static, Result<Bytes, ExportError>> {
+    Box::pin(async_stream::try_stream! {
+        let mut buffer = Vec::with_capacity(CHUNK_BYTES);
+
+        while let Some(row) = rows.try_next().await? {
+            serde_json::to_writer(&mut buffer, &row)?;
+            buffer.push(b'\n');
+
+            if buffer.len() >= CHUNK_BYTES {
+                yield Bytes::from(std::mem::take(&mut buffer));
+            }
+        }
+
+        yield Bytes::from(buffer);
+    })
+}
+
+fn encode_parquet(
+    mut rows: BoxStream<'static, Result<HistoryExportRow, StoreError>>,
+) -> BoxStream<'static, Result<Bytes, ExportError>> {
+    Box::pin(async_stream::try_stream! {
+        let schema = parquet_schema();
+        let buffer = SharedBuffer::default();
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let mut writer = ArrowWriter::try_new(buffer.clone(), schema.clone(), Some(props))?;
+        let mut batch = Vec::with_capacity(ROW_GROUP_ROWS);
+
+        while let Some(row) = rows.try_next().await? {
+            batch.push(row);
+
+            if batch.len() >= ROW_GROUP_ROWS {
+                writer.write(&record_batch(&schema, &batch)?)?;
+                writer.flush()?;
+                batch.clear();
+                yield buffer.take();
+            }
+        }
+
+        if !batch.is_empty() {
+            writer.write(&record_batch(&schema, &batch)?)?;
+        }
+        writer.close()?;
+        yield buffer.take();
+    })
+}
+
+/// Digits in the Parquet decimal columns, matching the Postgres NUMERIC types
+const PRICE_PRECISION: u8 = 7;
+const AMOUNT_PRECISION: u8 = 20;
+
+/// Typed Parquet columns: UTC microsecond timestamps and exact decimals
+fn parquet_schema() -> SchemaRef {
+    let price = DataType::Decimal128(PRICE_PRECISION, PRICE_DP as i8);
+    let amount = DataType::Decimal128(AMOUNT_PRECISION, AMOUNT_DP as i8);
+
+    Arc::new(Schema::new(vec![
+        Field::new("market_id", DataType::Utf8, false),
+        Field::new("source", DataType::Utf8, false),
+        Field::new("source_id", DataType::Utf8, false),
+        Field::new(
+            "recorded_at",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("yes_price", price.clone(), false),
+        Field::new("no_price", price, false),
+        Field::new("volume", amount.clone(), false),
+        Field::new("volume_24h", amount.clone(), false),
+        Field::new("liquidity", amount, true),
+    ]))
+}
+
+fn record_batch(schema: &SchemaRef, rows: &[HistoryExportRow]) -> Result<RecordBatch, ArrowError> {
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.market_id.to_string()),
+        )),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.source.as_str()))),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|r| &r.source_id))),
+        Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                rows.iter().map(|r| r.recorded_at.timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(price_array(rows.iter().map(|r| Some(r.yes_price)))?),
+        Arc::new(price_array(rows.iter().map(|r| Some(r.no_price)))?),
+        Arc::new(amount_array(rows.iter().map(|r| Some(r.volume)))?),
+        Arc::new(amount_array(rows.iter().map(|r| Some(r.volume_24h)))?),
+        Arc::new(amount_array(rows.iter().map(|r| r.liquidity))?),
+    ];
+
+    RecordBatch::try_new(schema.clone(), columns)
+}
+
+fn price_array(
+    values: impl Iterator<Item = Option<Decimal>>,
+) -> Result<Decimal128Array, ArrowError> {
+    decimal_array(values, PRICE_PRECISION, PRICE_DP)
+}
+
+fn amount_array(
+    values: impl Iterator<Item = Option<Decimal>>,
+) -> Result<Decimal128Array, ArrowError> {
+    decimal_array(values, AMOUNT_PRECISION, AMOUNT_DP)
+}
+
+/// Decimals as unscaled integers at a fixed scale
+fn decimal_array(
+    values: impl Iterator<Item = Option<Decimal>>,
+    precision: u8,
+    scale: u32,
+) -> Result<Decimal128Array, ArrowError> {
+    values
+        .map(|value| {
+            value.map(|mut d| {
+                d.rescale(scale);
+                d.mantissa()
+            })
+        })
+        .collect::<Decimal128Array>()
+        .with_precision_and_scale(precision, scale as i8)
+}
+
+/// Sink the CSV and Parquet writers append to, drained as chunks are sent
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+
+    fn take(&self) -> Bytes {
+        Bytes::from(std::mem::take(&mut *self.0.lock().unwrap()))
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use futures::stream;
+    use pm_shared::MarketSource;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use uuid::Uuid;
+
+    fn row(minute: u32) -> HistoryExportRow {
+        HistoryExportRow {
+            market_id: Uuid::nil(),
+            source: MarketSource::Kalshi,
+            source_id: "TICKER".to_string(),
+            recorded_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, minute, 0).unwrap(),
+            yes_price: Decimal::new(25, 2),
+            no_price: Decimal::new(75, 2),
+            volume: "123456789.01".parse().unwrap(),
+            volume_24h: Decimal::ONE,
+            liquidity: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn parquet_round_trips_with_typed_columns() {
+        let rows = Box::pin(stream::iter((0..3).map(|m| Ok(row(m)))));
+        let chunks: Vec<Bytes> = encode(rows, ExportFormat::Parquet)
+            .try_collect()
+            .await
+            .unwrap();
+        let file = Bytes::from(chunks.concat());
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        let batch = &batches[0];
+
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(
+            batch.schema().field_with_name("recorded_at").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        );
+        let timestamps = batch
+            .column_by_name("recorded_at")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMicrosecondArray>()
+            .unwrap();
+        assert_eq!(timestamps.value(2), row(2).recorded_at.timestamp_micros());
+        let volumes = batch
+            .column_by_name("volume")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(volumes.value_as_string(0), "123456789.01");
+        assert_eq!(batch.column_by_name("liquidity").unwrap().null_count(), 3);
+    }
+}
@@ -1,29 +1,69 @@
 mod config;
-mod db;
 mod error;
+mod export;
 mod routes;
 
 use axum::{
-    routing::get,
+    routing::{get, post},
     Router,
 };
-use sqlx::postgres::PgPoolOptions;
+use clap::{Parser, Subcommand};
+use pm_shared::migrations;
+use pm_shared::store::{
+    AnomalyStore, Database, HistoryStore, MarketStore, MetricsStore, QuarantineStore, RunStore,
+};
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use config::Config;
-use db::{MarketRepository, PriceHistoryRepository};
+
+#[derive(Parser)]
+#[command(name = "pm-api")]
+#[command(about = "Prediction Market History Tracker API server", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Serve the HTTP API (default)
+    Serve,
+
+    /// Apply pending database migrations and exit
+    Migrate,
+}
 
 // Shared application state
 #[derive(Clone)]
 struct AppState {
-    market_repo: Arc<MarketRepository>,
-    history_repo: Arc<PriceHistoryRepository>,
+    market_repo: Arc<dyn MarketStore>,
+    history_repo: Arc<dyn HistoryStore>,
+    run_repo: Arc<dyn RunStore>,
+    quarantine_repo: Arc<dyn QuarantineStore>,
+    anomaly_repo: Arc<dyn AnomalyStore>,
+    metrics_repo: Arc<dyn MetricsStore>,
+}
+
+#[cfg(test)]
+impl AppState {
+    fn in_memory(store: Arc<pm_shared::store::MemoryStore>) -> Self {
+        Self {
+            market_repo: store.clone(),
+            history_repo: store.clone(),
+            run_repo: store.clone(),
+            quarantine_repo: store.clone(),
+            anomaly_repo: store.clone(),
+            metrics_repo: store,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+
     // Initialize tracing
     tracing_subscriber::registry()
         .with(
@@ -35,24 +75,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = Config::from_env()?;
-    tracing::info!("Starting PM History Tracker API on {}:{}", config.host, config.port);
 
-    // Create database connection pool
-    let pool = PgPoolOptions::new()
-        .max_connections(10)
-        .connect(&config.database_url)
-        .await?;
+    // Connect to Postgres or SQLite, depending on the DATABASE_URL scheme
+    let db = Database::connect(&config.database_url, 10).await?;
 
     tracing::info!("Connected to database");
 
-    // Create repositories
-    let market_repo = Arc::new(MarketRepository::new(pool.clone()));
-    let history_repo = Arc::new(PriceHistoryRepository::new(pool.clone()));
+    if matches!(cli.command, Some(Commands::Migrate)) {
+        migrations::run(&db).await?;
+        tracing::info!(
+            "Database migrated to version {}",
+            migrations::database_version(&db).await?
+        );
+        return Ok(());
+    }
+
+    if config.auto_migrate {
+        migrations::run(&db).await?;
+    }
+
+    let pending = migrations::check_version(&db).await?;
+    if pending > 0 {
+        tracing::warn!(
+            "Database is {} migration(s) behind; run `pm-api migrate`",
+            pending
+        );
+    }
+
+    tracing::info!("Starting PM History Tracker API on {}:{}", config.host, config.port);
 
     // Create shared app state
     let app_state = AppState {
-        market_repo,
-        history_repo,
+        market_repo: db.markets(),
+        history_repo: db.history(),
+        run_repo: db.runs(),
+        quarantine_repo: db.quarantine(),
+        anomaly_repo: db.anomalies(),
+        metrics_repo: db.metrics(),
     };
 
     // Build router
@@ -60,8 +119,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/health", get(health_check))
         .route("/api/search", get(routes::search::search_markets))
         .route("/api/markets", get(routes::markets::list_markets))
+        .route("/api/markets/batch", post(routes::markets::batch_markets))
+        .route(
+            "/api/markets/by-source/:source/:source_id",
+            get(routes::markets::get_market_by_source),
+        )
         .route("/api/markets/:id", get(routes::markets::get_market))
         .route("/api/markets/:id/history", get(routes::history::get_price_history))
+        .route("/api/markets/:id/revisions", get(routes::markets::get_market_revisions))
+        .route("/api/markets/:id/anomalies", get(routes::history::get_anomalies))
+        .route("/api/markets/:id/metrics", get(routes::markets::get_market_metrics))
+        .route("/api/history", get(routes::history::get_aligned_history))
+        .route("/api/correlations", get(routes::correlations::get_correlations))
+        .route("/api/taxonomy", get(routes::taxonomy::get_taxonomy))
+        .route("/api/export/history", get(routes::export::export_history))
+        .route("/api/admin/runs", get(routes::admin::list_runs))
+        .route("/api/admin/quality", get(routes::admin::quality_report))
         .with_state(app_state)
         .layer(CorsLayer::permissive());
 
@@ -0,0 +1,150 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiResult, routes::search::parse_filter, AppState};
+use pm_shared::{CollectionRun, MarketSource, QuarantinedMarket, SourceQuality};
+
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    pub source: Option<String>,
+    #[serde(default)]
+    pub failed: bool,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+pub async fn list_runs(
+    State(app_state): State<AppState>,
+    Query(params): Query<RunsQuery>,
+) -> ApiResult<Json<Vec<CollectionRun>>> {
+    let limit = params.limit.min(500);
+    let source = parse_filter::<MarketSource>(params.source.as_deref())?;
+    let runs = app_state
+        .run_repo
+        .list(limit, source.map(|s| s.as_str()), params.failed)
+        .await?;
+    Ok(Json(runs))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QualityQuery {
+    #[serde(default = "default_hours")]
+    pub hours: i64,
+    pub source: Option<String>,
+    #[serde(default = "default_quality_limit")]
+    pub limit: i64,
+}
+
+fn default_hours() -> i64 {
+    24
+}
+
+fn default_quality_limit() -> i64 {
+    20
+}
+
+/// Markets the worker quarantined over the window, summarized and most recent first
+#[derive(Debug, Serialize)]
+pub struct QualityReport {
+    pub since: DateTime<Utc>,
+    pub sources: Vec<SourceQuality>,
+    pub recent: Vec<QuarantinedMarket>,
+}
+
+pub async fn quality_report(
+    State(app_state): State<AppState>,
+    Query(params): Query<QualityQuery>,
+) -> ApiResult<Json<QualityReport>> {
+    let source = parse_filter::<MarketSource>(params.source.as_deref())?;
+    let since = Utc::now() - Duration::hours(params.hours.clamp(1, 24 * 90));
+    let limit = params.limit.clamp(0, 500);
+
+    let mut sources = app_state.quarantine_repo.quarantine_summary(since).await?;
+    sources.retain(|s| source.is_none_or(|source| s.source == source));
+    let recent = app_state
+        .quarantine_repo
+        .quarantined(since, source, limit)
+        .await?;
+
+    Ok(Json(QualityReport {
+        since,
+        sources,
+        recent,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::{MemoryStore, QuarantineStore};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn rejected(source: MarketSource, source_id: &str, check: &str) -> QuarantinedMarket {
+        QuarantinedMarket {
+            id: Uuid::new_v4(),
+            source,
+            source_id: Some(source_id.to_string()),
+            checks: vec![check.to_string()],
+            reason: check.to_string(),
+            payload: serde_json::json!({}),
+            quarantined_at: Utc::now(),
+            last_seen_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_quarantine_for_one_source() {
+        let store = Arc::new(MemoryStore::new());
+        store
+            .quarantine(&[
+                rejected(MarketSource::Kalshi, "A", "price_sum"),
+                rejected(MarketSource::Kalshi, "B", "price_sum"),
+                rejected(MarketSource::Polymarket, "C", "missing_price"),
+            ])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let params = QualityQuery {
+            hours: default_hours(),
+            source: Some("kalshi".to_string()),
+            limit: default_quality_limit(),
+        };
+        let Json(report) = quality_report(State(state), Query(params)).await.unwrap();
+
+        assert_eq!(report.sources.len(), 1);
+        assert_eq!(report.sources[0].quarantined, 2);
+        assert_eq!(report.sources[0].checks[0].count, 2);
+        assert_eq!(report.recent.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reports_a_market_failing_every_cycle_once() {
+        let store = Arc::new(MemoryStore::new());
+        for _ in 0..3 {
+            let market = rejected(MarketSource::Kalshi, "A", "price_sum");
+            store.quarantine(&[market]).await.unwrap();
+        }
+        let state = AppState::in_memory(store);
+
+        let params = QualityQuery {
+            hours: default_hours(),
+            source: None,
+            limit: default_quality_limit(),
+        };
+        let Json(report) = quality_report(State(state), Query(params)).await.unwrap();
+
+        assert_eq!(report.sources[0].quarantined, 1);
+        assert_eq!(report.recent.len(), 1);
+        assert!(report.recent[0].quarantined_at < report.recent[0].last_seen_at);
+    }
+}
@@ -0,0 +1,294 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use super::{export::parse_market_ids, history::SEED_LOOKBACK};
+use crate::{error::{ApiError, ApiResult}, AppState};
+use pm_shared::{
+    correlation, series, Category, CorrelationReport, MarketLabel, MarketRef, Resolution,
+};
+
+/// Most markets correlated in one request
+const MAX_MARKETS: usize = 50;
+
+/// Most grid points per market
+const MAX_POINTS: usize = 10_000;
+
+/// Snapshots read per market
+const HISTORY_LIMIT: i64 = 50_000;
+
+#[derive(Debug, Deserialize)]
+pub struct CorrelationQuery {
+    /// Comma-separated market ids
+    pub market_ids: Option<String>,
+    /// Instead of `market_ids`, the `top` highest-volume markets in this
+    /// canonical category
+    pub category: Option<String>,
+    #[serde(default = "default_top")]
+    pub top: usize,
+    /// How far back to look
+    #[serde(default = "default_hours")]
+    pub hours: i64,
+    /// Grid spacing, e.g. 15m, 1h or 1d
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    /// How many of the most correlated pairs to list
+    #[serde(default = "default_pairs")]
+    pub pairs: usize,
+}
+
+fn default_top() -> usize {
+    10
+}
+
+fn default_hours() -> i64 {
+    24 * 7
+}
+
+fn default_interval() -> String {
+    "1h".to_string()
+}
+
+fn default_pairs() -> usize {
+    10
+}
+
+/// Pairwise correlation of the markets' YES price returns over the last
+/// `hours`, resampled onto a common grid. Flagged anomalies are left out.
+pub async fn get_correlations(
+    State(app_state): State<AppState>,
+    Query(params): Query<CorrelationQuery>,
+) -> ApiResult<Json<CorrelationReport>> {
+    let interval = series::parse_interval(&params.interval).map_err(ApiError::BadRequest)?;
+    let hours = params.hours.clamp(1, 24 * 365);
+    let to = Utc::now();
+    let from = to - Duration::hours(hours);
+
+    let grid = series::grid(from, to, interval, MAX_POINTS).map_err(|too_many| {
+        ApiError::BadRequest(format!(
+            "{} hours at {} is {}; use a longer interval",
+            hours, params.interval, too_many
+        ))
+    })?;
+
+    let markets = match (params.market_ids.as_deref(), params.category.as_deref()) {
+        (Some(ids), None) => {
+            let ids = parse_market_ids(Some(ids))?;
+            if ids.len() > MAX_MARKETS {
+                return Err(ApiError::BadRequest(format!(
+                    "At most {} markets can be correlated at once",
+                    MAX_MARKETS
+                )));
+            }
+
+            let mut seen = HashSet::new();
+            if let Some(id) = ids.iter().find(|id| !seen.insert(**id)) {
+                return Err(ApiError::BadRequest(format!(
+                    "Market {} is listed more than once",
+                    id
+                )));
+            }
+
+            let refs: Vec<MarketRef> = ids.iter().copied().map(MarketRef::Id).collect();
+            let mut found = app_state.market_repo.lookup(&refs).await?;
+            let mut markets = Vec::with_capacity(ids.len());
+            for id in &ids {
+                match found.iter().position(|m| m.id == *id) {
+                    Some(i) => markets.push(found.swap_remove(i)),
+                    None => return Err(ApiError::NotFound(format!("Market {} not found", id))),
+                }
+            }
+            markets
+        }
+        (None, Some(category)) => {
+            let category = category.parse::<Category>().map_err(ApiError::BadRequest)?;
+            let top = params.top.min(MAX_MARKETS) as i64;
+            app_state.market_repo.top_in_category(category, top).await?
+        }
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Pass either `market_ids` or `category`".to_string(),
+            ))
+        }
+    };
+    if markets.len() < 2 {
+        return Err(ApiError::BadRequest(
+            "Need at least two markets to correlate".to_string(),
+        ));
+    }
+
+    let resolution = Resolution::for_interval(interval);
+    let start = grid.first().copied().unwrap_or(from);
+    let mut prices = Vec::with_capacity(markets.len());
+    for market in &markets {
+        let history_repo = &app_state.history_repo;
+        let mut history = history_repo
+            .history_between(market.id, start, to, HISTORY_LIMIT, resolution, false)
+            .await?;
+        // The last price before the grid starts, to carry into its first point
+        history.extend(
+            history_repo
+                .history_between(market.id, start - SEED_LOOKBACK, start, 1, resolution, false)
+                .await?,
+        );
+        prices.push(
+            series::resample(&history, &grid)
+                .into_iter()
+                .map(|s| s.map(|s| s.yes_price))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let labels = markets
+        .into_iter()
+        .map(|m| MarketLabel {
+            id: m.id,
+            source: m.source,
+            title: m.title,
+        })
+        .collect();
+
+    Ok(Json(correlation::report(
+        labels,
+        &prices,
+        from,
+        to,
+        interval,
+        params.pairs.min(100),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::{HistoryStore, MarketStore, MemoryStore, Snapshot};
+    use chrono::DateTime;
+    use pm_shared::{CreateMarket, Decimal, MarketSource, MarketStatus};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    fn market(source_id: &str, category: Category, volume: i64) -> CreateMarket {
+        CreateMarket {
+            source_id: source_id.to_string(),
+            source: MarketSource::Kalshi,
+            title: format!("Market {}", source_id),
+            description: String::new(),
+            category: Some(category),
+            source_category: None,
+            tags: None,
+            yes_price: Decimal::new(5, 1),
+            no_price: Decimal::new(5, 1),
+            volume: Decimal::from(volume),
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    fn snapshot(market_id: Uuid, recorded_at: DateTime<Utc>, cents: i64) -> Snapshot {
+        Snapshot {
+            market_id,
+            yes_price: Decimal::new(cents, 2),
+            no_price: Decimal::ONE - Decimal::new(cents, 2),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at,
+        }
+    }
+
+    fn query(category: &str, top: usize) -> CorrelationQuery {
+        CorrelationQuery {
+            market_ids: None,
+            category: Some(category.to_string()),
+            top,
+            hours: 48,
+            interval: "1h".to_string(),
+            pairs: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn correlates_top_markets_in_category() {
+        let store = Arc::new(MemoryStore::new());
+        let election = store.upsert(market("ELECTION", Category::Politics, 500)).await.unwrap();
+        let policy = store.upsert(market("POLICY", Category::Politics, 300)).await.unwrap();
+        store.upsert(market("MINOR", Category::Politics, 10)).await.unwrap();
+        store.upsert(market("BTC", Category::Crypto, 900)).await.unwrap();
+
+        // Policy follows the election market's hourly swings
+        let now = Utc::now();
+        let swings = [50, 56, 47, 61, 52, 58, 44, 50, 63, 55, 48, 57, 51, 60];
+        let mut snapshots = Vec::new();
+        for (h, cents) in swings.iter().enumerate() {
+            let recorded_at = now - Duration::hours(swings.len() as i64 - h as i64);
+            for (id, price) in [(election.id, *cents), (policy.id, cents / 2 + 10)] {
+                snapshots.push(snapshot(id, recorded_at, price));
+            }
+        }
+        store.import_snapshots(&snapshots).await.unwrap();
+        let state = AppState::in_memory(store);
+
+        let Json(report) = get_correlations(State(state.clone()), Query(query("POLITICS", 2)))
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = report.markets.iter().map(|m| m.id).collect();
+        assert_eq!(ids, [election.id, policy.id]);
+        assert_eq!(report.pairs.len(), 1);
+        assert!(report.pairs[0].correlation > 0.95);
+        assert_eq!(report.interval_seconds, 3600);
+
+        let lonely = get_correlations(State(state.clone()), Query(query("Crypto", 10))).await;
+        assert!(matches!(lonely, Err(ApiError::BadRequest(_))));
+
+        let unknown = get_correlations(State(state), Query(query("Elections", 10))).await;
+        assert!(matches!(unknown, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn carries_prices_from_before_the_window_and_refuses_repeats() {
+        let store = Arc::new(MemoryStore::new());
+        let a = store.upsert(market("A", Category::Politics, 1)).await.unwrap();
+        let b = store.upsert(market("B", Category::Politics, 1)).await.unwrap();
+
+        // Both last moved before the window and once near its end
+        let now = Utc::now();
+        store
+            .import_snapshots(&[
+                snapshot(a.id, now - Duration::hours(60), 40),
+                snapshot(b.id, now - Duration::hours(60), 30),
+                snapshot(a.id, now - Duration::hours(2), 50),
+                snapshot(b.id, now - Duration::hours(2), 35),
+            ])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let ids = |ids: &[Uuid]| CorrelationQuery {
+            market_ids: Some(ids.iter().map(Uuid::to_string).collect::<Vec<_>>().join(",")),
+            category: None,
+            ..query("", 10)
+        };
+
+        let Json(report) = get_correlations(State(state.clone()), Query(ids(&[b.id, a.id])))
+            .await
+            .unwrap();
+        assert_eq!(report.markets.iter().map(|m| m.id).collect::<Vec<_>>(), [b.id, a.id]);
+        // Every step of the 48 hour grid has a price at both ends
+        assert_eq!(report.pairs[0].observations, 48);
+
+        let repeated = ids(&[a.id, b.id, a.id]);
+        let repeated = get_correlations(State(state.clone()), Query(repeated)).await;
+        assert!(matches!(repeated, Err(ApiError::BadRequest(_))));
+
+        let unknown = get_correlations(State(state), Query(ids(&[a.id, Uuid::new_v4()]))).await;
+        assert!(matches!(unknown, Err(ApiError::NotFound(_))));
+    }
+}
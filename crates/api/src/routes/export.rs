@@ -0,0 +1,135 @@
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{error::{ApiError, ApiResult}, export, AppState};
+use pm_shared::ExportFormat;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Comma-separated market ids; every market when omitted
+    pub market_ids: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    /// csv (default), jsonl or parquet
+    pub format: Option<String>,
+}
+
+/// Stream raw price history straight from the database, encoded as it is read
+pub async fn export_history(
+    State(app_state): State<AppState>,
+    Query(params): Query<ExportQuery>,
+) -> ApiResult<Response> {
+    let format = match params.format.as_deref() {
+        Some(f) => f.parse::<ExportFormat>().map_err(ApiError::BadRequest)?,
+        None => ExportFormat::Csv,
+    };
+    let market_ids = parse_market_ids(params.market_ids.as_deref())?;
+
+    if let (Some(from), Some(to)) = (params.from, params.to) {
+        if from >= to {
+            return Err(ApiError::BadRequest("`from` must be before `to`".to_string()));
+        }
+    }
+
+    let rows = app_state.history_repo.export(market_ids, params.from, params.to);
+    let body = export::encode(rows, format).inspect_err(|e| {
+        tracing::error!("History export aborted: {}", e);
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"history.{}\"", format),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+pub(crate) fn parse_market_ids(raw: Option<&str>) -> ApiResult<Vec<Uuid>> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse::<Uuid>()
+                .map_err(|_| ApiError::BadRequest(format!("Invalid market id: {}", id)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+    use pm_shared::store::{HistoryStore, MarketStore, MemoryStore};
+    use pm_shared::{CreateMarket, Decimal, MarketSource, MarketStatus};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn exports_csv_and_jsonl() {
+        let store = Arc::new(MemoryStore::new());
+        let market = store
+            .upsert(CreateMarket {
+                source_id: "TICKER".to_string(),
+                source: MarketSource::Kalshi,
+                title: "Market".to_string(),
+                description: String::new(),
+                category: None,
+                source_category: None,
+                tags: None,
+                yes_price: Decimal::new(25, 2),
+                no_price: Decimal::new(75, 2),
+                volume: Decimal::from(10),
+                volume_24h: Decimal::ONE,
+                liquidity: None,
+                status: MarketStatus::Open,
+                close_at: None,
+                url: String::new(),
+            })
+            .await
+            .unwrap();
+        store.record_snapshot(&market, market.updated_at).await.unwrap();
+
+        let export = |format: &str| {
+            export_history(
+                State(AppState::in_memory(store.clone())),
+                Query(ExportQuery {
+                    market_ids: Some(market.id.to_string()),
+                    from: None,
+                    to: None,
+                    format: Some(format.to_string()),
+                }),
+            )
+        };
+
+        let body = to_bytes(export("csv").await.unwrap().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(body.to_vec()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("market_id,source,source_id,recorded_at"));
+        assert!(lines[1].starts_with(&format!("{},kalshi,TICKER,", market.id)));
+
+        let body = to_bytes(export("jsonl").await.unwrap().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let row: serde_json::Value = serde_json::from_slice(body.trim_ascii_end()).unwrap();
+        assert_eq!(row["source_id"], "TICKER");
+        assert_eq!(row["yes_price"], 0.25);
+
+        assert!(matches!(export("xlsx").await, Err(ApiError::BadRequest(_))));
+    }
+}
@@ -2,17 +2,68 @@ use axum::{
     extract::{Path, Query, State},
     Json,
 };
+use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{error::ApiResult, AppState};
-use pm_shared::PriceHistory;
+use super::export::parse_market_ids;
+use crate::{error::{ApiError, ApiResult}, AppState};
+use pm_shared::{
+    series, AlignedHistory, AlignedRow, Anomaly, MarketLabel, MarketRef, PriceHistory, Resolution,
+};
+
+/// Most markets aligned in one request
+const MAX_MARKETS: usize = 50;
+
+/// Most grid points per market
+const MAX_POINTS: usize = 10_000;
+
+/// Snapshots read per market
+const HISTORY_LIMIT: i64 = 50_000;
+
+/// How far before the grid to look for a value to carry into its first point
+/// when `max_age` is not set
+pub(crate) const SEED_LOOKBACK: Duration = Duration::days(30);
 
 #[derive(Debug, Deserialize)]
 pub struct HistoryQuery {
     #[serde(default = "default_limit")]
     pub limit: i64,
     pub hours: Option<i64>,
+    /// raw, hourly or daily; picked from the span of `hours` when omitted
+    pub resolution: Option<String>,
+    /// Keep snapshots flagged as anomalies (raw resolution only)
+    #[serde(default)]
+    pub include_anomalies: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlignedQuery {
+    /// Comma-separated market ids
+    pub market_ids: Option<String>,
+    /// Grid spacing, e.g. 15m, 1h or 1d
+    #[serde(default = "default_interval")]
+    pub interval: String,
+    /// Defaults to 24 hours before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub to: Option<DateTime<Utc>>,
+    /// Leave a gap rather than carry a price forward further than this, e.g. 2h
+    pub max_age: Option<String>,
+    #[serde(default)]
+    pub include_anomalies: bool,
+}
+
+fn default_interval() -> String {
+    "1h".to_string()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnomaliesQuery {
+    /// Only snapshots recorded in the last `hours`
+    pub hours: Option<i64>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
 }
 
 fn default_limit() -> i64 {
@@ -25,6 +76,379 @@ pub async fn get_price_history(
     Query(params): Query<HistoryQuery>,
 ) -> ApiResult<Json<Vec<PriceHistory>>> {
     let limit = params.limit.min(1000);
-    let history = app_state.history_repo.get_history(market_id, limit, params.hours).await?;
+    let resolution = match params.resolution.as_deref() {
+        Some(r) => r.parse::<Resolution>().map_err(ApiError::BadRequest)?,
+        None => Resolution::for_span(params.hours),
+    };
+
+    let history = app_state
+        .history_repo
+        .get_history(market_id, limit, params.hours, resolution, params.include_anomalies)
+        .await?;
     Ok(Json(history))
 }
+
+/// Several markets' YES prices on a common grid from `from` to `to`, each
+/// carried forward from its last snapshot. Flagged anomalies are left out
+/// unless `include_anomalies`.
+pub async fn get_aligned_history(
+    State(app_state): State<AppState>,
+    Query(params): Query<AlignedQuery>,
+) -> ApiResult<Json<AlignedHistory>> {
+    let interval = series::parse_interval(&params.interval).map_err(ApiError::BadRequest)?;
+    let max_age = params
+        .max_age
+        .as_deref()
+        .map(series::parse_interval)
+        .transpose()
+        .map_err(ApiError::BadRequest)?;
+
+    let to = params.to.unwrap_or_else(Utc::now);
+    let from = params.from.unwrap_or(to - Duration::hours(24));
+    if from > to {
+        return Err(ApiError::BadRequest("`from` must not be after `to`".to_string()));
+    }
+
+    let grid = series::grid(from, to, interval, MAX_POINTS).map_err(|too_many| {
+        ApiError::BadRequest(format!(
+            "{} to {} at {} is {}; use a longer interval",
+            from, to, params.interval, too_many
+        ))
+    })?;
+
+    let ids = parse_market_ids(params.market_ids.as_deref())?;
+    if ids.is_empty() || ids.len() > MAX_MARKETS {
+        return Err(ApiError::BadRequest(format!(
+            "Pass between 1 and {} market_ids",
+            MAX_MARKETS
+        )));
+    }
+
+    let refs: Vec<MarketRef> = ids.iter().copied().map(MarketRef::Id).collect();
+    let found = app_state.market_repo.lookup(&refs).await?;
+    let mut markets = Vec::with_capacity(ids.len());
+    for id in &ids {
+        match found.iter().find(|m| m.id == *id) {
+            Some(market) => markets.push(market),
+            None => return Err(ApiError::NotFound(format!("Market {} not found", id))),
+        }
+    }
+
+    let resolution = Resolution::for_interval(interval);
+    let anomalies = params.include_anomalies;
+    let start = grid.first().copied().unwrap_or(from);
+    let seed_from = start - max_age.unwrap_or(SEED_LOOKBACK);
+    let mut columns = Vec::with_capacity(markets.len());
+    for market in &markets {
+        let history = &app_state.history_repo;
+        let mut snapshots = history
+            .history_between(market.id, start, to, HISTORY_LIMIT, resolution, anomalies)
+            .await?;
+        // The last price before the grid starts, to carry into its first point
+        snapshots.extend(
+            history
+                .history_between(market.id, seed_from, start, 1, resolution, anomalies)
+                .await?,
+        );
+
+        columns.push(
+            series::resample(&snapshots, &grid)
+                .into_iter()
+                .zip(&grid)
+                .map(|(snapshot, at)| {
+                    snapshot
+                        .filter(|s| max_age.is_none_or(|age| *at - s.recorded_at <= age))
+                        .map(|s| s.yes_price)
+                })
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let rows = grid
+        .iter()
+        .enumerate()
+        .map(|(i, at)| AlignedRow {
+            at: *at,
+            prices: columns.iter().map(|column| column[i]).collect(),
+        })
+        .collect();
+
+    let markets = markets
+        .into_iter()
+        .map(|m| MarketLabel {
+            id: m.id,
+            source: m.source,
+            title: m.title.clone(),
+        })
+        .collect();
+
+    Ok(Json(AlignedHistory {
+        from,
+        to,
+        interval_seconds: interval.num_seconds(),
+        markets,
+        rows,
+    }))
+}
+
+/// Snapshots of a market flagged as anomalies, newest first
+pub async fn get_anomalies(
+    State(app_state): State<AppState>,
+    Path(market_id): Path<Uuid>,
+    Query(params): Query<AnomaliesQuery>,
+) -> ApiResult<Json<Vec<Anomaly>>> {
+    // Surface NotFound for unknown markets rather than an empty list
+    app_state.market_repo.get_by_id(market_id).await?;
+
+    let since = params.hours.map(|hours| Utc::now() - Duration::hours(hours));
+    let anomalies = app_state
+        .anomaly_repo
+        .anomalies(Some(market_id), since, params.limit.clamp(0, 1000))
+        .await?;
+
+    Ok(Json(anomalies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::{AnomalyStore, HistoryStore, MarketStore, MemoryStore, Snapshot};
+    use pm_shared::{CreateMarket, Decimal, MarketSource, MarketStatus};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn returns_recorded_snapshots() {
+        let store = Arc::new(MemoryStore::new());
+        let market = store
+            .upsert(CreateMarket {
+                source_id: "TICKER".to_string(),
+                source: MarketSource::Kalshi,
+                title: "Market".to_string(),
+                description: String::new(),
+                category: None,
+                source_category: None,
+                tags: None,
+                yes_price: Decimal::new(3, 1),
+                no_price: Decimal::new(7, 1),
+                volume: Decimal::ZERO,
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                status: MarketStatus::Open,
+                close_at: None,
+                url: String::new(),
+            })
+            .await
+            .unwrap();
+        store.record_snapshot(&market, market.updated_at).await.unwrap();
+        let state = AppState::in_memory(store);
+
+        let params = HistoryQuery {
+            limit: default_limit(),
+            hours: Some(24),
+            resolution: None,
+            include_anomalies: false,
+        };
+        let Json(history) = get_price_history(State(state), Path(market.id), Query(params))
+            .await
+            .unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].yes_price, Decimal::new(3, 1));
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_resolution() {
+        let state = AppState::in_memory(Arc::new(MemoryStore::new()));
+
+        let params = HistoryQuery {
+            limit: default_limit(),
+            hours: None,
+            resolution: Some("weekly".to_string()),
+            include_anomalies: false,
+        };
+        let result = get_price_history(State(state), Path(Uuid::new_v4()), Query(params)).await;
+
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn aligns_markets_on_a_common_grid() {
+        let store = Arc::new(MemoryStore::new());
+        let mut ids = Vec::new();
+        for source_id in ["A", "B"] {
+            let market = store
+                .upsert(CreateMarket {
+                    source_id: source_id.to_string(),
+                    source: MarketSource::Kalshi,
+                    title: format!("Market {}", source_id),
+                    description: String::new(),
+                    category: None,
+                    source_category: None,
+                    tags: None,
+                    yes_price: Decimal::new(5, 1),
+                    no_price: Decimal::new(5, 1),
+                    volume: Decimal::ZERO,
+                    volume_24h: Decimal::ZERO,
+                    liquidity: None,
+                    status: MarketStatus::Open,
+                    close_at: None,
+                    url: String::new(),
+                })
+                .await
+                .unwrap();
+            ids.push(market.id);
+        }
+
+        let snapshot = |market: usize, at: &str, yes_cents: i64| Snapshot {
+            market_id: ids[market],
+            yes_price: Decimal::new(yes_cents, 2),
+            no_price: Decimal::ONE - Decimal::new(yes_cents, 2),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at.parse().unwrap(),
+        };
+        store
+            .import_snapshots(&[
+                snapshot(0, "2024-05-31T23:50:00Z", 40),
+                snapshot(0, "2024-06-01T00:20:00Z", 50),
+                snapshot(0, "2024-06-01T00:40:00Z", 90),
+                snapshot(1, "2024-06-01T00:31:00Z", 30),
+            ])
+            .await
+            .unwrap();
+        store
+            .flag(&[Anomaly {
+                market_id: ids[0],
+                recorded_at: "2024-06-01T00:40:00Z".parse().unwrap(),
+                kind: "price_spike".to_string(),
+                score: Some(9.0),
+                detail: String::new(),
+                detected_at: Utc::now(),
+            }])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let query = |max_age: Option<&str>| AlignedQuery {
+            market_ids: Some(format!("{},{}", ids[0], ids[1])),
+            interval: "15m".to_string(),
+            from: Some("2024-06-01T00:00:00Z".parse().unwrap()),
+            to: Some("2024-06-01T01:00:00Z".parse().unwrap()),
+            max_age: max_age.map(String::from),
+            include_anomalies: false,
+        };
+        let cents = |table: &AlignedHistory, column: usize| -> Vec<Option<i64>> {
+            let cents = |p: Decimal| (p * Decimal::ONE_HUNDRED).try_into().unwrap();
+            table.rows.iter().map(|row| row.prices[column].map(cents)).collect()
+        };
+
+        let Json(table) = get_aligned_history(State(state.clone()), Query(query(None)))
+            .await
+            .unwrap();
+        assert_eq!(table.rows.len(), 5);
+        assert_eq!(table.interval_seconds, 15 * 60);
+        // Seeded from before the grid, spike left out
+        assert_eq!(cents(&table, 0), [Some(40), Some(40), Some(50), Some(50), Some(50)]);
+        assert_eq!(cents(&table, 1), [None, None, None, Some(30), Some(30)]);
+
+        let Json(strict) = get_aligned_history(State(state.clone()), Query(query(Some("20m"))))
+            .await
+            .unwrap();
+        assert_eq!(cents(&strict, 0), [Some(40), None, Some(50), None, None]);
+        assert_eq!(cents(&strict, 1), [None, None, None, Some(30), None]);
+
+        let unknown = get_aligned_history(
+            State(state),
+            Query(AlignedQuery {
+                market_ids: Some(Uuid::new_v4().to_string()),
+                ..query(None)
+            }),
+        )
+        .await;
+        assert!(matches!(unknown, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn hourly_grid_only_sees_prices_recorded_by_each_point() {
+        let store = Arc::new(MemoryStore::new());
+        let market = store
+            .upsert(CreateMarket {
+                source_id: "A".to_string(),
+                source: MarketSource::Kalshi,
+                title: "Market A".to_string(),
+                description: String::new(),
+                category: None,
+                source_category: None,
+                tags: None,
+                yes_price: Decimal::new(5, 1),
+                no_price: Decimal::new(5, 1),
+                volume: Decimal::ZERO,
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                status: MarketStatus::Open,
+                close_at: None,
+                url: String::new(),
+            })
+            .await
+            .unwrap();
+        let snapshot = |at: &str, yes_cents: i64| Snapshot {
+            market_id: market.id,
+            yes_price: Decimal::new(yes_cents, 2),
+            no_price: Decimal::ONE - Decimal::new(yes_cents, 2),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at.parse().unwrap(),
+        };
+        store
+            .import_snapshots(&[
+                snapshot("2024-06-01T10:05:00Z", 40),
+                snapshot("2024-06-01T10:50:00Z", 60),
+            ])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let query = |max_age: Option<&str>| AlignedQuery {
+            market_ids: Some(market.id.to_string()),
+            interval: "1h".to_string(),
+            from: Some("2024-06-01T10:00:00Z".parse().unwrap()),
+            to: Some("2024-06-01T12:00:00Z".parse().unwrap()),
+            max_age: max_age.map(String::from),
+            include_anomalies: false,
+        };
+        let cents = |table: &AlignedHistory| -> Vec<Option<i64>> {
+            let cents = |p: Decimal| (p * Decimal::ONE_HUNDRED).try_into().unwrap();
+            table.rows.iter().map(|row| row.prices[0].map(cents)).collect()
+        };
+
+        let Json(table) = get_aligned_history(State(state.clone()), Query(query(None)))
+            .await
+            .unwrap();
+        assert_eq!(cents(&table), [None, Some(60), Some(60)]);
+
+        // Age runs from 10:50, not from the start of its hour
+        let Json(strict) = get_aligned_history(State(state), Query(query(Some("1h"))))
+            .await
+            .unwrap();
+        assert_eq!(cents(&strict), [None, Some(60), None]);
+    }
+
+    #[tokio::test]
+    async fn refuses_a_grid_spanning_all_time() {
+        let state = AppState::in_memory(Arc::new(MemoryStore::new()));
+
+        let params = AlignedQuery {
+            market_ids: Some(Uuid::new_v4().to_string()),
+            interval: "1s".to_string(),
+            from: Some(DateTime::<Utc>::MIN_UTC),
+            to: Some(DateTime::<Utc>::MAX_UTC),
+            max_age: None,
+            include_anomalies: false,
+        };
+        let result = get_aligned_history(State(state), Query(params)).await;
+
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    }
+}
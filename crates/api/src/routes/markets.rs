@@ -1,12 +1,18 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{rejection::JsonRejection, Path, Query, State},
     Json,
 };
+use chrono::{DateTime, Duration, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::{error::ApiResult, AppState};
-use pm_shared::Market;
+use crate::{error::{ApiError, ApiResult}, routes::search::market_filter, AppState};
+use pm_shared::store::{MarketFilter, MarketSort, SortOrder, StoreError};
+use pm_shared::{
+    stats, Equivalent, Market, MarketBatch, MarketDetail, MarketMetrics, MarketRef,
+    MarketRevision, MarketSource, Resolution,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct ListQuery {
@@ -18,6 +24,82 @@ pub struct ListQuery {
     pub sort: String,
     #[serde(default = "default_order")]
     pub order: String,
+    /// Canonical category, e.g. politics
+    pub category: Option<String>,
+    /// Exact tag, e.g. asset:btc
+    pub tag: Option<String>,
+    /// Show markets as they were at this time
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarketQuery {
+    pub as_of: Option<DateTime<Utc>>,
+    /// Comma-separated extras: stats, equivalents, revisions, metrics
+    pub include: Option<String>,
+}
+
+/// Extras `GET /api/markets/:id` can return alongside the market
+#[derive(Debug, Default)]
+struct Include {
+    stats: bool,
+    equivalents: bool,
+    revisions: bool,
+    metrics: bool,
+}
+
+impl Include {
+    fn parse(include: Option<&str>) -> ApiResult<Self> {
+        let mut parsed = Self::default();
+        for part in include.unwrap_or_default().split(',').map(str::trim) {
+            match part.to_lowercase().as_str() {
+                "" => {}
+                "stats" => parsed.stats = true,
+                "equivalents" => parsed.equivalents = true,
+                "revisions" => parsed.revisions = true,
+                "metrics" => parsed.metrics = true,
+                _ => {
+                    return Err(ApiError::BadRequest(format!(
+                        "Unknown include: {} (expected stats, equivalents, revisions or metrics)",
+                        part
+                    )))
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn any(&self) -> bool {
+        self.stats || self.equivalents || self.revisions || self.metrics
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    /// Market IDs or `{source, source_id}` pairs
+    pub markets: Vec<MarketRef>,
+}
+
+/// Most references one batch lookup takes
+const MAX_BATCH: usize = 500;
+
+/// Revisions returned with `include=revisions`
+const DETAIL_REVISIONS: i64 = 10;
+
+/// Equivalents returned with `include=equivalents`
+const MAX_EQUIVALENTS: usize = 5;
+
+/// Title similarity below which a market on the other venue isn't listed
+const MIN_SIMILARITY: f64 = 0.3;
+
+#[derive(Debug, Deserialize)]
+pub struct RevisionsQuery {
+    #[serde(default = "default_revisions_limit")]
+    pub limit: usize,
+}
+
+fn default_revisions_limit() -> usize {
+    50
 }
 
 fn default_limit() -> i64 {
@@ -37,14 +119,778 @@ pub async fn list_markets(
     Query(params): Query<ListQuery>,
 ) -> ApiResult<Json<Vec<Market>>> {
     let limit = params.limit.min(100);
-    let markets = app_state.market_repo.list(limit, params.offset, &params.sort, &params.order).await?;
-    Ok(Json(markets))
+    let sort = params.sort.parse::<MarketSort>().map_err(ApiError::BadRequest)?;
+    let order = params.order.parse::<SortOrder>().map_err(ApiError::BadRequest)?;
+    let filter = MarketFilter {
+        as_of: params.as_of,
+        ..market_filter(params.category.as_deref(), params.tag.as_deref())?
+    };
+    let markets = app_state
+        .market_repo
+        .list(limit, params.offset, sort, order, &filter)
+        .await?;
+
+    match params.as_of {
+        Some(at) => Ok(Json(as_of(&app_state, markets, at).await?)),
+        None => Ok(Json(markets)),
+    }
 }
 
 pub async fn get_market(
     State(app_state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> ApiResult<Json<Market>> {
+    Query(params): Query<MarketQuery>,
+) -> ApiResult<Json<MarketDetail>> {
+    let include = Include::parse(params.include.as_deref())?;
     let market = app_state.market_repo.get_by_id(id).await?;
-    Ok(Json(market))
+
+    let market = match params.as_of {
+        // Extras describe the market now, so they don't mix with a rewind
+        Some(_) if include.any() => {
+            return Err(ApiError::BadRequest(
+                "include cannot be combined with as_of".to_string(),
+            ))
+        }
+        Some(at) => as_of(&app_state, vec![market], at)
+            .await?
+            .pop()
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("Market {} has no snapshot as of {}", id, at))
+            })?,
+        None => market,
+    };
+
+    let mut detail = MarketDetail {
+        market,
+        stats: None,
+        equivalents: None,
+        revisions: None,
+        metrics: None,
+    };
+
+    if include.stats {
+        let now = Utc::now();
+        let history_repo = &app_state.history_repo;
+        let span = history_repo.span(id).await?;
+        let history = history_repo
+            .get_history(id, 10_000, Some(stats::RANGE.num_hours()), Resolution::Raw, false)
+            .await?;
+        // Changes reach back a week, too far to read raw history for at a
+        // short collection interval, so their ends are looked up directly
+        let at = |ago: Duration| history_repo.snapshots_at(std::slice::from_ref(&id), now - ago);
+        let [hour, day, week] = stats::Anchors::OFFSETS;
+        let anchors = stats::Anchors {
+            latest: at(Duration::zero()).await?.pop(),
+            hour_ago: at(hour).await?.pop(),
+            day_ago: at(day).await?.pop(),
+            week_ago: at(week).await?.pop(),
+        };
+        detail.stats = Some(stats::compute(span, &history, &anchors, now));
+    }
+
+    if include.equivalents {
+        detail.equivalents = Some(equivalents(&app_state, &detail.market).await?);
+    }
+
+    if include.revisions {
+        let revisions = app_state
+            .market_repo
+            .latest_revisions(id, DETAIL_REVISIONS)
+            .await?;
+        detail.revisions = Some(revisions);
+    }
+
+    if include.metrics {
+        detail.metrics = match app_state.metrics_repo.metrics(id).await {
+            Ok(metrics) => Some(metrics),
+            Err(StoreError::NotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+    }
+
+    Ok(Json(detail))
+}
+
+/// `GET /api/markets/:id` for a market known by its venue identity, e.g.
+/// `/api/markets/by-source/kalshi/FED-25DEC`
+pub async fn get_market_by_source(
+    State(app_state): State<AppState>,
+    Path((source, source_id)): Path<(String, String)>,
+    query: Query<MarketQuery>,
+) -> ApiResult<Json<MarketDetail>> {
+    let source: MarketSource = source.parse().map_err(ApiError::BadRequest)?;
+    let market = match app_state.market_repo.get_by_source(source, &source_id).await {
+        Ok(market) => market,
+        Err(StoreError::NotFound) => {
+            return Err(ApiError::NotFound(format!(
+                "No {} market with id {}",
+                source, source_id
+            )))
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    get_market(State(app_state), Path(market.id), query).await
+}
+
+/// Look up many markets at once by ID or venue identity. Markets come back in
+/// request order; references that match nothing are listed as missing.
+pub async fn batch_markets(
+    State(app_state): State<AppState>,
+    request: Result<Json<BatchRequest>, JsonRejection>,
+) -> ApiResult<Json<MarketBatch>> {
+    let Json(request) = request.map_err(|e| ApiError::BadRequest(e.body_text()))?;
+    if request.markets.len() > MAX_BATCH {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} markets per batch, got {}",
+            MAX_BATCH,
+            request.markets.len()
+        )));
+    }
+
+    let found = app_state.market_repo.lookup(&request.markets).await?;
+
+    let mut batch = MarketBatch {
+        markets: Vec::with_capacity(request.markets.len()),
+        missing: Vec::new(),
+    };
+    for market_ref in request.markets {
+        match found.iter().find(|m| market_ref.matches(m)) {
+            Some(market) => batch.markets.push(market.clone()),
+            None => batch.missing.push(market_ref),
+        }
+    }
+
+    Ok(Json(batch))
+}
+
+/// Markets on the other venue whose titles resemble `market`'s, most similar
+/// first. Candidates are the other venue's highest-volume markets sharing its
+/// category, or its first tag when it has none; unclassified markets have no
+/// equivalents.
+async fn equivalents(app_state: &AppState, market: &Market) -> ApiResult<Vec<Equivalent>> {
+    let other_venue = match market.source {
+        MarketSource::Polymarket => MarketSource::Kalshi,
+        MarketSource::Kalshi => MarketSource::Polymarket,
+    };
+    let filter = match (market.category, market.tags.as_deref()) {
+        (Some(category), _) => MarketFilter {
+            category: Some(category),
+            tag: None,
+            source: Some(other_venue),
+            as_of: None,
+        },
+        (None, Some([tag, ..])) => MarketFilter {
+            category: None,
+            tag: Some(tag.clone()),
+            source: Some(other_venue),
+            as_of: None,
+        },
+        _ => return Ok(Vec::new()),
+    };
+
+    let candidates = app_state
+        .market_repo
+        .list(500, 0, MarketSort::Volume, SortOrder::Desc, &filter)
+        .await?;
+
+    let mut equivalents: Vec<Equivalent> = candidates
+        .into_iter()
+        .map(|candidate| Equivalent {
+            similarity: stats::title_similarity(&market.title, &candidate.title),
+            market: candidate,
+        })
+        .filter(|e| e.similarity >= MIN_SIMILARITY)
+        .collect();
+
+    equivalents.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    equivalents.truncate(MAX_EQUIVALENTS);
+
+    Ok(equivalents)
+}
+
+/// Metadata changes recorded for a market, newest first
+pub async fn get_market_revisions(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RevisionsQuery>,
+) -> ApiResult<Json<Vec<MarketRevision>>> {
+    // Surface NotFound for unknown markets rather than an empty list
+    app_state.market_repo.get_by_id(id).await?;
+
+    let revisions = app_state
+        .market_repo
+        .latest_revisions(id, params.limit.min(500) as i64)
+        .await?;
+
+    Ok(Json(revisions))
+}
+
+/// Volatility, momentum and volume metrics last computed by the worker
+pub async fn get_market_metrics(
+    State(app_state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<Json<MarketMetrics>> {
+    // Surface NotFound for unknown markets before missing metrics
+    app_state.market_repo.get_by_id(id).await?;
+
+    match app_state.metrics_repo.metrics(id).await {
+        Ok(metrics) => Ok(Json(metrics)),
+        Err(StoreError::NotFound) => Err(ApiError::NotFound(format!(
+            "No metrics computed for market {} yet",
+            id
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Rewind `markets` to their state at `at`: prices and volume from the latest
+/// snapshot at or before it, metadata from the revisions made since. Markets
+/// with no snapshot by then are dropped.
+///
+/// Selection and ordering are done on current values before rewinding.
+pub(crate) async fn as_of(
+    app_state: &AppState,
+    markets: Vec<Market>,
+    at: DateTime<Utc>,
+) -> ApiResult<Vec<Market>> {
+    let ids: Vec<Uuid> = markets.iter().map(|m| m.id).collect();
+
+    let snapshots: HashMap<Uuid, _> = app_state
+        .history_repo
+        .snapshots_at(&ids, at)
+        .await?
+        .into_iter()
+        .map(|s| (s.market_id, s))
+        .collect();
+    let revisions = app_state.market_repo.revisions(&ids, Some(at)).await?;
+
+    Ok(markets
+        .into_iter()
+        .filter_map(|market| {
+            let snapshot = snapshots.get(&market.id)?;
+            let later: Vec<_> = revisions
+                .iter()
+                .filter(|r| r.market_id == market.id)
+                .cloned()
+                .collect();
+            Some(market.as_of(snapshot, &later))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::{HistoryStore, MarketStore, MemoryStore, MetricsStore, Snapshot};
+    use pm_shared::{Category, CreateMarket, Decimal, MarketChange, MarketSource, MarketStatus};
+    use std::sync::Arc;
+
+    fn market(source_id: &str, volume: i64) -> CreateMarket {
+        CreateMarket {
+            source_id: source_id.to_string(),
+            source: MarketSource::Polymarket,
+            title: format!("Market {}", source_id),
+            description: String::new(),
+            category: None,
+            source_category: None,
+            tags: None,
+            yes_price: Decimal::new(5, 1),
+            no_price: Decimal::new(5, 1),
+            volume: Decimal::from(volume),
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    fn query(sort: &str, order: &str) -> ListQuery {
+        ListQuery {
+            limit: default_limit(),
+            offset: 0,
+            sort: sort.to_string(),
+            order: order.to_string(),
+            category: None,
+            tag: None,
+            as_of: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_markets_in_requested_order() {
+        let store = Arc::new(MemoryStore::new());
+        for (id, volume) in [("a", 5), ("b", 50), ("c", 20)] {
+            store.upsert(market(id, volume)).await.unwrap();
+        }
+        let state = AppState::in_memory(store);
+
+        let Json(markets) = list_markets(State(state.clone()), Query(query("volume", "desc")))
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = markets.iter().map(|m| m.source_id.as_str()).collect();
+        assert_eq!(ids, ["b", "c", "a"]);
+
+        for (sort, order) in [("popularity", "desc"), ("volume", "up")] {
+            let unknown = list_markets(State(state.clone()), Query(query(sort, order))).await;
+            assert!(matches!(unknown, Err(ApiError::BadRequest(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_by_category_and_tag() {
+        let store = Arc::new(MemoryStore::new());
+        for (id, category, tags) in [
+            ("a", Category::Crypto, vec!["asset:btc"]),
+            ("b", Category::Crypto, vec!["asset:eth"]),
+            ("c", Category::Politics, vec!["asset:btc", "person:trump"]),
+        ] {
+            store
+                .upsert(CreateMarket {
+                    category: Some(category),
+                    tags: Some(tags.into_iter().map(String::from).collect()),
+                    ..market(id, 1)
+                })
+                .await
+                .unwrap();
+        }
+        let state = AppState::in_memory(store);
+
+        let filtered = |category: Option<&str>, tag: Option<&str>| ListQuery {
+            category: category.map(String::from),
+            tag: tag.map(String::from),
+            ..query("created_at", "asc")
+        };
+
+        let Json(crypto) = list_markets(State(state.clone()), Query(filtered(Some("crypto"), None)))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = crypto.iter().map(|m| m.source_id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+
+        let Json(btc) = list_markets(
+            State(state.clone()),
+            Query(filtered(Some("Crypto"), Some("asset:btc"))),
+        )
+        .await
+        .unwrap();
+        assert_eq!(btc.len(), 1);
+        assert_eq!(btc[0].source_id, "a");
+
+        let unknown = list_markets(State(state), Query(filtered(Some("elections"), None))).await;
+        assert!(matches!(unknown, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn sorts_by_metrics_and_serves_them() {
+        let store = Arc::new(MemoryStore::new());
+        let mut ids = Vec::new();
+        for id in ["a", "b", "c"] {
+            ids.push(store.upsert(market(id, 1)).await.unwrap().id);
+        }
+        let metrics = |market_id: Uuid, volatility: f64| MarketMetrics {
+            market_id,
+            volatility_24h: Some(volatility),
+            volatility_7d: None,
+            volatility_30d: None,
+            momentum_24h: None,
+            momentum_7d: None,
+            max_drawdown_30d: None,
+            last_move_at: None,
+            volume_acceleration: None,
+            samples: 24,
+            computed_at: Utc::now(),
+        };
+        // "c" has none yet
+        store
+            .save_metrics(&[metrics(ids[0], 0.2), metrics(ids[1], 0.9)])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        for order in ["desc", "asc"] {
+            let Json(markets) =
+                list_markets(State(state.clone()), Query(query("volatility_24h", order)))
+                    .await
+                    .unwrap();
+            let sorted: Vec<&str> = markets.iter().map(|m| m.source_id.as_str()).collect();
+            let expected = if order == "desc" { ["b", "a", "c"] } else { ["a", "b", "c"] };
+            assert_eq!(sorted, expected);
+        }
+
+        let Json(served) = get_market_metrics(State(state.clone()), Path(ids[1]))
+            .await
+            .unwrap();
+        assert_eq!(served.volatility_24h, Some(0.9));
+
+        let missing = get_market_metrics(State(state), Path(ids[2])).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn unknown_market_is_not_found() {
+        let state = AppState::in_memory(Arc::new(MemoryStore::new()));
+
+        let result = get_market(
+            State(state),
+            Path(Uuid::new_v4()),
+            Query(MarketQuery { as_of: None, include: None }),
+        )
+        .await;
+
+        assert!(matches!(result, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn include_adds_stats_and_equivalents() {
+        let store = Arc::new(MemoryStore::new());
+        let crypto = |source_id: &str, source: MarketSource, title: &str| CreateMarket {
+            source,
+            title: title.to_string(),
+            category: Some(Category::Crypto),
+            ..market(source_id, 1)
+        };
+        let current = store
+            .upsert(crypto("a", MarketSource::Polymarket, "Bitcoin above $100k on Friday?"))
+            .await
+            .unwrap();
+        store
+            .upsert(crypto("b", MarketSource::Kalshi, "Will Bitcoin be above $100k Friday"))
+            .await
+            .unwrap();
+        store
+            .upsert(crypto("c", MarketSource::Kalshi, "Ethereum flips Bitcoin this year"))
+            .await
+            .unwrap();
+        store
+            .upsert(crypto("d", MarketSource::Polymarket, "Bitcoin above $100k on Friday"))
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        let snapshot = |hours_ago: i64, yes_cents: i64| Snapshot {
+            market_id: current.id,
+            yes_price: Decimal::new(yes_cents, 2),
+            no_price: Decimal::ONE - Decimal::new(yes_cents, 2),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: now - Duration::hours(hours_ago),
+        };
+        store
+            .import_snapshots(&[snapshot(30, 40), snapshot(2, 55), snapshot(0, 60)])
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let include = |include: &str| {
+            Query(MarketQuery {
+                as_of: None,
+                include: Some(include.to_string()),
+            })
+        };
+
+        let Json(detail) = get_market(
+            State(state.clone()),
+            Path(current.id),
+            include("stats,equivalents"),
+        )
+        .await
+        .unwrap();
+
+        let stats = detail.stats.unwrap();
+        assert_eq!(stats.snapshot_count, 3);
+        assert_eq!(stats.first_snapshot_at, Some(now - Duration::hours(30)));
+        assert_eq!(stats.change_1h, Some(Decimal::new(5, 2)));
+        assert_eq!(stats.change_24h, Some(Decimal::new(20, 2)));
+        assert_eq!(stats.high_24h, Some(Decimal::new(60, 2)));
+        // Only the other venue's closely worded market
+        let equivalents = detail.equivalents.unwrap();
+        assert_eq!(equivalents.len(), 1);
+        assert_eq!(equivalents[0].market.source_id, "b");
+        assert!(detail.revisions.is_none());
+
+        let unknown = get_market(State(state.clone()), Path(current.id), include("charts")).await;
+        assert!(matches!(unknown, Err(ApiError::BadRequest(_))));
+
+        let rewound = get_market(
+            State(state),
+            Path(current.id),
+            Query(MarketQuery {
+                as_of: Some(now),
+                include: Some("stats".to_string()),
+            }),
+        )
+        .await;
+        assert!(matches!(rewound, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn week_change_holds_with_a_snapshot_every_minute() {
+        let store = Arc::new(MemoryStore::new());
+        let current = store.upsert(market("a", 1)).await.unwrap();
+        let now = Utc::now();
+        // Eight days of minutes, more than a single history read returns
+        let snapshots: Vec<Snapshot> = (0..8 * 24 * 60)
+            .map(|minutes_ago| {
+                let yes_price = if minutes_ago >= 7 * 24 * 60 { 30 } else { 50 };
+                Snapshot {
+                    market_id: current.id,
+                    yes_price: Decimal::new(yes_price, 2),
+                    no_price: Decimal::ONE - Decimal::new(yes_price, 2),
+                    volume: Decimal::ZERO,
+                    volume_24h: Decimal::ZERO,
+                    liquidity: None,
+                    recorded_at: now - Duration::minutes(minutes_ago),
+                }
+            })
+            .collect();
+        store.import_snapshots(&snapshots).await.unwrap();
+
+        let Json(detail) = get_market(
+            State(AppState::in_memory(store)),
+            Path(current.id),
+            Query(MarketQuery {
+                as_of: None,
+                include: Some("stats".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let stats = detail.stats.unwrap();
+        assert_eq!(stats.change_7d, Some(Decimal::new(20, 2)));
+        assert_eq!(stats.change_24h, Some(Decimal::ZERO));
+    }
+
+    #[tokio::test]
+    async fn equivalents_are_found_past_busier_markets_on_the_same_venue() {
+        let store = Arc::new(MemoryStore::new());
+        let crypto = |source_id: &str, source: MarketSource, title: &str, volume: i64| {
+            CreateMarket {
+                source,
+                title: title.to_string(),
+                category: Some(Category::Crypto),
+                ..market(source_id, volume)
+            }
+        };
+        let current = store
+            .upsert(crypto("a", MarketSource::Polymarket, "Bitcoin above $100k on Friday?", 1))
+            .await
+            .unwrap();
+        for i in 0..500 {
+            let title = format!("Solana token {} listed", i);
+            store
+                .upsert(crypto(&format!("p{}", i), MarketSource::Polymarket, &title, 1_000 + i))
+                .await
+                .unwrap();
+        }
+        store
+            .upsert(crypto("b", MarketSource::Kalshi, "Will Bitcoin be above $100k Friday", 1))
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let Json(detail) = get_market(
+            State(state),
+            Path(current.id),
+            Query(MarketQuery {
+                as_of: None,
+                include: Some("equivalents".to_string()),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let equivalents = detail.equivalents.unwrap();
+        assert_eq!(equivalents.len(), 1);
+        assert_eq!(equivalents[0].market.source_id, "b");
+    }
+
+    #[tokio::test]
+    async fn looks_markets_up_by_id_and_venue_identity() {
+        let store = Arc::new(MemoryStore::new());
+        let a = store.upsert(market("a", 1)).await.unwrap();
+        let b = store
+            .upsert(CreateMarket {
+                source: MarketSource::Kalshi,
+                ..market("FED-25DEC", 1)
+            })
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let refs = ["kalshi:FED-25DEC", &a.id.to_string(), "kalshi:NOPE", "polymarket:FED-25DEC"]
+            .map(|r| r.parse::<MarketRef>().unwrap());
+        let Json(batch) = batch_markets(
+            State(state.clone()),
+            Ok(Json(BatchRequest {
+                markets: refs.to_vec(),
+            })),
+        )
+        .await
+        .unwrap();
+
+        let found: Vec<Uuid> = batch.markets.iter().map(|m| m.id).collect();
+        assert_eq!(found, [b.id, a.id]);
+        assert_eq!(batch.missing, refs[2..]);
+
+        let Json(detail) = get_market_by_source(
+            State(state.clone()),
+            Path(("Kalshi".to_string(), "FED-25DEC".to_string())),
+            Query(MarketQuery { as_of: None, include: None }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(detail.market.id, b.id);
+
+        let lookup = |source: &str, source_id: &str| {
+            get_market_by_source(
+                State(state.clone()),
+                Path((source.to_string(), source_id.to_string())),
+                Query(MarketQuery { as_of: None, include: None }),
+            )
+        };
+        assert!(matches!(lookup("kalshi", "NOPE").await, Err(ApiError::NotFound(_))));
+        assert!(matches!(lookup("manifold", "X").await, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn lists_revisions_newest_first() {
+        let store = Arc::new(MemoryStore::new());
+        let current = store.upsert(market("a", 1)).await.unwrap();
+        let now = Utc::now();
+
+        for (hours_ago, old, new) in [(2, "open", "closed"), (1, "closed", "resolved")] {
+            store
+                .record_revisions(
+                    current.id,
+                    &[MarketChange {
+                        field: "status",
+                        old_value: Some(old.to_string()),
+                        new_value: Some(new.to_string()),
+                    }],
+                    now - Duration::hours(hours_ago),
+                )
+                .await
+                .unwrap();
+        }
+        let state = AppState::in_memory(store);
+
+        let Json(revisions) = get_market_revisions(
+            State(state.clone()),
+            Path(current.id),
+            Query(RevisionsQuery { limit: 1 }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].new_value.as_deref(), Some("resolved"));
+
+        let unknown = get_market_revisions(
+            State(state),
+            Path(Uuid::new_v4()),
+            Query(RevisionsQuery { limit: 1 }),
+        )
+        .await;
+        assert!(matches!(unknown, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn as_of_rewinds_prices_and_metadata() {
+        let store = Arc::new(MemoryStore::new());
+        let current = store.upsert(market("a", 80)).await.unwrap();
+        let now = Utc::now();
+
+        let snapshot = |hours_ago: i64, yes_cents: i64, volume: i64| Snapshot {
+            market_id: current.id,
+            yes_price: Decimal::new(yes_cents, 2),
+            no_price: Decimal::ONE - Decimal::new(yes_cents, 2),
+            volume: Decimal::from(volume),
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: now - Duration::hours(hours_ago),
+        };
+        store
+            .import_snapshots(&[snapshot(48, 20, 10), snapshot(24, 30, 40)])
+            .await
+            .unwrap();
+        store
+            .record_revisions(
+                current.id,
+                &[MarketChange {
+                    field: "status",
+                    old_value: Some("closed".to_string()),
+                    new_value: Some("open".to_string()),
+                }],
+                now - Duration::hours(30),
+            )
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let at = |hours_ago: i64| {
+            Query(MarketQuery {
+                as_of: Some(now - Duration::hours(hours_ago)),
+                include: None,
+            })
+        };
+
+        let Json(then) = get_market(State(state.clone()), Path(current.id), at(36))
+            .await
+            .unwrap();
+        assert_eq!(then.market.yes_price, Decimal::new(20, 2));
+        assert_eq!(then.market.volume, Decimal::from(10));
+        assert_eq!(then.market.status, MarketStatus::Closed);
+
+        let Json(later) = get_market(State(state.clone()), Path(current.id), at(12))
+            .await
+            .unwrap();
+        assert_eq!(later.market.yes_price, Decimal::new(30, 2));
+        assert_eq!(later.market.status, MarketStatus::Open);
+
+        let before_history = get_market(State(state), Path(current.id), at(72)).await;
+        assert!(matches!(before_history, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn as_of_listing_pages_by_volume_back_then() {
+        let store = Arc::new(MemoryStore::new());
+        let now = Utc::now();
+        let mut snapshots = Vec::new();
+        // Current volumes reverse the order they had a day ago; "new" didn't exist yet
+        for (id, volume_then, volume_now) in [("a", 30, 1), ("b", 20, 2), ("c", 10, 3)] {
+            let market = store.upsert(market(id, volume_now)).await.unwrap();
+            snapshots.push(Snapshot {
+                market_id: market.id,
+                yes_price: Decimal::new(5, 1),
+                no_price: Decimal::new(5, 1),
+                volume: Decimal::from(volume_then),
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                recorded_at: now - Duration::hours(30),
+            });
+        }
+        store.upsert(market("new", 100)).await.unwrap();
+        store.import_snapshots(&snapshots).await.unwrap();
+        let state = AppState::in_memory(store);
+
+        let page = |offset: i64| ListQuery {
+            limit: 2,
+            offset,
+            as_of: Some(now - Duration::hours(24)),
+            ..query("volume", "desc")
+        };
+        let ids = |markets: Vec<Market>| -> Vec<String> {
+            markets.into_iter().map(|m| m.source_id).collect()
+        };
+
+        let Json(first) = list_markets(State(state.clone()), Query(page(0))).await.unwrap();
+        let Json(second) = list_markets(State(state), Query(page(2))).await.unwrap();
+
+        assert_eq!(ids(first), ["a", "b"]);
+        assert_eq!(ids(second), ["c"]);
+    }
 }
@@ -1,3 +1,7 @@
 pub mod search;
 pub mod markets;
 pub mod history;
+pub mod admin;
+pub mod export;
+pub mod correlations;
+pub mod taxonomy;
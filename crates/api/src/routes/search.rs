@@ -2,10 +2,12 @@ use axum::{
     extract::{Query, State},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiResult, AppState};
-use pm_shared::Market;
+use crate::{error::{ApiError, ApiResult}, routes::markets::as_of, AppState};
+use pm_shared::store::MarketFilter;
+use pm_shared::{Category, Market, MarketSource, MarketStatus};
 
 #[derive(Debug, Deserialize)]
 pub struct SearchQuery {
@@ -14,12 +16,36 @@ pub struct SearchQuery {
     pub limit: i64,
     pub source: Option<String>,
     pub status: Option<String>,
+    /// Canonical category, e.g. politics
+    pub category: Option<String>,
+    /// Exact tag, e.g. person:trump
+    pub tag: Option<String>,
+    /// Show matches as they were at this time; `status` then filters on the status back then
+    pub as_of: Option<DateTime<Utc>>,
 }
 
 fn default_limit() -> i64 {
     10
 }
 
+/// Parse an optional filter value, rejecting anything the enum doesn't know
+pub(crate) fn parse_filter<T>(raw: Option<&str>) -> ApiResult<Option<T>>
+where
+    T: std::str::FromStr<Err = String>,
+{
+    raw.map(|s| s.parse::<T>().map_err(ApiError::BadRequest)).transpose()
+}
+
+/// Category and tag filters shared by market listing and search
+pub(crate) fn market_filter(category: Option<&str>, tag: Option<&str>) -> ApiResult<MarketFilter> {
+    Ok(MarketFilter {
+        category: parse_filter::<Category>(category)?,
+        tag: tag.map(|t| t.trim().to_lowercase()),
+        source: None,
+        as_of: None,
+    })
+}
+
 #[derive(Debug, Serialize)]
 pub struct SearchResponse {
     pub results: Vec<SearchResultItem>,
@@ -38,16 +64,32 @@ pub async fn search_markets(
     Query(params): Query<SearchQuery>,
 ) -> ApiResult<Json<SearchResponse>> {
     let limit = params.limit.min(100);
+    let status = parse_filter::<MarketStatus>(params.status.as_deref())?;
+    let filter = MarketFilter {
+        source: parse_filter::<MarketSource>(params.source.as_deref())?,
+        as_of: params.as_of,
+        ..market_filter(params.category.as_deref(), params.tag.as_deref())?
+    };
 
-    let results = app_state.market_repo
-        .search(
-            &params.q,
-            limit,
-            params.source.as_deref(),
-            params.status.as_deref(),
-        )
+    let mut results = app_state.market_repo
+        .search(&params.q, limit, status, &filter)
         .await?;
 
+    if let Some(at) = params.as_of {
+        let (markets, scores): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+        let scores: std::collections::HashMap<_, _> =
+            markets.iter().map(|m| m.id).zip(scores).collect();
+
+        results = as_of(&app_state, markets, at)
+            .await?
+            .into_iter()
+            .map(|m| {
+                let score = scores[&m.id];
+                (m, score)
+            })
+            .collect();
+    }
+
     let total = results.len();
     let items = results
         .into_iter()
@@ -59,3 +101,136 @@ pub async fn search_markets(
         total,
     }))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use pm_shared::store::{HistoryStore, MarketStore, MemoryStore, Snapshot};
+    use pm_shared::{CreateMarket, Decimal, MarketChange};
+    use std::sync::Arc;
+
+    fn market(source: MarketSource, title: &str) -> CreateMarket {
+        CreateMarket {
+            source_id: title.to_string(),
+            source,
+            title: title.to_string(),
+            description: String::new(),
+            category: None,
+            source_category: None,
+            tags: None,
+            yes_price: Decimal::new(5, 1),
+            no_price: Decimal::new(5, 1),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_matches_by_source() {
+        let store = Arc::new(MemoryStore::new());
+        store.upsert(market(MarketSource::Kalshi, "Fed rate cut in March")).await.unwrap();
+        store.upsert(market(MarketSource::Polymarket, "Fed rate cut by June")).await.unwrap();
+        store.upsert(market(MarketSource::Kalshi, "Bitcoin above 100k")).await.unwrap();
+        let state = AppState::in_memory(store);
+
+        let params = SearchQuery {
+            q: "fed rate".to_string(),
+            limit: default_limit(),
+            source: Some("kalshi".to_string()),
+            status: None,
+            category: None,
+            tag: None,
+            as_of: None,
+        };
+        let Json(response) = search_markets(State(state.clone()), Query(params)).await.unwrap();
+
+        assert_eq!(response.total, 1);
+        assert_eq!(response.results[0].market.title, "Fed rate cut in March");
+
+        let params = SearchQuery {
+            q: "fed rate".to_string(),
+            limit: default_limit(),
+            source: None,
+            status: Some("active".to_string()),
+            category: None,
+            tag: None,
+            as_of: None,
+        };
+        let result = search_markets(State(state), Query(params)).await;
+        assert!(matches!(result, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn as_of_fills_the_page_with_markets_matching_back_then() {
+        let store = Arc::new(MemoryStore::new());
+        let now = Utc::now();
+        // Busier matches listed since, which would take the page first
+        for i in 0..5 {
+            let title = format!("Fed rate cut {}", i);
+            store
+                .upsert(CreateMarket {
+                    volume: Decimal::from(100),
+                    ..market(MarketSource::Kalshi, &title)
+                })
+                .await
+                .unwrap();
+        }
+        let mut recorded = Vec::new();
+        for title in ["Fed rate cut in March", "Fed rate cut by June", "Fed rate hike"] {
+            recorded.push(store.upsert(market(MarketSource::Kalshi, title)).await.unwrap());
+        }
+        let snapshots: Vec<Snapshot> = recorded
+            .iter()
+            .map(|m| Snapshot {
+                market_id: m.id,
+                yes_price: m.yes_price,
+                no_price: m.no_price,
+                volume: Decimal::ZERO,
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                recorded_at: now - Duration::hours(48),
+            })
+            .collect();
+        store.import_snapshots(&snapshots).await.unwrap();
+        // The hike market was still closed a day ago
+        store
+            .record_revisions(
+                recorded[2].id,
+                &[MarketChange {
+                    field: "status",
+                    old_value: Some("closed".to_string()),
+                    new_value: Some("open".to_string()),
+                }],
+                now - Duration::hours(12),
+            )
+            .await
+            .unwrap();
+        let state = AppState::in_memory(store);
+
+        let params = |status: &str| SearchQuery {
+            q: "fed rate".to_string(),
+            limit: 2,
+            source: None,
+            status: Some(status.to_string()),
+            category: None,
+            tag: None,
+            as_of: Some(now - Duration::hours(24)),
+        };
+
+        let Json(open) = search_markets(State(state.clone()), Query(params("open")))
+            .await
+            .unwrap();
+        assert_eq!(open.total, 2);
+        assert!(open.results.iter().all(|r| r.market.title.contains("cut")));
+
+        let Json(closed) = search_markets(State(state), Query(params("closed"))).await.unwrap();
+        assert_eq!(closed.total, 1);
+        assert_eq!(closed.results[0].market.title, "Fed rate hike");
+        assert_eq!(closed.results[0].market.status, MarketStatus::Closed);
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+
+use crate::{error::ApiResult, AppState};
+use pm_shared::Taxonomy;
+
+#[derive(Debug, Deserialize)]
+pub struct TaxonomyQuery {
+    /// How many of the most used tags to list
+    #[serde(default = "default_tags")]
+    pub tags: i64,
+}
+
+fn default_tags() -> i64 {
+    50
+}
+
+/// Canonical categories and the most used tags, with how many markets carry each
+pub async fn get_taxonomy(
+    State(app_state): State<AppState>,
+    Query(params): Query<TaxonomyQuery>,
+) -> ApiResult<Json<Taxonomy>> {
+    let taxonomy = app_state
+        .market_repo
+        .taxonomy(params.tags.clamp(0, 500))
+        .await?;
+    Ok(Json(taxonomy))
+}
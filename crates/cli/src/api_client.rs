@@ -1,6 +1,11 @@
 use anyhow::Result;
-use pm_shared::{Market, PriceHistory};
+use chrono::{DateTime, Utc};
+use pm_shared::{
+    CollectionRun, CorrelationReport, ExportFormat, Market, MarketBatch, MarketDetail, MarketRef,
+    PriceHistory, QuarantinedMarket, SourceQuality, Taxonomy,
+};
 use serde::Deserialize;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +20,40 @@ struct SearchResultItem {
     score: f32,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct QualityReport {
+    pub sources: Vec<SourceQuality>,
+    pub recent: Vec<QuarantinedMarket>,
+}
+
+/// Canonical category and tag to narrow listings and searches to
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarketFilter<'a> {
+    pub category: Option<&'a str>,
+    pub tag: Option<&'a str>,
+}
+
+impl MarketFilter<'_> {
+    fn push_query(&self, query: &mut Vec<(&'static str, String)>) {
+        if let Some(category) = self.category {
+            query.push(("category", category.to_string()));
+        }
+        if let Some(tag) = self.tag {
+            query.push(("tag", tag.to_string()));
+        }
+    }
+}
+
+/// Markets to correlate, by id or as a category's top markets
+pub struct CorrelationRequest<'a> {
+    pub market_ids: &'a [Uuid],
+    pub category: Option<&'a str>,
+    pub top: usize,
+    pub hours: i64,
+    pub interval: &'a str,
+    pub pairs: usize,
+}
+
 pub struct ApiClient {
     base_url: String,
     client: reqwest::Client,
@@ -28,17 +67,26 @@ impl ApiClient {
         }
     }
 
-    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<(Market, f32)>> {
-        let url = format!("{}/api/search?q={}&limit={}", self.base_url, query, limit);
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        filter: MarketFilter<'_>,
+    ) -> Result<Vec<(Market, f32)>> {
+        let url = format!("{}/api/search", self.base_url);
+
+        let mut params = vec![("q", query.to_string()), ("limit", limit.to_string())];
+        filter.push_query(&mut params);
 
         let response = self.client
             .get(&url)
+            .query(&params)
             .timeout(std::time::Duration::from_secs(10))
             .send()
             .await?;
 
         if !response.status().is_success() {
-            anyhow::bail!("API error: {}", response.status());
+            return Err(api_error(response).await);
         }
 
         let data: SearchResponse = response.json().await?;
@@ -46,20 +94,71 @@ impl ApiClient {
         Ok(data.results.into_iter().map(|r| (r.market, r.score)).collect())
     }
 
-    pub async fn get_market(&self, id: Uuid) -> Result<Market> {
-        let url = format!("{}/api/markets/{}", self.base_url, id);
+    /// A market with the extras named in `include` (stats, equivalents,
+    /// revisions, metrics)
+    pub async fn get_market(&self, market: &MarketRef, include: &[&str]) -> Result<MarketDetail> {
+        let url = match market {
+            MarketRef::Id(id) => format!("{}/api/markets/{}", self.base_url, id),
+            MarketRef::Source { source, source_id } => {
+                let mut url = reqwest::Url::parse(&self.base_url)?;
+                url.path_segments_mut()
+                    .map_err(|_| anyhow::anyhow!("Invalid API URL: {}", self.base_url))?
+                    .pop_if_empty()
+                    .extend(["api", "markets", "by-source", source.as_str(), source_id]);
+                url.to_string()
+            }
+        };
 
-        let response = self.client
+        let mut request = self.client
             .get(&url)
+            .timeout(std::time::Duration::from_secs(10));
+        if !include.is_empty() {
+            request = request.query(&[("include", include.join(","))]);
+        }
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Market IDs for `markets`, in order. Venue identities are looked up in
+    /// one batch request; any that match no market is an error.
+    pub async fn resolve(&self, markets: &[MarketRef]) -> Result<Vec<Uuid>> {
+        let ids: Option<Vec<Uuid>> = markets
+            .iter()
+            .map(|m| match m {
+                MarketRef::Id(id) => Some(*id),
+                MarketRef::Source { .. } => None,
+            })
+            .collect();
+        if let Some(ids) = ids {
+            return Ok(ids);
+        }
+
+        let url = format!("{}/api/markets/batch", self.base_url);
+
+        let response = self.client
+            .post(&url)
+            .json(&serde_json::json!({ "markets": markets }))
             .timeout(std::time::Duration::from_secs(10))
             .send()
             .await?;
 
         if !response.status().is_success() {
-            anyhow::bail!("API error: {}", response.status());
+            return Err(api_error(response).await);
         }
 
-        Ok(response.json().await?)
+        let batch: MarketBatch = response.json().await?;
+        if !batch.missing.is_empty() {
+            let missing: Vec<String> = batch.missing.iter().map(MarketRef::to_string).collect();
+            anyhow::bail!("No market found for {}", missing.join(", "));
+        }
+
+        Ok(batch.markets.iter().map(|m| m.id).collect())
     }
 
     pub async fn get_history(&self, id: Uuid, hours: Option<i64>) -> Result<Vec<PriceHistory>> {
@@ -82,8 +181,96 @@ impl ApiClient {
         Ok(response.json().await?)
     }
 
-    pub async fn list_markets(&self, limit: usize) -> Result<Vec<Market>> {
-        let url = format!("{}/api/markets?limit={}", self.base_url, limit);
+    /// Metrics computed by the worker, `None` if it hasn't computed any yet
+    pub async fn list_markets(
+        &self,
+        limit: usize,
+        sort: &str,
+        order: &str,
+        filter: MarketFilter<'_>,
+    ) -> Result<Vec<Market>> {
+        let url = format!("{}/api/markets", self.base_url);
+
+        let mut query = vec![
+            ("limit", limit.to_string()),
+            ("sort", sort.to_string()),
+            ("order", order.to_string()),
+        ];
+        filter.push_query(&mut query);
+
+        let response = self.client
+            .get(&url)
+            .query(&query)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_taxonomy(&self, tags: usize) -> Result<Taxonomy> {
+        let url = format!("{}/api/taxonomy?tags={}", self.base_url, tags);
+
+        let response = self.client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API error: {}", response.status());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_runs(
+        &self,
+        limit: usize,
+        source: Option<&str>,
+        failed: bool,
+    ) -> Result<Vec<CollectionRun>> {
+        let mut url = format!("{}/api/admin/runs?limit={}", self.base_url, limit);
+
+        if let Some(s) = source {
+            url.push_str(&format!("&source={}", s));
+        }
+
+        if failed {
+            url.push_str("&failed=true");
+        }
+
+        let response = self.client
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API error: {}", response.status());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    pub async fn get_quality(
+        &self,
+        hours: i64,
+        source: Option<&str>,
+        limit: usize,
+    ) -> Result<QualityReport> {
+        let mut url = format!(
+            "{}/api/admin/quality?hours={}&limit={}",
+            self.base_url, hours, limit
+        );
+
+        if let Some(s) = source {
+            url.push_str(&format!("&source={}", s));
+        }
 
         let response = self.client
             .get(&url)
@@ -97,4 +284,85 @@ impl ApiClient {
 
         Ok(response.json().await?)
     }
+
+    pub async fn correlate(&self, request: &CorrelationRequest<'_>) -> Result<CorrelationReport> {
+        let url = format!("{}/api/correlations", self.base_url);
+
+        let mut query = vec![
+            ("hours", request.hours.to_string()),
+            ("interval", request.interval.to_string()),
+            ("pairs", request.pairs.to_string()),
+        ];
+        if !request.market_ids.is_empty() {
+            let ids: Vec<String> = request.market_ids.iter().map(Uuid::to_string).collect();
+            query.push(("market_ids", ids.join(",")));
+        }
+        if let Some(category) = request.category {
+            query.push(("category", category.to_string()));
+            query.push(("top", request.top.to_string()));
+        }
+
+        let response = self.client
+            .get(&url)
+            .query(&query)
+            .timeout(std::time::Duration::from_secs(30))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(api_error(response).await);
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Stream a history export into `out`, returning the number of bytes written.
+    /// No timeout: large exports can take minutes.
+    pub async fn export_history<W: AsyncWrite + Unpin>(
+        &self,
+        market_ids: &[Uuid],
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        format: ExportFormat,
+        out: &mut W,
+    ) -> Result<u64> {
+        let url = format!("{}/api/export/history", self.base_url);
+
+        let mut query = vec![("format", format.to_string())];
+        if !market_ids.is_empty() {
+            let ids: Vec<String> = market_ids.iter().map(Uuid::to_string).collect();
+            query.push(("market_ids", ids.join(",")));
+        }
+        if let Some(from) = from {
+            query.push(("from", from.to_rfc3339()));
+        }
+        if let Some(to) = to {
+            query.push(("to", to.to_rfc3339()));
+        }
+
+        let mut response = self.client.get(&url).query(&query).send().await?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("API error: {}", response.status());
+        }
+
+        let mut written = 0;
+        while let Some(chunk) = response.chunk().await? {
+            out.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        out.flush().await?;
+
+        Ok(written)
+    }
+}
+
+/// Error for a failed response, with the API's explanation when it gave one
+async fn api_error(response: reqwest::Response) -> anyhow::Error {
+    let status = response.status();
+    let body: serde_json::Value = response.json().await.unwrap_or_default();
+    match body["error"].as_str() {
+        Some(error) => anyhow::anyhow!("API error: {}: {}", status, error),
+        None => anyhow::anyhow!("API error: {}", status),
+    }
 }
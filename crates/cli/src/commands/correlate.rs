@@ -0,0 +1,111 @@
+use anyhow::Result;
+use colored::*;
+use uuid::Uuid;
+
+use crate::api_client::{ApiClient, CorrelationRequest};
+use pm_shared::CorrelationReport;
+
+/// Longest title shown in the legend
+const TITLE_WIDTH: usize = 60;
+
+pub async fn correlate_command(api_url: &str, request: &CorrelationRequest<'_>) -> Result<()> {
+    let client = ApiClient::new(api_url.to_string());
+
+    println!("{}", "Computing correlations...".cyan());
+
+    let report = client.correlate(request).await?;
+
+    println!(
+        "\n{}",
+        format!(
+            "Return Correlations ({} to {}, every {})",
+            report.from.format("%Y-%m-%d %H:%M"),
+            report.to.format("%Y-%m-%d %H:%M"),
+            format_interval(report.interval_seconds)
+        )
+        .green()
+        .bold()
+    );
+    println!("{}", "=".repeat(80).green());
+
+    for (idx, market) in report.markets.iter().enumerate() {
+        let title: String = market.title.chars().take(TITLE_WIDTH).collect();
+        println!(
+            "{:>3}. {} {}",
+            (idx + 1).to_string().cyan().bold(),
+            title.white(),
+            format!("({})", market.source.as_str()).bright_black()
+        );
+    }
+
+    print_matrix(&report);
+
+    if !report.pairs.is_empty() {
+        println!("\n{}", "Most Correlated Pairs".green().bold());
+        println!("{}", "-".repeat(80));
+
+        let number = |id: Uuid| {
+            report
+                .markets
+                .iter()
+                .position(|m| m.id == id)
+                .map_or(0, |i| i + 1)
+        };
+        for pair in &report.pairs {
+            println!(
+                "  {:>3} & {:<3} {}  {}",
+                number(pair.market_a),
+                number(pair.market_b),
+                shade(Some(pair.correlation)),
+                format!("over {} steps", pair.observations).bright_black()
+            );
+        }
+    }
+
+    println!("\n{}", "=".repeat(80).green());
+
+    Ok(())
+}
+
+/// Matrix with markets numbered as in the legend, shaded by strength
+fn print_matrix(report: &CorrelationReport) {
+    print!("\n{:>5}", "");
+    for idx in 1..=report.markets.len() {
+        print!(" {:>6}", idx);
+    }
+    println!();
+
+    for (idx, row) in report.matrix.iter().enumerate() {
+        print!("{:>5}", (idx + 1).to_string().cyan());
+        for value in row {
+            print!(" {}", shade(*value));
+        }
+        println!();
+    }
+}
+
+/// A correlation padded to 6 columns: green when strongly positive, red when
+/// strongly negative, dimmed when weak
+fn shade(value: Option<f64>) -> ColoredString {
+    let Some(value) = value else {
+        return format!("{:>6}", "-").bright_black();
+    };
+
+    let text = format!("{:>+6.2}", value);
+    match value {
+        v if v >= 0.7 => text.green().bold(),
+        v if v >= 0.4 => text.green(),
+        v if v <= -0.7 => text.red().bold(),
+        v if v <= -0.4 => text.red(),
+        _ => text.bright_black(),
+    }
+}
+
+fn format_interval(seconds: i64) -> String {
+    match seconds {
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3600 == 0 => format!("{}h", s / 3600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{}s", s),
+    }
+}
@@ -1,44 +1,84 @@
 use anyhow::Result;
+use chrono::{Duration, Utc};
 use colored::*;
-use uuid::Uuid;
 
 use crate::api_client::ApiClient;
+use pm_shared::{Decimal, Equivalent, MarketRef, MarketStats, MarketStatus};
 
-pub async fn detail_command(api_url: &str, market_id: Uuid) -> Result<()> {
+/// Revisions this recent are called out at the top of the detail view
+const RECENT_DAYS: i64 = 7;
+
+pub async fn detail_command(api_url: &str, market: &MarketRef) -> Result<()> {
     let client = ApiClient::new(api_url.to_string());
 
     println!("{}", "Fetching market details...".cyan());
 
-    let market = client.get_market(market_id).await?;
+    let detail = client
+        .get_market(market, &["stats", "equivalents", "revisions"])
+        .await?;
+    let market = &detail.market;
+
+    let cutoff = Utc::now() - Duration::days(RECENT_DAYS);
+    let recent: Vec<_> = detail
+        .revisions
+        .iter()
+        .flatten()
+        .filter(|r| r.changed_at >= cutoff)
+        .collect();
 
     println!("\n{}", "=".repeat(80).green());
     println!("{}", market.title.white().bold());
     println!("{}", "=".repeat(80).green());
 
+    if !recent.is_empty() {
+        println!(
+            "\n{}",
+            format!("Changed in the last {} days:", RECENT_DAYS).red().bold()
+        );
+        for revision in recent {
+            let (old, new) =
+                change_snippets(revision.old_value.as_deref(), revision.new_value.as_deref());
+            println!(
+                "  {} {}: {} → {}",
+                revision.changed_at.format("%Y-%m-%d %H:%M").to_string().bright_black(),
+                revision.field.yellow(),
+                old,
+                new
+            );
+        }
+    }
+
     println!("\n{}", "Description:".yellow().bold());
     println!("{}", market.description);
 
     println!("\n{}", "Market Details:".yellow().bold());
     println!("  {} {}", "ID:".bright_black(), market.id);
-    println!("  {} {}", "Source:".bright_black(), market.source.blue());
+    println!("  {} {}", "Source:".bright_black(), market.source.as_str().blue());
     println!("  {} {}", "Source ID:".bright_black(), market.source_id);
     println!(
         "  {} {}",
         "Status:".bright_black(),
-        if market.status == "open" {
-            market.status.green()
+        if market.status == MarketStatus::Open {
+            market.status.as_str().green()
         } else {
-            market.status.yellow()
+            market.status.as_str().yellow()
         }
     );
 
-    if let Some(category) = &market.category {
-        println!("  {} {}", "Category:".bright_black(), category);
+    if let Some(category) = market.category {
+        match &market.source_category {
+            Some(raw) => println!("  {} {} ({})", "Category:".bright_black(), category, raw),
+            None => println!("  {} {}", "Category:".bright_black(), category),
+        }
+    }
+
+    if let Some(tags) = market.tags.as_ref().filter(|t| !t.is_empty()) {
+        println!("  {} {}", "Tags:".bright_black(), tags.join(", "));
     }
 
     println!("\n{}", "Current Prices:".yellow().bold());
-    println!("  {} {:.2}%", "Yes:".bright_black(), market.yes_price * 100.0);
-    println!("  {} {:.2}%", "No:".bright_black(), market.no_price * 100.0);
+    println!("  {} {:.2}%", "Yes:".bright_black(), market.yes_price * Decimal::ONE_HUNDRED);
+    println!("  {} {:.2}%", "No:".bright_black(), market.no_price * Decimal::ONE_HUNDRED);
 
     println!("\n{}", "Volume & Liquidity:".yellow().bold());
     println!("  {} ${:.2}", "Total Volume:".bright_black(), market.volume);
@@ -47,6 +87,10 @@ pub async fn detail_command(api_url: &str, market_id: Uuid) -> Result<()> {
         println!("  {} ${:.2}", "Liquidity:".bright_black(), liq);
     }
 
+    if let Some(stats) = &detail.stats {
+        print_stats(stats);
+    }
+
     println!("\n{}", "Timestamps:".yellow().bold());
     println!("  {} {}", "Created:".bright_black(), market.created_at.format("%Y-%m-%d %H:%M:%S UTC"));
     println!("  {} {}", "Updated:".bright_black(), market.updated_at.format("%Y-%m-%d %H:%M:%S UTC"));
@@ -57,8 +101,101 @@ pub async fn detail_command(api_url: &str, market_id: Uuid) -> Result<()> {
     println!("\n{}", "Link:".yellow().bold());
     println!("  {}", market.url.bright_blue());
 
+    if let Some(equivalents) = detail.equivalents.as_ref().filter(|e| !e.is_empty()) {
+        print_equivalents(equivalents);
+    }
+
     println!("\n{}", "=".repeat(80).green());
     println!("\n{}", format!("Use 'pm-cli history {}' to see price history", market.id).bright_black());
 
     Ok(())
 }
+
+/// Price changes, 24h range and extent of the stored history
+pub(crate) fn print_stats(stats: &MarketStats) {
+    let points = |d: Option<Decimal>| match d {
+        Some(d) => format!("{:+.2}%", d * Decimal::ONE_HUNDRED),
+        None => "-".to_string(),
+    };
+    let price = |d: Option<Decimal>| match d {
+        Some(d) => format!("{:.2}%", d * Decimal::ONE_HUNDRED),
+        None => "-".to_string(),
+    };
+
+    println!("\n{}", "Price Movement:".yellow().bold());
+    println!(
+        "  {} {} / {} / {}",
+        "Change 1h/24h/7d:".bright_black(),
+        points(stats.change_1h),
+        points(stats.change_24h),
+        points(stats.change_7d)
+    );
+    println!(
+        "  {} {} - {}",
+        "24h Range:       ".bright_black(),
+        price(stats.low_24h),
+        price(stats.high_24h)
+    );
+
+    let span = match (stats.first_snapshot_at, stats.last_snapshot_at) {
+        (Some(first), Some(last)) => format!(
+            "{} snapshots, {} to {}",
+            stats.snapshot_count,
+            first.format("%Y-%m-%d %H:%M"),
+            last.format("%Y-%m-%d %H:%M")
+        ),
+        _ => "none recorded".to_string(),
+    };
+    println!("  {} {}", "History:         ".bright_black(), span);
+}
+
+fn print_equivalents(equivalents: &[Equivalent]) {
+    println!("\n{}", "On Other Venues:".yellow().bold());
+    for equivalent in equivalents {
+        let market = &equivalent.market;
+        println!(
+            "  {} {:.2}% {} {}",
+            market.source.as_str().blue(),
+            market.yes_price * Decimal::ONE_HUNDRED,
+            market.title,
+            format!("({:.0}% similar, {})", equivalent.similarity * 100.0, market.id)
+                .bright_black()
+        );
+    }
+}
+
+/// Old and new revision values on one line each, cut to fit next to the
+/// field name. Long values are shown around their first difference, so an
+/// edit deep inside a description stays visible.
+fn change_snippets(old: Option<&str>, new: Option<&str>) -> (String, String) {
+    const MAX_CHARS: usize = 50;
+    const CONTEXT_CHARS: usize = 15;
+
+    let flatten = |value: Option<&str>| -> Option<Vec<char>> {
+        value.map(|v| v.split_whitespace().collect::<Vec<_>>().join(" ").chars().collect())
+    };
+    let (old, new) = (flatten(old), flatten(new));
+
+    let common = match (&old, &new) {
+        (Some(old), Some(new)) => old.iter().zip(new).take_while(|(a, b)| a == b).count(),
+        _ => 0,
+    };
+    let start = common.saturating_sub(CONTEXT_CHARS);
+
+    let snippet = |value: Option<Vec<char>>| -> String {
+        let Some(chars) = value else {
+            return "(none)".to_string();
+        };
+        let start = start.min(chars.len());
+        let mut text: String = chars[start..].iter().take(MAX_CHARS).collect();
+        if start > 0 {
+            text.insert(0, '…');
+        }
+        if chars.len() - start > MAX_CHARS {
+            text.push('…');
+        }
+        text
+    };
+
+    (snippet(old), snippet(new))
+}
@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
+use pm_shared::ExportFormat;
+use std::path::Path;
+use uuid::Uuid;
+
+use crate::api_client::ApiClient;
+
+pub async fn export_command(
+    api_url: &str,
+    market_ids: &[Uuid],
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    format: Option<ExportFormat>,
+    output: &Path,
+) -> Result<()> {
+    let format = match format.or_else(|| ExportFormat::from_path(output)) {
+        Some(format) => format,
+        None => anyhow::bail!(
+            "Cannot infer the format of {}; pass --format csv|jsonl|parquet",
+            output.display()
+        ),
+    };
+
+    let client = ApiClient::new(api_url.to_string());
+
+    let scope = if market_ids.is_empty() {
+        "all markets".to_string()
+    } else {
+        format!("{} market(s)", market_ids.len())
+    };
+    println!(
+        "{}",
+        format!("Exporting history for {} as {}...", scope, format).cyan()
+    );
+
+    let mut file = tokio::fs::File::create(output)
+        .await
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+
+    let written = match client
+        .export_history(market_ids, from, to, format, &mut file)
+        .await
+    {
+        Ok(written) => written,
+        Err(e) => {
+            // Don't leave a truncated file behind that looks like a finished export
+            drop(file);
+            let _ = tokio::fs::remove_file(output).await;
+            return Err(e);
+        }
+    };
+
+    println!(
+        "{} {} ({:.1} KiB)",
+        "Wrote".green(),
+        output.display(),
+        written as f64 / 1024.0
+    );
+
+    Ok(())
+}
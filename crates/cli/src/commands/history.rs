@@ -1,24 +1,29 @@
 use anyhow::Result;
+use chrono::Utc;
 use colored::*;
-use uuid::Uuid;
 
 use crate::api_client::ApiClient;
+use crate::commands::detail::print_stats;
+use pm_shared::{Decimal, MarketMetrics, MarketRef};
 
-pub async fn history_command(api_url: &str, market_id: Uuid, hours: Option<i64>) -> Result<()> {
+pub async fn history_command(
+    api_url: &str,
+    market: &MarketRef,
+    hours: Option<i64>,
+) -> Result<()> {
     let client = ApiClient::new(api_url.to_string());
 
     println!("{}", "Fetching price history...".cyan());
 
-    let history = client.get_history(market_id, hours).await?;
+    let detail = client.get_market(market, &["stats", "metrics"]).await?;
+    let market = &detail.market;
+    let history = client.get_history(market.id, hours).await?;
 
     if history.is_empty() {
         println!("{}", "No price history available for this market.".yellow());
         return Ok(());
     }
 
-    // Get market details for title
-    let market = client.get_market(market_id).await?;
-
     println!("\n{}", "=".repeat(80).green());
     println!("{}", format!("Price History: {}", market.title).white().bold());
     println!("{}", "=".repeat(80).green());
@@ -47,34 +52,79 @@ pub async fn history_command(api_url: &str, market_id: Uuid, hours: Option<i64>)
         println!(
             "{:^20} | {:>9.2}% | {:>9.2}% | ${:>10.0} | ${:>10.0}",
             snapshot.recorded_at.format("%Y-%m-%d %H:%M"),
-            snapshot.yes_price * 100.0,
-            snapshot.no_price * 100.0,
+            snapshot.yes_price * Decimal::ONE_HUNDRED,
+            snapshot.no_price * Decimal::ONE_HUNDRED,
             snapshot.volume,
             snapshot.volume_24h
         );
     }
 
-    // Calculate price change
-    if history.len() >= 2 {
-        let latest = &history[0];
-        let oldest = &history[history.len() - 1];
-        let yes_change = (latest.yes_price - oldest.yes_price) * 100.0;
-        let no_change = (latest.no_price - oldest.no_price) * 100.0;
+    if let Some(stats) = &detail.stats {
+        print_stats(stats);
+    }
 
-        println!("\n{}", "Price Change:".yellow().bold());
-        println!(
-            "  {} {:+.2}%",
-            "Yes:".bright_black(),
-            yes_change
-        );
-        println!(
-            "  {} {:+.2}%",
-            "No:".bright_black(),
-            no_change
-        );
+    // Not computed by the worker yet for new markets
+    if let Some(metrics) = &detail.metrics {
+        print_metrics(metrics);
     }
 
     println!("\n{}", "=".repeat(80).green());
 
     Ok(())
 }
+
+fn print_metrics(metrics: &MarketMetrics) {
+    let points = |d: Option<Decimal>| match d {
+        Some(d) => format!("{:+.2}%", d * Decimal::ONE_HUNDRED),
+        None => "-".to_string(),
+    };
+    let ratio = |v: Option<f64>| match v {
+        Some(v) => format!("{:.3}", v),
+        None => "-".to_string(),
+    };
+
+    println!(
+        "\n{} {}",
+        "Metrics".yellow().bold(),
+        format!(
+            "({} hourly closes, computed {})",
+            metrics.samples,
+            metrics.computed_at.format("%Y-%m-%d %H:%M")
+        )
+        .bright_black()
+    );
+    println!(
+        "  {} {} / {} / {}",
+        "Volatility 24h/7d/30d:".bright_black(),
+        ratio(metrics.volatility_24h),
+        ratio(metrics.volatility_7d),
+        ratio(metrics.volatility_30d)
+    );
+    println!(
+        "  {} {} / {}",
+        "Momentum 24h/7d:      ".bright_black(),
+        points(metrics.momentum_24h),
+        points(metrics.momentum_7d)
+    );
+    println!(
+        "  {} {}",
+        "Max drawdown 30d:     ".bright_black(),
+        points(metrics.max_drawdown_30d.map(|d| -d))
+    );
+
+    let last_move = match metrics.last_move_at {
+        Some(at) => format!(
+            "{} ({}h ago)",
+            at.format("%Y-%m-%d %H:%M"),
+            (Utc::now() - at).num_hours()
+        ),
+        None => "none in 30 days".to_string(),
+    };
+    println!("  {} {}", "Last 5pt move:        ".bright_black(), last_move);
+
+    let acceleration = match metrics.volume_acceleration {
+        Some(a) => format!("{:+.0}% vs previous 24h", a * 100.0),
+        None => "-".to_string(),
+    };
+    println!("  {} {}", "Volume 24h:           ".bright_black(), acceleration);
+}
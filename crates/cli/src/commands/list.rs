@@ -1,14 +1,21 @@
 use anyhow::Result;
 use colored::*;
 
-use crate::api_client::ApiClient;
-
-pub async fn list_command(api_url: &str, limit: usize) -> Result<()> {
+use crate::api_client::{ApiClient, MarketFilter};
+use pm_shared::{Decimal, MarketStatus};
+
+pub async fn list_command(
+    api_url: &str,
+    limit: usize,
+    sort: &str,
+    order: &str,
+    filter: MarketFilter<'_>,
+) -> Result<()> {
     let client = ApiClient::new(api_url.to_string());
 
     println!("{}", "Fetching markets...".cyan());
 
-    let markets = client.list_markets(limit).await?;
+    let markets = client.list_markets(limit, sort, order, filter).await?;
 
     if markets.is_empty() {
         println!("{}", "No markets found.".yellow());
@@ -29,18 +36,22 @@ pub async fn list_command(api_url: &str, limit: usize) -> Result<()> {
         println!(
             "   {} {} | {} {} | {} ${:.0} | {} {:.1}%",
             "Source:".bright_black(),
-            market.source.blue(),
+            market.source.as_str().blue(),
             "Status:".bright_black(),
-            if market.status == "open" {
-                market.status.green()
+            if market.status == MarketStatus::Open {
+                market.status.as_str().green()
             } else {
-                market.status.yellow()
+                market.status.as_str().yellow()
             },
             "Volume:".bright_black(),
             market.volume,
             "Yes:".bright_black(),
-            market.yes_price * 100.0
+            market.yes_price * Decimal::ONE_HUNDRED
         );
+
+        if let Some(category) = market.category {
+            println!("   {} {}", "Category:".bright_black(), category);
+        }
     }
 
     println!("\n{}", "=".repeat(80).green());
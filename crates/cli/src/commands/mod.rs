@@ -2,8 +2,18 @@ pub mod search;
 pub mod detail;
 pub mod history;
 pub mod list;
+pub mod runs;
+pub mod export;
+pub mod quality;
+pub mod correlate;
+pub mod taxonomy;
 
 pub use search::search_command;
 pub use detail::detail_command;
 pub use history::history_command;
 pub use list::list_command;
+pub use runs::runs_command;
+pub use export::export_command;
+pub use quality::quality_command;
+pub use correlate::correlate_command;
+pub use taxonomy::taxonomy_command;
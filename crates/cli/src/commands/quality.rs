@@ -0,0 +1,71 @@
+use anyhow::Result;
+use colored::*;
+
+use crate::api_client::ApiClient;
+
+pub async fn quality_command(
+    api_url: &str,
+    hours: i64,
+    source: Option<&str>,
+    limit: usize,
+) -> Result<()> {
+    let client = ApiClient::new(api_url.to_string());
+
+    println!("{}", "Fetching quarantined markets...".cyan());
+
+    let report = client.get_quality(hours, source, limit).await?;
+
+    if report.sources.is_empty() {
+        println!(
+            "{}",
+            format!("No markets quarantined in the last {}h.", hours).green()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        format!("Quarantined Markets (last {}h)", hours).green().bold()
+    );
+    println!("{}", "=".repeat(80).green());
+
+    for source in &report.sources {
+        println!(
+            "\n{} {}",
+            source.source.as_str().blue().bold(),
+            format!("{} quarantined", source.quarantined).red()
+        );
+        for check in &source.checks {
+            println!("   {:<26} {:>6}", check.check, check.count);
+        }
+    }
+
+    if !report.recent.is_empty() {
+        println!("\n{}", "Recent Rejections".green().bold());
+        println!("{}", "-".repeat(80));
+
+        for market in &report.recent {
+            println!(
+                "{} | {:<10} | {}",
+                market.last_seen_at.format("%Y-%m-%d %H:%M:%S"),
+                market.source.as_str(),
+                market.source_id.as_deref().unwrap_or("<no id>").white().bold()
+            );
+            println!("   {}", market.reason.bright_black());
+            if market.quarantined_at < market.last_seen_at {
+                println!(
+                    "   {}",
+                    format!(
+                        "failing since {}",
+                        market.quarantined_at.format("%Y-%m-%d %H:%M:%S")
+                    )
+                    .bright_black()
+                );
+            }
+        }
+    }
+
+    println!("\n{}", "=".repeat(80).green());
+
+    Ok(())
+}
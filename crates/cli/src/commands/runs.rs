@@ -0,0 +1,75 @@
+use anyhow::Result;
+use colored::*;
+
+use crate::api_client::ApiClient;
+
+pub async fn runs_command(
+    api_url: &str,
+    limit: usize,
+    source: Option<&str>,
+    failed: bool,
+) -> Result<()> {
+    let client = ApiClient::new(api_url.to_string());
+
+    println!("{}", "Fetching collection runs...".cyan());
+
+    let runs = client.list_runs(limit, source, failed).await?;
+
+    if runs.is_empty() {
+        println!("{}", "No collection runs recorded.".yellow());
+        return Ok(());
+    }
+
+    println!("\n{}", "Collection Runs".green().bold());
+    println!("{}", "=".repeat(80).green());
+    println!(
+        "{:<19} | {:<12} | {:>7} | {:>8} | {:>6} | {:>8} | Status",
+        "Started", "Step", "Fetched", "Recorded", "Failed", "Duration"
+    );
+    println!("{}", "-".repeat(80));
+
+    for run in &runs {
+        let step = match &run.source {
+            Some(source) => format!("  {}", source),
+            None => run.kind.clone(),
+        };
+
+        let duration = match run.finished_at {
+            Some(finished) => {
+                let ms = (finished - run.started_at).num_milliseconds();
+                format!("{:.1}s", ms as f64 / 1000.0)
+            }
+            None => "-".to_string(),
+        };
+
+        let status = match (&run.error, run.finished_at) {
+            (Some(error), _) => match run.http_status {
+                Some(code) => format!("HTTP {}: {}", code, error).red(),
+                None => error.red(),
+            },
+            (None, None) => "unfinished".yellow(),
+            (None, Some(_)) if run.markets_failed > 0 => "partial".yellow(),
+            (None, Some(_)) => "ok".green(),
+        };
+
+        let line = format!(
+            "{:<19} | {:<12} | {:>7} | {:>8} | {:>6} | {:>8} |",
+            run.started_at.format("%Y-%m-%d %H:%M:%S"),
+            step,
+            run.markets_fetched,
+            run.markets_recorded,
+            run.markets_failed,
+            duration
+        );
+
+        if run.parent_id.is_none() {
+            println!("{} {}", line.white().bold(), status);
+        } else {
+            println!("{} {}", line.bright_black(), status);
+        }
+    }
+
+    println!("\n{}", "=".repeat(80).green());
+
+    Ok(())
+}
@@ -1,14 +1,20 @@
 use anyhow::Result;
 use colored::*;
 
-use crate::api_client::ApiClient;
+use crate::api_client::{ApiClient, MarketFilter};
+use pm_shared::{Decimal, MarketStatus};
 
-pub async fn search_command(api_url: &str, query: &str, limit: usize) -> Result<()> {
+pub async fn search_command(
+    api_url: &str,
+    query: &str,
+    limit: usize,
+    filter: MarketFilter<'_>,
+) -> Result<()> {
     let client = ApiClient::new(api_url.to_string());
 
     println!("{}", "Searching markets...".cyan());
 
-    let results = client.search(query, limit).await?;
+    let results = client.search(query, limit, filter).await?;
 
     if results.is_empty() {
         println!("{}", "No markets found matching your query.".yellow());
@@ -38,24 +44,24 @@ pub async fn search_command(api_url: &str, query: &str, limit: usize) -> Result<
         println!(
             "   {} {} | {} {} | {} ${:.0}",
             "Source:".bright_black(),
-            market.source.blue(),
+            market.source.as_str().blue(),
             "Status:".bright_black(),
-            if market.status == "open" {
-                market.status.green()
+            if market.status == MarketStatus::Open {
+                market.status.as_str().green()
             } else {
-                market.status.yellow()
+                market.status.as_str().yellow()
             },
             "Volume:".bright_black(),
             market.volume
         );
 
-        if market.yes_price > 0.0 {
+        if market.yes_price > Decimal::ZERO {
             println!(
                 "   {} {:.1}% | {} {:.1}%",
                 "Yes:".bright_black(),
-                market.yes_price * 100.0,
+                market.yes_price * Decimal::ONE_HUNDRED,
                 "No:".bright_black(),
-                market.no_price * 100.0
+                market.no_price * Decimal::ONE_HUNDRED
             );
         }
 
@@ -63,7 +69,7 @@ pub async fn search_command(api_url: &str, query: &str, limit: usize) -> Result<
     }
 
     println!("\n{}", "=".repeat(80).green());
-    println!("\n{}", format!("Use 'pm-cli detail <ID>' to see full market details").bright_black());
+    println!("\n{}", "Use 'pm-cli detail <ID>' to see full market details".bright_black());
 
     Ok(())
 }
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use colored::*;
+
+use crate::api_client::ApiClient;
+
+pub async fn taxonomy_command(api_url: &str, tags: usize) -> Result<()> {
+    let client = ApiClient::new(api_url.to_string());
+
+    println!("{}", "Fetching taxonomy...".cyan());
+
+    let taxonomy = client.get_taxonomy(tags).await?;
+
+    println!("\n{}", "Categories".green().bold());
+    println!("{}", "=".repeat(80).green());
+    for entry in &taxonomy.categories {
+        let count = format!("{:>8}", entry.markets);
+        println!(
+            "   {:<14} {}",
+            entry.category.as_str().blue(),
+            if entry.markets == 0 { count.bright_black() } else { count.normal() }
+        );
+    }
+
+    println!("\n{}", "Tags".green().bold());
+    println!("{}", "=".repeat(80).green());
+    if taxonomy.tags.is_empty() {
+        println!("   {}", "No tagged markets yet.".yellow());
+    }
+    for entry in &taxonomy.tags {
+        println!("   {:<24} {:>8}", entry.tag, entry.markets);
+    }
+
+    println!(
+        "\n{}",
+        "Use 'pm-cli list --category <CATEGORY>' or '--tag <TAG>' to filter markets".bright_black()
+    );
+
+    Ok(())
+}
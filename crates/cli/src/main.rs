@@ -2,8 +2,10 @@ mod api_client;
 mod commands;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use clap::{Parser, Subcommand};
-use uuid::Uuid;
+use pm_shared::{ExportFormat, MarketRef};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "pm-cli")]
@@ -27,18 +29,26 @@ enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Only markets in this canonical category, e.g. politics
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Only markets carrying this tag, e.g. person:trump
+        #[arg(long)]
+        tag: Option<String>,
     },
 
     /// Get market details
     Detail {
-        /// Market ID
-        id: Uuid,
+        /// Market ID, or venue and venue id (kalshi:TICKER, polymarket:0x...)
+        market: MarketRef,
     },
 
     /// Get price history for a market
     History {
-        /// Market ID
-        id: Uuid,
+        /// Market ID, or venue and venue id (kalshi:TICKER, polymarket:0x...)
+        market: MarketRef,
 
         /// Limit to last N hours
         #[arg(long)]
@@ -50,6 +60,112 @@ enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value = "20")]
         limit: usize,
+
+        /// created_at, volume, volume_24h, close_at, or a metric such as
+        /// volatility_24h, momentum_7d or volume_acceleration
+        #[arg(long, default_value = "created_at")]
+        sort: String,
+
+        /// asc or desc
+        #[arg(long, default_value = "desc")]
+        order: String,
+
+        /// Only markets in this canonical category, e.g. crypto
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Only markets carrying this tag, e.g. asset:btc
+        #[arg(long)]
+        tag: Option<String>,
+    },
+
+    /// List canonical categories and the most used tags with their market counts
+    Taxonomy {
+        /// How many tags to list
+        #[arg(long, default_value = "30")]
+        tags: usize,
+    },
+
+    /// Show recent collection runs
+    Runs {
+        /// Maximum number of rows
+        #[arg(short, long, default_value = "30")]
+        limit: usize,
+
+        /// Only show steps for this source
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Only show failed or unfinished runs
+        #[arg(long)]
+        failed: bool,
+    },
+
+    /// Report markets the worker quarantined for failing validation
+    Quality {
+        /// How many hours back to look
+        #[arg(long, default_value = "24")]
+        hours: i64,
+
+        /// Only report this source
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Maximum number of recent rejections to list
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Correlate price moves between markets, printed as a shaded matrix.
+    ///
+    /// Pass market IDs or kalshi:TICKER / polymarket:0x... references, or
+    /// --category to take its highest-volume markets.
+    Correlate {
+        /// Markets to correlate
+        markets: Vec<MarketRef>,
+
+        /// Correlate the top markets by volume in this category instead
+        #[arg(long, conflicts_with = "markets")]
+        category: Option<String>,
+
+        /// How many of the category's markets to take
+        #[arg(long, default_value = "10")]
+        top: usize,
+
+        /// How many hours back to look
+        #[arg(long, default_value = "168")]
+        hours: i64,
+
+        /// Grid the prices are resampled onto, e.g. 15m, 1h, 1d
+        #[arg(long, default_value = "1h")]
+        interval: String,
+
+        /// How many of the most correlated pairs to list
+        #[arg(long, default_value = "10")]
+        pairs: usize,
+    },
+
+    /// Export raw price history to a CSV, JSON Lines or Parquet file
+    Export {
+        /// Market IDs or kalshi:TICKER / polymarket:0x... references to export
+        /// (all markets when omitted)
+        markets: Vec<MarketRef>,
+
+        /// Only snapshots at or after this time (RFC 3339)
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+
+        /// Only snapshots before this time (RFC 3339)
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+
+        /// csv, jsonl or parquet; inferred from the output extension when omitted
+        #[arg(long)]
+        format: Option<ExportFormat>,
+
+        /// File to write
+        #[arg(short, long)]
+        output: PathBuf,
     },
 }
 
@@ -58,17 +174,52 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Search { query, limit } => {
-            commands::search_command(&cli.api_url, &query, limit).await?;
+        Commands::Search { query, limit, category, tag } => {
+            let filter = api_client::MarketFilter {
+                category: category.as_deref(),
+                tag: tag.as_deref(),
+            };
+            commands::search_command(&cli.api_url, &query, limit, filter).await?;
+        }
+        Commands::Detail { market } => {
+            commands::detail_command(&cli.api_url, &market).await?;
+        }
+        Commands::History { market, hours } => {
+            commands::history_command(&cli.api_url, &market, hours).await?;
+        }
+        Commands::List { limit, sort, order, category, tag } => {
+            let filter = api_client::MarketFilter {
+                category: category.as_deref(),
+                tag: tag.as_deref(),
+            };
+            commands::list_command(&cli.api_url, limit, &sort, &order, filter).await?;
+        }
+        Commands::Taxonomy { tags } => {
+            commands::taxonomy_command(&cli.api_url, tags).await?;
+        }
+        Commands::Runs { limit, source, failed } => {
+            commands::runs_command(&cli.api_url, limit, source.as_deref(), failed).await?;
         }
-        Commands::Detail { id } => {
-            commands::detail_command(&cli.api_url, id).await?;
+        Commands::Quality { hours, source, limit } => {
+            commands::quality_command(&cli.api_url, hours, source.as_deref(), limit).await?;
         }
-        Commands::History { id, hours } => {
-            commands::history_command(&cli.api_url, id, hours).await?;
+        Commands::Correlate { markets, category, top, hours, interval, pairs } => {
+            let client = api_client::ApiClient::new(cli.api_url.clone());
+            let market_ids = client.resolve(&markets).await?;
+            let request = api_client::CorrelationRequest {
+                market_ids: &market_ids,
+                category: category.as_deref(),
+                top,
+                hours,
+                interval: &interval,
+                pairs,
+            };
+            commands::correlate_command(&cli.api_url, &request).await?;
         }
-        Commands::List { limit } => {
-            commands::list_command(&cli.api_url, limit).await?;
+        Commands::Export { markets, from, to, format, output } => {
+            let client = api_client::ApiClient::new(cli.api_url.clone());
+            let market_ids = client.resolve(&markets).await?;
+            commands::export_command(&cli.api_url, &market_ids, from, to, format, &output).await?;
         }
     }
 
@@ -0,0 +1,163 @@
+//! Correlation of price moves between markets.
+//!
+//! Prices are compared as log-odds returns between consecutive points of a
+//! common grid (see `series`), so markets near 0 or 1 aren't drowned out by
+//! those trading around 50 cents.
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::metrics::log_odds;
+use crate::models::{CorrelatedPair, CorrelationReport, Decimal, MarketLabel};
+
+/// Grid steps two markets must both have a return on to be correlated
+pub const MIN_OBSERVATIONS: usize = 10;
+
+/// Correlate `prices[i]`, the YES price of `markets[i]` at each point of the
+/// grid `[from, to]` every `interval` (`None` before its first snapshot).
+/// `pairs` caps how many of the strongest pairs are listed.
+pub fn report(
+    markets: Vec<MarketLabel>,
+    prices: &[Vec<Option<Decimal>>],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    interval: Duration,
+    pairs: usize,
+) -> CorrelationReport {
+    let returns: Vec<Vec<Option<f64>>> = prices.iter().map(|p| returns(p)).collect();
+    let n = returns.len();
+
+    let mut matrix = vec![vec![None; n]; n];
+    let mut correlated = Vec::new();
+    for i in 0..n {
+        for j in i..n {
+            let Some((correlation, observations)) = pearson(&returns[i], &returns[j]) else {
+                continue;
+            };
+            matrix[i][j] = Some(correlation);
+            matrix[j][i] = Some(correlation);
+
+            if i != j {
+                correlated.push(CorrelatedPair {
+                    market_a: markets[i].id,
+                    market_b: markets[j].id,
+                    correlation,
+                    observations,
+                });
+            }
+        }
+    }
+
+    correlated.sort_by(|a, b| b.correlation.abs().total_cmp(&a.correlation.abs()));
+    correlated.truncate(pairs);
+
+    CorrelationReport {
+        from,
+        to,
+        interval_seconds: interval.num_seconds(),
+        markets,
+        matrix,
+        pairs: correlated,
+    }
+}
+
+/// Log-odds change into each grid point from the one before; `None` where
+/// either end has no price
+fn returns(prices: &[Option<Decimal>]) -> Vec<Option<f64>> {
+    prices
+        .windows(2)
+        .map(|pair| match (pair[0], pair[1]) {
+            (Some(a), Some(b)) => Some(log_odds(b) - log_odds(a)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Pearson correlation over the steps where both series have a return, with
+/// the number of such steps
+fn pearson(a: &[Option<f64>], b: &[Option<f64>]) -> Option<(f64, usize)> {
+    let pairs: Vec<(f64, f64)> = a
+        .iter()
+        .zip(b)
+        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+        .collect();
+    if pairs.len() < MIN_OBSERVATIONS {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_a = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_b = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+
+    let (mut covariance, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        covariance += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return None;
+    }
+
+    let correlation = (covariance / (var_a * var_b).sqrt()).clamp(-1.0, 1.0);
+    Some((correlation, pairs.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MarketSource;
+    use uuid::Uuid;
+
+    fn label(title: &str) -> MarketLabel {
+        MarketLabel {
+            id: Uuid::new_v4(),
+            source: MarketSource::Kalshi,
+            title: title.to_string(),
+        }
+    }
+
+    fn cents(prices: &[i64]) -> Vec<Option<Decimal>> {
+        prices.iter().map(|c| Some(Decimal::new(*c, 2))).collect()
+    }
+
+    #[test]
+    fn ranks_pairs_by_strength() {
+        let zigzag = [50, 55, 48, 60, 52, 58, 45, 50, 62, 55, 49, 57];
+        let mirrored: Vec<i64> = zigzag.iter().map(|c| 100 - c).collect();
+        let unrelated = [50, 50, 51, 51, 50, 52, 51, 50, 50, 52, 51, 51];
+
+        let markets = vec![label("a"), label("mirror of a"), label("b")];
+        let prices = vec![cents(&zigzag), cents(&mirrored), cents(&unrelated)];
+        let now = Utc::now();
+
+        let report = report(markets, &prices, now, now, Duration::hours(1), 10);
+
+        assert_eq!(report.matrix[0][0], Some(1.0));
+        assert!((report.matrix[0][1].unwrap() + 1.0).abs() < 1e-9);
+        assert_eq!(report.pairs.len(), 3);
+        assert_eq!(report.pairs[0].market_a, report.markets[0].id);
+        assert_eq!(report.pairs[0].market_b, report.markets[1].id);
+        assert_eq!(report.pairs[0].observations, 11);
+    }
+
+    #[test]
+    fn needs_enough_overlap() {
+        let mut late = vec![None; 8];
+        late.extend(cents(&[50, 55, 48, 60, 52, 58]));
+        let early = cents(&[50, 55, 48, 60, 52, 58, 45, 50, 62, 55, 49, 57, 50, 51]);
+        let now = Utc::now();
+
+        let report = report(
+            vec![label("early"), label("late")],
+            &[early, late],
+            now,
+            now,
+            Duration::hours(1),
+            10,
+        );
+
+        assert!(report.matrix[0][0].is_some());
+        assert_eq!(report.matrix[0][1], None);
+        assert!(report.pairs.is_empty());
+    }
+}
@@ -1,3 +1,9 @@
+pub mod correlation;
+pub mod metrics;
+pub mod migrations;
 pub mod models;
+pub mod series;
+pub mod stats;
+pub mod store;
 
 pub use models::*;
@@ -0,0 +1,229 @@
+//! Volatility, momentum and volume metrics derived from a market's price history.
+//!
+//! Everything is computed from hourly closes, so markets collected at
+//! different intervals are comparable.
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use uuid::Uuid;
+
+use crate::models::{Decimal, MarketMetrics, PriceHistory};
+
+/// How far back metrics look
+pub const LOOKBACK: Duration = Duration::days(30);
+
+/// A YES price move at least this large counts as significant
+pub const SIGNIFICANT_MOVE: Decimal = Decimal::from_parts(5, 0, 0, false, 2);
+
+/// Prices are clamped into `[PRICE_CLAMP, 1 - PRICE_CLAMP]` before taking log-odds,
+/// which are unbounded at 0 and 1
+const PRICE_CLAMP: f64 = 0.01;
+
+/// Compute a market's metrics as of `now` from its snapshots, in any order
+/// and at any resolution
+pub fn compute(market_id: Uuid, history: &[PriceHistory], now: DateTime<Utc>) -> MarketMetrics {
+    let closes = hourly_closes(history, now - LOOKBACK, now);
+
+    MarketMetrics {
+        market_id,
+        volatility_24h: volatility(&closes, now - Duration::hours(24)),
+        volatility_7d: volatility(&closes, now - Duration::days(7)),
+        volatility_30d: volatility(&closes, now - LOOKBACK),
+        momentum_24h: momentum(&closes, now - Duration::hours(24)),
+        momentum_7d: momentum(&closes, now - Duration::days(7)),
+        max_drawdown_30d: max_drawdown(&closes),
+        last_move_at: last_move_at(&closes),
+        volume_acceleration: volume_acceleration(&closes, now),
+        samples: closes.len() as i32,
+        computed_at: now,
+    }
+}
+
+/// The last snapshot of each hour in `[from, to]`, oldest first
+fn hourly_closes(
+    history: &[PriceHistory],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<&PriceHistory> {
+    let mut snapshots: Vec<&PriceHistory> = history
+        .iter()
+        .filter(|s| s.recorded_at >= from && s.recorded_at <= to)
+        .collect();
+    snapshots.sort_by_key(|s| s.recorded_at);
+
+    let hour = |s: &PriceHistory| s.recorded_at.duration_trunc(Duration::hours(1)).ok();
+    let mut closes: Vec<&PriceHistory> = Vec::with_capacity(snapshots.len());
+    for snapshot in snapshots {
+        match closes.last_mut() {
+            Some(last) if hour(last) == hour(snapshot) => *last = snapshot,
+            _ => closes.push(snapshot),
+        }
+    }
+
+    closes
+}
+
+pub(crate) fn log_odds(price: Decimal) -> f64 {
+    let p = f64::try_from(price)
+        .unwrap_or_default()
+        .clamp(PRICE_CLAMP, 1.0 - PRICE_CLAMP);
+    (p / (1.0 - p)).ln()
+}
+
+/// Realized volatility: root of the summed squared log-odds returns since `since`
+fn volatility(closes: &[&PriceHistory], since: DateTime<Utc>) -> Option<f64> {
+    let window: Vec<f64> = closes
+        .iter()
+        .filter(|s| s.recorded_at >= since)
+        .map(|s| log_odds(s.yes_price))
+        .collect();
+    if window.len() < 3 {
+        return None;
+    }
+
+    let sum_squares: f64 = window
+        .windows(2)
+        .map(|pair| (pair[1] - pair[0]).powi(2))
+        .sum();
+    Some(sum_squares.sqrt())
+}
+
+/// YES price change since the last close at or before `since`
+fn momentum(closes: &[&PriceHistory], since: DateTime<Utc>) -> Option<Decimal> {
+    let start = closes.iter().rev().find(|s| s.recorded_at <= since)?;
+    let latest = closes.last()?;
+    Some(latest.yes_price - start.yes_price)
+}
+
+/// Largest fall of the YES price from a preceding peak
+fn max_drawdown(closes: &[&PriceHistory]) -> Option<Decimal> {
+    let mut peak = closes.first()?.yes_price;
+    let mut drawdown = Decimal::ZERO;
+
+    for close in closes {
+        peak = peak.max(close.yes_price);
+        drawdown = drawdown.max(peak - close.yes_price);
+    }
+
+    Some(drawdown)
+}
+
+/// When the price settled within `SIGNIFICANT_MOVE` of where it is now: the
+/// first close after the latest one that was further away
+fn last_move_at(closes: &[&PriceHistory]) -> Option<DateTime<Utc>> {
+    let latest = closes.last()?.yes_price;
+    let away = closes
+        .iter()
+        .rposition(|s| (s.yes_price - latest).abs() >= SIGNIFICANT_MOVE)?;
+
+    closes.get(away + 1).map(|s| s.recorded_at)
+}
+
+/// Volume traded in the last 24 hours relative to the 24 hours before, minus
+/// one: 0.5 means trading picked up by half
+fn volume_acceleration(closes: &[&PriceHistory], now: DateTime<Utc>) -> Option<f64> {
+    let volume_at = |at: DateTime<Utc>| {
+        closes
+            .iter()
+            .rev()
+            .find(|s| s.recorded_at <= at)
+            .map(|s| s.volume)
+    };
+
+    let latest = closes.last()?.volume;
+    let day_ago = volume_at(now - Duration::hours(24))?;
+    let two_days_ago = volume_at(now - Duration::hours(48))?;
+
+    let previous = day_ago - two_days_ago;
+    if previous <= Decimal::ZERO {
+        return None;
+    }
+
+    f64::try_from((latest - day_ago) / previous - Decimal::ONE).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(at: DateTime<Utc>, yes_cents: i64, volume: i64) -> PriceHistory {
+        let yes_price = Decimal::new(yes_cents, 2);
+        PriceHistory {
+            id: Uuid::new_v4(),
+            market_id: Uuid::nil(),
+            yes_price,
+            no_price: Decimal::ONE - yes_price,
+            volume: Decimal::from(volume),
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2024-06-10T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn flat_market_has_no_volatility_or_momentum() {
+        let history: Vec<PriceHistory> = (0..72)
+            .map(|h| snapshot(now() - Duration::hours(h), 50, 1000))
+            .collect();
+
+        let metrics = compute(Uuid::nil(), &history, now());
+
+        assert_eq!(metrics.samples, 72);
+        assert_eq!(metrics.volatility_24h, Some(0.0));
+        assert_eq!(metrics.momentum_24h, Some(Decimal::ZERO));
+        assert_eq!(metrics.momentum_7d, None);
+        assert_eq!(metrics.max_drawdown_30d, Some(Decimal::ZERO));
+        assert_eq!(metrics.last_move_at, None);
+        assert_eq!(metrics.volume_acceleration, None);
+    }
+
+    #[test]
+    fn measures_moves_drawdown_and_volume_pickup() {
+        // 60 -> 80 three days ago, down to 70 yesterday; 10 traded per hour
+        // in the day before last, 20 per hour since
+        let history: Vec<PriceHistory> = (0..=96)
+            .rev()
+            .map(|h| {
+                let at = now() - Duration::hours(h);
+                let price = match h {
+                    72.. => 60,
+                    24.. => 80,
+                    _ => 70,
+                };
+                let volume = if h >= 24 {
+                    10 * (96 - h)
+                } else {
+                    720 + 20 * (24 - h)
+                };
+                snapshot(at, price, volume)
+            })
+            .collect();
+
+        let metrics = compute(Uuid::nil(), &history, now());
+
+        assert_eq!(metrics.momentum_24h, Some(Decimal::new(-10, 2)));
+        assert_eq!(metrics.max_drawdown_30d, Some(Decimal::new(10, 2)));
+        assert_eq!(metrics.last_move_at, Some(now() - Duration::hours(23)));
+        assert_eq!(metrics.volume_acceleration, Some(1.0));
+        assert!(metrics.volatility_24h.unwrap() > 0.0);
+        assert!(metrics.volatility_7d.unwrap() > metrics.volatility_24h.unwrap());
+    }
+
+    #[test]
+    fn uses_the_last_snapshot_of_each_hour() {
+        let hour = now() - Duration::hours(1);
+        let history = vec![
+            snapshot(hour, 40, 0),
+            snapshot(hour + Duration::minutes(30), 45, 0),
+            snapshot(now(), 45, 0),
+        ];
+
+        let closes = hourly_closes(&history, now() - LOOKBACK, now());
+
+        assert_eq!(closes.len(), 2);
+        assert_eq!(closes[0].yes_price, Decimal::new(45, 2));
+    }
+}
@@ -0,0 +1,102 @@
+use sqlx::migrate::{MigrateError, Migrator};
+
+use crate::store::Database;
+
+/// Migrations from the workspace `migrations/` directory, embedded at compile time
+pub static MIGRATOR: Migrator = sqlx::migrate!("../../migrations");
+
+/// SQLite equivalents from `migrations/sqlite/`, versioned independently
+#[cfg(feature = "sqlite")]
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("../../migrations/sqlite");
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchemaError {
+    #[error(transparent)]
+    Migrate(#[from] MigrateError),
+
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+
+    #[error(
+        "database schema version {database} is newer than this binary supports ({binary}); \
+         upgrade the binary before starting it"
+    )]
+    DatabaseAhead { database: i64, binary: i64 },
+}
+
+/// Migrations for the backend `db` runs on
+fn migrator(db: &Database) -> &'static Migrator {
+    match db {
+        Database::Postgres(_) => &MIGRATOR,
+        #[cfg(feature = "sqlite")]
+        Database::Sqlite(_) => &SQLITE_MIGRATOR,
+    }
+}
+
+/// Latest migration version embedded in this binary for `db`'s backend
+pub fn binary_version(db: &Database) -> i64 {
+    migrator(db).iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+/// Latest migration version applied to the database, 0 if none
+pub async fn database_version(db: &Database) -> Result<i64, sqlx::Error> {
+    let version: Option<i64> = match db {
+        Database::Postgres(pool) => {
+            sqlx::query_scalar(
+                r#"
+                SELECT CASE
+                    WHEN to_regclass('_sqlx_migrations') IS NULL THEN NULL
+                    ELSE (SELECT MAX(version) FROM _sqlx_migrations WHERE success)
+                END
+                "#,
+            )
+            .fetch_one(pool)
+            .await?
+        }
+        #[cfg(feature = "sqlite")]
+        Database::Sqlite(pool) => {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = '_sqlx_migrations')",
+            )
+            .fetch_one(pool)
+            .await?;
+
+            if exists {
+                sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+                    .fetch_one(pool)
+                    .await?
+            } else {
+                None
+            }
+        }
+    };
+
+    Ok(version.unwrap_or(0))
+}
+
+/// Apply all pending migrations
+pub async fn run(db: &Database) -> Result<(), SchemaError> {
+    match db {
+        Database::Postgres(pool) => MIGRATOR.run(pool).await?,
+        #[cfg(feature = "sqlite")]
+        Database::Sqlite(pool) => SQLITE_MIGRATOR.run(pool).await?,
+    }
+    Ok(())
+}
+
+/// Refuse to run against a database migrated by a newer binary.
+///
+/// Returns the number of migrations the database is behind this binary.
+pub async fn check_version(db: &Database) -> Result<i64, SchemaError> {
+    let database = database_version(db).await?;
+    let binary = binary_version(db);
+
+    if database > binary {
+        return Err(SchemaError::DatabaseAhead { database, binary });
+    }
+
+    Ok(migrator(db)
+        .iter()
+        .filter(|m| m.version > database)
+        .count() as i64)
+}
@@ -1,28 +1,51 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+pub use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+/// Decimal places stored for prices, which are probabilities in 0..=1
+pub const PRICE_DP: u32 = 6;
+
+/// Decimal places stored for USD volume and liquidity
+pub const AMOUNT_DP: u32 = 2;
+
+/// Parse a decimal written plainly (`0.65`) or in scientific notation (`6.5e-1`)
+pub fn parse_decimal(raw: &str) -> Option<Decimal> {
+    let raw = raw.trim();
+    raw.parse()
+        .or_else(|_| Decimal::from_scientific(raw))
+        .ok()
+}
+
 /// Prediction market metadata and current state
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Market {
     pub id: Uuid,
     pub source_id: String,
-    pub source: String,
+    pub source: MarketSource,
     pub title: String,
     pub description: String,
-    pub category: Option<String>,
+    /// Canonical category, derived from the venue's own category and the title
+    pub category: Option<Category>,
+    /// Category exactly as the venue sent it
+    pub source_category: Option<String>,
+    /// Entities mentioned in the title, as `kind:name` (e.g. `asset:btc`)
     pub tags: Option<Vec<String>>,
-    pub yes_price: f32,
-    pub no_price: f32,
-    pub volume: f32,
-    pub volume_24h: f32,
-    pub liquidity: Option<f32>,
-    pub status: String,
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub volume: Decimal,
+    pub volume_24h: Decimal,
+    pub liquidity: Option<Decimal>,
+    pub status: MarketStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub close_at: Option<DateTime<Utc>>,
     pub url: String,
+    /// Created by an import and not yet seen by a collector, so its status
+    /// and metadata are stand-ins rather than anything the venue said
+    #[serde(skip)]
+    pub placeholder: bool,
 }
 
 /// Time-series snapshot of market prices and metrics
@@ -30,11 +53,11 @@ pub struct Market {
 pub struct PriceHistory {
     pub id: Uuid,
     pub market_id: Uuid,
-    pub yes_price: f32,
-    pub no_price: f32,
-    pub volume: f32,
-    pub volume_24h: f32,
-    pub liquidity: Option<f32>,
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub volume: Decimal,
+    pub volume_24h: Decimal,
+    pub liquidity: Option<Decimal>,
     pub recorded_at: DateTime<Utc>,
 }
 
@@ -42,17 +65,21 @@ pub struct PriceHistory {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateMarket {
     pub source_id: String,
-    pub source: String,
+    pub source: MarketSource,
     pub title: String,
     pub description: String,
-    pub category: Option<String>,
+    /// Canonical category, derived from the venue's own category and the title
+    pub category: Option<Category>,
+    /// Category exactly as the venue sent it
+    pub source_category: Option<String>,
+    /// Entities mentioned in the title, as `kind:name` (e.g. `asset:btc`)
     pub tags: Option<Vec<String>>,
-    pub yes_price: f32,
-    pub no_price: f32,
-    pub volume: f32,
-    pub volume_24h: f32,
-    pub liquidity: Option<f32>,
-    pub status: String,
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub volume: Decimal,
+    pub volume_24h: Decimal,
+    pub liquidity: Option<Decimal>,
+    pub status: MarketStatus,
     pub close_at: Option<DateTime<Utc>>,
     pub url: String,
 }
@@ -60,15 +87,338 @@ pub struct CreateMarket {
 /// Request to update a market
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateMarket {
-    pub yes_price: Option<f32>,
-    pub no_price: Option<f32>,
-    pub volume: Option<f32>,
-    pub volume_24h: Option<f32>,
-    pub liquidity: Option<f32>,
-    pub status: Option<String>,
+    pub yes_price: Option<Decimal>,
+    pub no_price: Option<Decimal>,
+    pub volume: Option<Decimal>,
+    pub volume_24h: Option<Decimal>,
+    pub liquidity: Option<Decimal>,
+    pub status: Option<MarketStatus>,
     pub close_at: Option<DateTime<Utc>>,
 }
 
+/// One field of a market changed by an upsert. Values are stored as text;
+/// timestamps as RFC 3339.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MarketRevision {
+    pub id: Uuid,
+    pub market_id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// A field change about to be written to `market_revisions`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketChange {
+    pub field: &'static str,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+impl Market {
+    /// Metadata fields versioned in `market_revisions`
+    pub const REVISED_FIELDS: [&'static str; 4] = ["title", "description", "status", "close_at"];
+
+    /// Revised fields that `next` would change
+    pub fn changes(&self, next: &CreateMarket) -> Vec<MarketChange> {
+        let pairs = [
+            ("title", Some(self.title.clone()), Some(next.title.clone())),
+            (
+                "description",
+                Some(self.description.clone()),
+                Some(next.description.clone()),
+            ),
+            (
+                "status",
+                Some(self.status.to_string()),
+                Some(next.status.to_string()),
+            ),
+            (
+                "close_at",
+                self.close_at.map(|t| t.to_rfc3339()),
+                next.close_at.map(|t| t.to_rfc3339()),
+            ),
+        ];
+
+        pairs
+            .into_iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(field, old_value, new_value)| MarketChange {
+                field,
+                old_value,
+                new_value,
+            })
+            .collect()
+    }
+
+    /// The market as it was when `snapshot` was taken: prices and volume from
+    /// the snapshot, metadata rolled back through the revisions made since.
+    ///
+    /// `later` must hold the market's revisions after the snapshot time,
+    /// oldest first.
+    pub fn as_of(mut self, snapshot: &PriceHistory, later: &[MarketRevision]) -> Market {
+        self.yes_price = snapshot.yes_price;
+        self.no_price = snapshot.no_price;
+        self.volume = snapshot.volume;
+        self.volume_24h = snapshot.volume_24h;
+        self.liquidity = snapshot.liquidity;
+        self.updated_at = snapshot.recorded_at;
+
+        // The earliest change to each field after the snapshot holds its value back then
+        for field in Self::REVISED_FIELDS {
+            let Some(revision) = later.iter().find(|r| r.field == field) else {
+                continue;
+            };
+            let old = revision.old_value.clone();
+
+            match field {
+                "title" => self.title = old.unwrap_or_default(),
+                "description" => self.description = old.unwrap_or_default(),
+                "status" => {
+                    if let Some(status) = old.and_then(|s| s.parse().ok()) {
+                        self.status = status;
+                    }
+                }
+                "close_at" => {
+                    self.close_at = old
+                        .and_then(|t| DateTime::parse_from_rfc3339(&t).ok())
+                        .map(|t| t.with_timezone(&Utc))
+                }
+                _ => {}
+            }
+        }
+
+        self
+    }
+}
+
+/// Audit record of a collection cycle or one source step within it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CollectionRun {
+    pub id: Uuid,
+    pub kind: String,
+    pub parent_id: Option<Uuid>,
+    pub source: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub markets_fetched: i32,
+    pub markets_recorded: i32,
+    pub markets_failed: i32,
+    pub error: Option<String>,
+    pub http_status: Option<i32>,
+}
+
+/// A collected market that failed validation, held back with the venue payload
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct QuarantinedMarket {
+    pub id: Uuid,
+    pub source: MarketSource,
+    /// Venue id, when the payload had one
+    pub source_id: Option<String>,
+    /// Names of the failed checks, e.g. `price_range`
+    pub checks: Vec<String>,
+    /// Every failure, human readable
+    pub reason: String,
+    pub payload: serde_json::Value,
+    /// First time the market failed these checks
+    pub quarantined_at: DateTime<Utc>,
+    /// Latest time it failed them; `reason` and `payload` are from then
+    pub last_seen_at: DateTime<Utc>,
+}
+
+/// Quarantined markets from one source, broken down by failed check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceQuality {
+    pub source: MarketSource,
+    pub quarantined: i64,
+    /// Most frequent first; a market failing several checks counts under each
+    pub checks: Vec<CheckCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckCount {
+    pub check: String,
+    pub count: i64,
+}
+
+/// A recorded snapshot flagged as implausible against the market's recent history
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Anomaly {
+    pub market_id: Uuid,
+    /// `recorded_at` of the flagged snapshot
+    pub recorded_at: DateTime<Utc>,
+    /// `price_spike`, `volume_decrease` or `stale`
+    pub kind: String,
+    /// z-score of the price change, for `price_spike`
+    pub score: Option<f64>,
+    pub detail: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Derived price and volume metrics for a market, refreshed periodically by the worker
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MarketMetrics {
+    pub market_id: Uuid,
+    /// Realized volatility of hourly log-odds returns of the YES price
+    pub volatility_24h: Option<f64>,
+    pub volatility_7d: Option<f64>,
+    pub volatility_30d: Option<f64>,
+    /// YES price change over the window
+    pub momentum_24h: Option<Decimal>,
+    pub momentum_7d: Option<Decimal>,
+    /// Largest fall of the YES price from a preceding peak in the last 30 days
+    pub max_drawdown_30d: Option<Decimal>,
+    /// When the YES price last settled after a move of 5 points or more
+    pub last_move_at: Option<DateTime<Utc>>,
+    /// Volume traded in the last 24h relative to the 24h before, minus one
+    pub volume_acceleration: Option<f64>,
+    /// Hourly closes the metrics were computed from
+    pub samples: i32,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl MarketMetrics {
+    /// Metrics markets can be listed by (`sort=` on `/api/markets`)
+    pub const SORTABLE: [&'static str; 8] = [
+        "volatility_24h",
+        "volatility_7d",
+        "volatility_30d",
+        "momentum_24h",
+        "momentum_7d",
+        "max_drawdown_30d",
+        "last_move_at",
+        "volume_acceleration",
+    ];
+}
+
+/// Pairwise correlation of price returns between markets, resampled onto a
+/// common time grid
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationReport {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Grid spacing in seconds
+    pub interval_seconds: i64,
+    /// Markets in matrix order
+    pub markets: Vec<MarketLabel>,
+    /// `matrix[i][j]` correlates markets `i` and `j`; `None` with too little
+    /// overlapping history or a flat price
+    pub matrix: Vec<Vec<Option<f64>>>,
+    /// Most strongly correlated pairs first, positive or negative
+    pub pairs: Vec<CorrelatedPair>,
+}
+
+/// Enough of a market to name it in a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketLabel {
+    pub id: Uuid,
+    pub source: MarketSource,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelatedPair {
+    pub market_a: Uuid,
+    pub market_b: Uuid,
+    pub correlation: f64,
+    /// Grid steps where both markets had a return
+    pub observations: usize,
+}
+
+/// Several markets' prices on one time grid, for overlaying and comparing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedHistory {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Grid spacing in seconds
+    pub interval_seconds: i64,
+    /// Markets in column order
+    pub markets: Vec<MarketLabel>,
+    /// One row per grid point, oldest first
+    pub rows: Vec<AlignedRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignedRow {
+    pub at: DateTime<Utc>,
+    /// YES price of each market carried forward from its last snapshot at or
+    /// before `at`; `None` where there is none, or it is older than allowed
+    pub prices: Vec<Option<Decimal>>,
+}
+
+/// How many markets carry each canonical category and the most common tags
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Taxonomy {
+    /// Every canonical category, including empty ones
+    pub categories: Vec<CategoryCount>,
+    /// Most used tags first
+    pub tags: Vec<TagCount>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCount {
+    pub category: Category,
+    pub markets: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub markets: i64,
+}
+
+/// Extent of the price history stored for a market
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistorySpan {
+    /// Earliest snapshot or rollup bucket
+    pub first_at: Option<DateTime<Utc>>,
+    pub last_at: Option<DateTime<Utc>>,
+    /// Raw snapshots still stored, flagged ones included
+    pub snapshots: i64,
+}
+
+/// Figures derived from a market's price history for its detail view
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub first_snapshot_at: Option<DateTime<Utc>>,
+    pub last_snapshot_at: Option<DateTime<Utc>>,
+    pub snapshot_count: i64,
+    /// YES price change since the last snapshot at or before the window start
+    pub change_1h: Option<Decimal>,
+    pub change_24h: Option<Decimal>,
+    pub change_7d: Option<Decimal>,
+    /// YES price range over the last 24 hours
+    pub high_24h: Option<Decimal>,
+    pub low_24h: Option<Decimal>,
+}
+
+/// Market on the other venue that looks like the same question
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Equivalent {
+    #[serde(flatten)]
+    pub market: Market,
+    /// Overlap of title words, 0 to 1
+    pub similarity: f64,
+}
+
+/// A market with the extras requested through `include=`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketDetail {
+    #[serde(flatten)]
+    pub market: Market,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<MarketStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equivalents: Option<Vec<Equivalent>>,
+    /// Latest metadata changes, newest first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revisions: Option<Vec<MarketRevision>>,
+    /// `None` as well when the worker hasn't computed them yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MarketMetrics>,
+}
+
 /// Search result with relevance score
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
@@ -78,7 +428,7 @@ pub struct SearchResult {
 }
 
 /// Market source platforms
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum MarketSource {
     Polymarket,
@@ -86,6 +436,9 @@ pub enum MarketSource {
 }
 
 impl MarketSource {
+    /// Every supported source, in collection order
+    pub const ALL: [MarketSource; 2] = [MarketSource::Polymarket, MarketSource::Kalshi];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             MarketSource::Polymarket => "polymarket",
@@ -112,8 +465,70 @@ impl std::str::FromStr for MarketSource {
     }
 }
 
+/// A market named by its id or by its venue identity, written `<uuid>` or
+/// `<source>:<source_id>` (e.g. `kalshi:FED-25DEC`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MarketRef {
+    Id(Uuid),
+    Source {
+        source: MarketSource,
+        source_id: String,
+    },
+}
+
+impl MarketRef {
+    pub fn matches(&self, market: &Market) -> bool {
+        match self {
+            MarketRef::Id(id) => market.id == *id,
+            MarketRef::Source { source, source_id } => {
+                market.source == *source && market.source_id == *source_id
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for MarketRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketRef::Id(id) => write!(f, "{}", id),
+            MarketRef::Source { source, source_id } => write!(f, "{}:{}", source, source_id),
+        }
+    }
+}
+
+impl std::str::FromStr for MarketRef {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(id) = s.parse() {
+            return Ok(MarketRef::Id(id));
+        }
+
+        match s.split_once(':') {
+            Some((source, source_id)) if !source_id.is_empty() => Ok(MarketRef::Source {
+                source: source.parse()?,
+                source_id: source_id.to_string(),
+            }),
+            _ => Err(format!(
+                "Not a market ID or <source>:<source_id> (e.g. kalshi:TICKER): {}",
+                s
+            )),
+        }
+    }
+}
+
+/// Markets found for a batch of references
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketBatch {
+    /// In request order
+    pub markets: Vec<Market>,
+    /// References no market matched
+    pub missing: Vec<MarketRef>,
+}
+
 /// Market status
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum MarketStatus {
     Open,
@@ -136,3 +551,300 @@ impl std::fmt::Display for MarketStatus {
         write!(f, "{}", self.as_str())
     }
 }
+
+impl std::str::FromStr for MarketStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "open" => Ok(MarketStatus::Open),
+            "closed" => Ok(MarketStatus::Closed),
+            "resolved" => Ok(MarketStatus::Resolved),
+            _ => Err(format!("Unknown market status: {}", s)),
+        }
+    }
+}
+
+/// Canonical market category shared by every venue
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Politics,
+    Economics,
+    Finance,
+    Crypto,
+    Sports,
+    Science,
+    Technology,
+    Climate,
+    Health,
+    Culture,
+    World,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 12] = [
+        Category::Politics,
+        Category::Economics,
+        Category::Finance,
+        Category::Crypto,
+        Category::Sports,
+        Category::Science,
+        Category::Technology,
+        Category::Climate,
+        Category::Health,
+        Category::Culture,
+        Category::World,
+        Category::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::Politics => "politics",
+            Category::Economics => "economics",
+            Category::Finance => "finance",
+            Category::Crypto => "crypto",
+            Category::Sports => "sports",
+            Category::Science => "science",
+            Category::Technology => "technology",
+            Category::Climate => "climate",
+            Category::Health => "health",
+            Category::Culture => "culture",
+            Category::World => "world",
+            Category::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        Category::ALL
+            .into_iter()
+            .find(|c| c.as_str() == name)
+            .ok_or_else(|| format!("Unknown market category: {}", s))
+    }
+}
+
+/// Store the enums as their lowercase names in TEXT columns, on any backend
+macro_rules! text_enum {
+    ($ty:ty) => {
+        impl<DB: sqlx::Database> sqlx::Type<DB> for $ty
+        where
+            str: sqlx::Type<DB>,
+        {
+            fn type_info() -> DB::TypeInfo {
+                <str as sqlx::Type<DB>>::type_info()
+            }
+
+            fn compatible(ty: &DB::TypeInfo) -> bool {
+                <str as sqlx::Type<DB>>::compatible(ty)
+            }
+        }
+
+        impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for $ty
+        where
+            &'q str: sqlx::Encode<'q, DB>,
+        {
+            fn encode_by_ref(
+                &self,
+                buf: &mut <DB as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+            ) -> sqlx::encode::IsNull {
+                self.as_str().encode_by_ref(buf)
+            }
+        }
+
+        impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for $ty
+        where
+            &'r str: sqlx::Decode<'r, DB>,
+        {
+            fn decode(
+                value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef,
+            ) -> Result<Self, sqlx::error::BoxDynError> {
+                Ok(<&str as sqlx::Decode<DB>>::decode(value)?.parse()?)
+            }
+        }
+    };
+}
+
+text_enum!(MarketSource);
+text_enum!(MarketStatus);
+text_enum!(Category);
+
+/// Granularity of price history served by the API
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Raw,
+    Hourly,
+    Daily,
+}
+
+impl Resolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resolution::Raw => "raw",
+            Resolution::Hourly => "hourly",
+            Resolution::Daily => "daily",
+        }
+    }
+
+    /// Span of one rollup row; `None` for raw snapshots
+    pub fn bucket_width(&self) -> Option<Duration> {
+        match self {
+            Resolution::Raw => None,
+            Resolution::Hourly => Some(Duration::hours(1)),
+            Resolution::Daily => Some(Duration::days(1)),
+        }
+    }
+
+    /// Coarsest stored resolution that still has a point every `step`
+    pub fn for_interval(step: Duration) -> Self {
+        if step >= Duration::days(1) {
+            Resolution::Daily
+        } else if step >= Duration::hours(1) {
+            Resolution::Hourly
+        } else {
+            Resolution::Raw
+        }
+    }
+
+    /// Coarsest resolution that still gives useful detail over `hours`
+    pub fn for_span(hours: Option<i64>) -> Self {
+        match hours {
+            Some(h) if h > 90 * 24 => Resolution::Daily,
+            Some(h) if h > 7 * 24 => Resolution::Hourly,
+            _ => Resolution::Raw,
+        }
+    }
+}
+
+impl std::fmt::Display for Resolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for Resolution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(Resolution::Raw),
+            "hourly" => Ok(Resolution::Hourly),
+            "daily" => Ok(Resolution::Daily),
+            _ => Err(format!("Unknown resolution: {}", s)),
+        }
+    }
+}
+
+/// Raw snapshot with its market's venue identifiers, as written by bulk export
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HistoryExportRow {
+    pub market_id: Uuid,
+    pub source: MarketSource,
+    pub source_id: String,
+    pub recorded_at: DateTime<Utc>,
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub volume: Decimal,
+    pub volume_24h: Decimal,
+    pub liquidity: Option<Decimal>,
+}
+
+/// File format for bulk export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    /// Format implied by a file name's extension
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" | "ndjson" => Ok(ExportFormat::Jsonl),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_scientific_decimals() {
+        assert_eq!(parse_decimal("0.65"), Some(Decimal::new(65, 2)));
+        assert_eq!(parse_decimal(" 1234.5 "), Some(Decimal::new(12345, 1)));
+        assert_eq!(parse_decimal("6.5e-1"), Some(Decimal::new(65, 2)));
+        assert_eq!(parse_decimal("1.2E3"), Some(Decimal::from(1200)));
+        assert_eq!(parse_decimal("0"), Some(Decimal::ZERO));
+        assert_eq!(parse_decimal(""), None);
+        assert_eq!(parse_decimal("n/a"), None);
+    }
+
+    #[test]
+    fn decimals_serialize_as_json_numbers() {
+        let snapshot = PriceHistory {
+            id: Uuid::nil(),
+            market_id: Uuid::nil(),
+            yes_price: Decimal::new(123457, PRICE_DP),
+            no_price: Decimal::new(876543, PRICE_DP),
+            volume: "123456789.01".parse().unwrap(),
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: Utc::now(),
+        };
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+
+        assert_eq!(json["yes_price"], serde_json::json!(0.123457));
+        assert_eq!(json["volume"], serde_json::json!(123456789.01));
+        assert!(json["volume_24h"].is_number());
+        assert!(json["liquidity"].is_null());
+    }
+}
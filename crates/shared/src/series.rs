@@ -0,0 +1,165 @@
+//! Putting snapshots taken at irregular times onto a common time grid, so
+//! several markets can be compared point by point.
+
+use chrono::{DateTime, Duration, DurationRound, Utc};
+
+use crate::models::PriceHistory;
+
+/// Parse a grid interval such as `30s`, `15m`, `1h` or `1d`
+pub fn parse_interval(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let invalid = || format!("Invalid interval: {} (expected e.g. 15m, 1h, 1d)", raw);
+
+    let split = raw.char_indices().last().map_or(0, |(i, _)| i);
+    let (count, unit) = raw.split_at(split);
+    let count: i64 = count.parse().map_err(|_| invalid())?;
+
+    let interval = match unit {
+        "s" => Duration::try_seconds(count),
+        "m" => Duration::try_minutes(count),
+        "h" => Duration::try_hours(count),
+        "d" => Duration::try_days(count),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    if interval <= Duration::zero() {
+        return Err(format!("Interval must be positive: {}", raw));
+    }
+    Ok(interval)
+}
+
+/// Grid points every `step` from `from` (rounded down to a multiple of
+/// `step`) through `to`. Refuses, before allocating, a grid of more than
+/// `max_points`.
+pub fn grid(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: Duration,
+    max_points: usize,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    let start = from.duration_trunc(step).unwrap_or(from);
+    let too_many = || format!("more than {} points", max_points);
+
+    let steps = match (to - start).num_milliseconds().checked_div(step.num_milliseconds()) {
+        Some(steps) if steps < 0 => return Ok(Vec::new()),
+        Some(steps) => steps,
+        None => return Err(too_many()),
+    };
+    if steps >= max_points as i64 {
+        return Err(too_many());
+    }
+
+    let mut points = Vec::with_capacity(steps as usize + 1);
+    let mut at = Some(start);
+    while let Some(point) = at.filter(|at| *at <= to) {
+        points.push(point);
+        at = point.checked_add_signed(step);
+    }
+
+    Ok(points)
+}
+
+/// The snapshot in effect at each grid point: the last one recorded at or
+/// before it, carried forward. `None` before the first snapshot.
+///
+/// `history` may be in any order.
+pub fn resample<'a>(
+    history: &'a [PriceHistory],
+    grid: &[DateTime<Utc>],
+) -> Vec<Option<&'a PriceHistory>> {
+    let mut snapshots: Vec<&PriceHistory> = history.iter().collect();
+    snapshots.sort_by_key(|s| s.recorded_at);
+
+    let mut next = 0;
+    let mut current = None;
+    grid.iter()
+        .map(|at| {
+            while next < snapshots.len() && snapshots[next].recorded_at <= *at {
+                current = Some(snapshots[next]);
+                next += 1;
+            }
+            current
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Decimal;
+    use uuid::Uuid;
+
+    fn snapshot(at: &str, yes_cents: i64) -> PriceHistory {
+        PriceHistory {
+            id: Uuid::new_v4(),
+            market_id: Uuid::nil(),
+            yes_price: Decimal::new(yes_cents, 2),
+            no_price: Decimal::new(100 - yes_cents, 2),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn parses_intervals() {
+        assert_eq!(parse_interval("15m"), Ok(Duration::minutes(15)));
+        assert_eq!(parse_interval("1h"), Ok(Duration::hours(1)));
+        assert_eq!(parse_interval("2d"), Ok(Duration::days(2)));
+        assert!(parse_interval("0h").is_err());
+        assert!(parse_interval("h").is_err());
+        assert!(parse_interval("5w").is_err());
+        assert!(parse_interval("5µ").is_err());
+        assert!(parse_interval("99999999999999d").is_err());
+    }
+
+    #[test]
+    fn carries_the_last_snapshot_forward() {
+        // Snapshots land a little after the hour, newest first as served
+        let history = vec![
+            snapshot("2024-06-01T02:01:30Z", 60),
+            snapshot("2024-06-01T00:59:00Z", 40),
+            snapshot("2024-06-01T00:02:00Z", 30),
+        ];
+        let points = grid(
+            "2024-06-01T00:00:00Z".parse().unwrap(),
+            "2024-06-01T03:00:00Z".parse().unwrap(),
+            Duration::hours(1),
+            10,
+        )
+        .unwrap();
+
+        let prices: Vec<Option<i64>> = resample(&history, &points)
+            .iter()
+            .map(|s| s.map(|s| (s.yes_price * Decimal::ONE_HUNDRED).try_into().unwrap()))
+            .collect();
+
+        assert_eq!(prices, vec![None, Some(40), Some(40), Some(60)]);
+    }
+
+    #[test]
+    fn refuses_a_grid_over_the_cap_before_building_it() {
+        let from: DateTime<Utc> = "2024-06-01T00:00:00Z".parse().unwrap();
+        let day = from + Duration::days(1);
+
+        assert_eq!(grid(from, day, Duration::hours(1), 25).unwrap().len(), 25);
+        assert!(grid(from, day, Duration::hours(1), 24).is_err());
+        assert!(grid(from, from - Duration::hours(1), Duration::hours(1), 1)
+            .unwrap()
+            .is_empty());
+
+        // Spans that would take forever, or run past the last representable time
+        assert!(grid(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::MAX_UTC, Duration::seconds(1), 10)
+            .is_err());
+        let last = grid(
+            DateTime::<Utc>::MAX_UTC - Duration::days(2),
+            DateTime::<Utc>::MAX_UTC,
+            Duration::days(1),
+            10,
+        )
+        .unwrap();
+        assert!(last.len() <= 3 && last.iter().all(|at| *at <= DateTime::<Utc>::MAX_UTC));
+    }
+}
@@ -0,0 +1,136 @@
+//! Summary figures for a market's detail view, and matching of the same
+//! question across venues.
+//!
+//! Unlike `metrics`, these work on snapshots as recorded, so they follow the
+//! latest price rather than the last hourly close.
+
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashSet;
+
+use crate::models::{Decimal, HistorySpan, MarketStats, PriceHistory};
+
+/// How far back the high and low look; history fetched for `compute` should
+/// reach at least this far
+pub const RANGE: Duration = Duration::hours(24);
+
+/// Snapshots in effect at the ends of the windows price changes cover, as
+/// `HistoryStore::snapshots_at` finds them
+#[derive(Debug, Clone, Default)]
+pub struct Anchors {
+    pub latest: Option<PriceHistory>,
+    pub hour_ago: Option<PriceHistory>,
+    pub day_ago: Option<PriceHistory>,
+    pub week_ago: Option<PriceHistory>,
+}
+
+impl Anchors {
+    /// How long before now `hour_ago`, `day_ago` and `week_ago` are taken
+    pub const OFFSETS: [Duration; 3] = [Duration::hours(1), Duration::hours(24), Duration::days(7)];
+}
+
+/// Title words too common to say anything about the question
+const STOPWORDS: &[&str] = &[
+    "the", "will", "and", "for", "before", "after", "than", "with", "by", "in", "on", "of", "to",
+    "be", "at", "or", "an", "a", "is", "win", "end", "yes", "no",
+];
+
+/// Stats as of `now` from the history span, the snapshots of the last
+/// `RANGE` in any order, and the change anchors
+pub fn compute(
+    span: HistorySpan,
+    history: &[PriceHistory],
+    anchors: &Anchors,
+    now: DateTime<Utc>,
+) -> MarketStats {
+    let range = history.iter().filter(|s| s.recorded_at >= now - RANGE && s.recorded_at <= now);
+
+    MarketStats {
+        first_snapshot_at: span.first_at,
+        last_snapshot_at: span.last_at,
+        snapshot_count: span.snapshots,
+        change_1h: change(anchors, &anchors.hour_ago),
+        change_24h: change(anchors, &anchors.day_ago),
+        change_7d: change(anchors, &anchors.week_ago),
+        high_24h: range.clone().map(|s| s.yes_price).max(),
+        low_24h: range.map(|s| s.yes_price).min(),
+    }
+}
+
+/// YES price change from `start` to the latest snapshot
+fn change(anchors: &Anchors, start: &Option<PriceHistory>) -> Option<Decimal> {
+    Some(anchors.latest.as_ref()?.yes_price - start.as_ref()?.yes_price)
+}
+
+/// Share of significant words two titles have in common, from 0 (none) to 1
+/// (the same words)
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (significant_words(a), significant_words(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+fn significant_words(title: &str) -> HashSet<String> {
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && !STOPWORDS.contains(w))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn snapshot(at: DateTime<Utc>, yes_cents: i64) -> PriceHistory {
+        let yes_price = Decimal::new(yes_cents, 2);
+        PriceHistory {
+            id: Uuid::new_v4(),
+            market_id: Uuid::nil(),
+            yes_price,
+            no_price: Decimal::ONE - yes_price,
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at,
+        }
+    }
+
+    #[test]
+    fn changes_and_range_follow_the_latest_snapshot() {
+        let now: DateTime<Utc> = "2024-06-10T12:00:00Z".parse().unwrap();
+        let history = vec![
+            snapshot(now - Duration::minutes(5), 62),
+            snapshot(now - Duration::minutes(65), 58),
+            snapshot(now - Duration::hours(12), 70),
+        ];
+        let anchors = Anchors {
+            latest: Some(history[0].clone()),
+            hour_ago: Some(history[1].clone()),
+            day_ago: Some(snapshot(now - Duration::hours(30), 40)),
+            week_ago: None,
+        };
+
+        let stats = compute(HistorySpan::default(), &history, &anchors, now);
+
+        assert_eq!(stats.change_1h, Some(Decimal::new(4, 2)));
+        assert_eq!(stats.change_24h, Some(Decimal::new(22, 2)));
+        // Nothing recorded a week ago
+        assert_eq!(stats.change_7d, None);
+        assert_eq!(stats.high_24h, Some(Decimal::new(70, 2)));
+        assert_eq!(stats.low_24h, Some(Decimal::new(58, 2)));
+    }
+
+    #[test]
+    fn similarity_ignores_case_punctuation_and_filler() {
+        let kalshi = "Will Bitcoin be above $100k on Dec 31?";
+        let polymarket = "Bitcoin above $100K by December 31";
+        assert!(title_similarity(kalshi, polymarket) >= 0.5);
+        assert_eq!(title_similarity("Fed cuts rates", "Lakers win title"), 0.0);
+        assert_eq!(title_similarity("", "Will it?"), 0.0);
+    }
+}
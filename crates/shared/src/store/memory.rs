@@ -0,0 +1,907 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, BoxStream};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::RwLock;
+use uuid::Uuid;
+
+use super::{
+    summarize, taxonomy, AnomalyStore, ArchiveStore, HistoryStore, MarketFilter, MarketStore,
+    MarketSort, MetricsStore, QuarantineStore, RawPayload, RunStore, Snapshot, SortOrder,
+    SourceRun, StoreError, StoreResult,
+};
+use crate::models::{
+    Anomaly, Category, CollectionRun, CreateMarket, Decimal, HistoryExportRow, HistorySpan,
+    Market, MarketChange, MarketMetrics, MarketRef, MarketRevision, MarketSource, MarketStatus,
+    PriceHistory, QuarantinedMarket, Resolution, SourceQuality, Taxonomy, UpdateMarket,
+};
+
+/// In-process store implementing every store trait, for tests.
+///
+/// Only raw snapshots are kept, so history is served at raw resolution
+/// whatever resolution is requested. Search matches markets whose title or
+/// description contains every query word, case-insensitively.
+#[derive(Default)]
+pub struct MemoryStore {
+    markets: RwLock<Vec<Market>>,
+    revisions: RwLock<Vec<MarketRevision>>,
+    history: RwLock<Vec<PriceHistory>>,
+    runs: RwLock<Vec<CollectionRun>>,
+    quarantine: RwLock<Vec<QuarantinedMarket>>,
+    anomalies: RwLock<Vec<Anomaly>>,
+    metrics: RwLock<Vec<MarketMetrics>>,
+    payloads: RwLock<Vec<RawPayload>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshots recorded in `[since, until]`, newest first
+    fn history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        include_anomalies: bool,
+    ) -> Vec<PriceHistory> {
+        let anomalies = self.anomalies.read().unwrap();
+
+        let mut history: Vec<PriceHistory> = self
+            .history
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|h| h.market_id == market_id)
+            .filter(|h| since.is_none_or(|since| h.recorded_at >= since))
+            .filter(|h| until.is_none_or(|until| h.recorded_at <= until))
+            .filter(|h| {
+                include_anomalies
+                    || !anomalies
+                        .iter()
+                        .any(|a| a.market_id == h.market_id && a.recorded_at == h.recorded_at)
+            })
+            .cloned()
+            .collect();
+
+        history.sort_by_key(|h| std::cmp::Reverse(h.recorded_at));
+        history.truncate(limit.max(0) as usize);
+        history
+    }
+}
+
+#[async_trait]
+impl MarketStore for MemoryStore {
+    async fn upsert(&self, market: CreateMarket) -> StoreResult<Market> {
+        let mut markets = self.markets.write().unwrap();
+        let now = Utc::now();
+
+        if let Some(existing) = markets
+            .iter_mut()
+            .find(|m| m.source == market.source && m.source_id == market.source_id)
+        {
+            existing.title = market.title;
+            existing.description = market.description;
+            existing.category = market.category;
+            existing.source_category = market.source_category;
+            existing.tags = market.tags;
+            existing.yes_price = market.yes_price;
+            existing.no_price = market.no_price;
+            existing.volume = market.volume;
+            existing.volume_24h = market.volume_24h;
+            existing.liquidity = market.liquidity;
+            existing.status = market.status;
+            existing.close_at = market.close_at;
+            if existing.placeholder {
+                existing.url = market.url;
+                existing.placeholder = false;
+            }
+            existing.updated_at = now;
+            return Ok(existing.clone());
+        }
+
+        let created = Market {
+            id: Uuid::new_v4(),
+            source_id: market.source_id,
+            source: market.source,
+            title: market.title,
+            description: market.description,
+            category: market.category,
+            source_category: market.source_category,
+            tags: market.tags,
+            yes_price: market.yes_price,
+            no_price: market.no_price,
+            volume: market.volume,
+            volume_24h: market.volume_24h,
+            liquidity: market.liquidity,
+            status: market.status,
+            created_at: now,
+            updated_at: now,
+            close_at: market.close_at,
+            url: market.url,
+            placeholder: false,
+        };
+        markets.push(created.clone());
+        Ok(created)
+    }
+
+    async fn create_placeholder(&self, market: CreateMarket) -> StoreResult<Market> {
+        if let Ok(existing) = self.get_by_source(market.source, &market.source_id).await {
+            return Ok(existing);
+        }
+
+        let created = self.upsert(market).await?;
+        let mut markets = self.markets.write().unwrap();
+        let stored = markets.iter_mut().find(|m| m.id == created.id).unwrap();
+        stored.placeholder = true;
+        Ok(stored.clone())
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> StoreResult<Market> {
+        self.markets
+            .read()
+            .unwrap()
+            .iter()
+            .find(|m| m.id == id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn get_by_source(&self, source: MarketSource, source_id: &str) -> StoreResult<Market> {
+        self.markets
+            .read()
+            .unwrap()
+            .iter()
+            .find(|m| m.source == source && m.source_id == source_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+
+    async fn lookup(&self, refs: &[MarketRef]) -> StoreResult<Vec<Market>> {
+        Ok(self
+            .markets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|m| refs.iter().any(|r| r.matches(m)))
+            .cloned()
+            .collect())
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        offset: i64,
+        sort_by: MarketSort,
+        order: SortOrder,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<Market>> {
+        let mut markets: Vec<Market> = self
+            .markets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|m| filter.matches(m))
+            .cloned()
+            .collect();
+
+        // Volumes as of then, so the listing sorts and pages on those
+        let mut rewound: HashMap<Uuid, (Decimal, Decimal)> = HashMap::new();
+        if let Some(at) = filter.as_of {
+            let ids: Vec<Uuid> = markets.iter().map(|m| m.id).collect();
+            for snapshot in self.snapshots_at(&ids, at).await? {
+                rewound.insert(snapshot.market_id, (snapshot.volume, snapshot.volume_24h));
+            }
+            markets.retain(|m| rewound.contains_key(&m.id));
+        }
+        let volumes = |market: &Market| {
+            rewound
+                .get(&market.id)
+                .copied()
+                .unwrap_or((market.volume, market.volume_24h))
+        };
+
+        let metrics = self.metrics.read().unwrap();
+        let metric = |market: &Market, key: &str| {
+            metrics
+                .iter()
+                .find(|m| m.market_id == market.id)
+                .and_then(|m| metric_value(m, key))
+        };
+
+        let compare = |a: &Market, b: &Market| -> Ordering {
+            let ordering = match sort_by {
+                MarketSort::Volume => volumes(a).0.cmp(&volumes(b).0),
+                MarketSort::Volume24h => volumes(a).1.cmp(&volumes(b).1),
+                MarketSort::CloseAt => match (a.close_at, b.close_at) {
+                    // NULLS LAST in both directions
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                MarketSort::Metric(key) => match (metric(a, key), metric(b, key)) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                MarketSort::CreatedAt => a.created_at.cmp(&b.created_at),
+            };
+            match order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        };
+        markets.sort_by(compare);
+
+        Ok(markets
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect())
+    }
+
+    async fn top_in_category(&self, category: Category, limit: i64) -> StoreResult<Vec<Market>> {
+        let mut markets: Vec<Market> = self
+            .markets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|m| m.category == Some(category))
+            .cloned()
+            .collect();
+
+        markets.sort_by_key(|m| std::cmp::Reverse(m.volume));
+        markets.truncate(limit.max(0) as usize);
+        Ok(markets)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        limit: i64,
+        status: Option<MarketStatus>,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<(Market, f32)>> {
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(|w| w.to_lowercase())
+            .collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut results: Vec<(Market, f32)> = self
+            .markets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|m| filter.as_of.is_some() || status.is_none_or(|s| m.status == s))
+            .filter(|m| filter.matches(m))
+            .filter_map(|m| {
+                let title = m.title.to_lowercase();
+                let text = format!("{} {}", title, m.description.to_lowercase());
+                if !words.iter().all(|w| text.contains(w.as_str())) {
+                    return None;
+                }
+                // Words found in the title rank higher than description-only matches
+                let in_title = words.iter().filter(|w| title.contains(w.as_str())).count();
+                Some((m.clone(), in_title as f32 / words.len() as f32))
+            })
+            .collect();
+
+        // As of a time, only markets recorded by then, with the status they had
+        if let Some(at) = filter.as_of {
+            let ids: Vec<Uuid> = results.iter().map(|(m, _)| m.id).collect();
+            let recorded: HashSet<Uuid> =
+                self.snapshots_at(&ids, at).await?.iter().map(|s| s.market_id).collect();
+            let later = self.revisions(&ids, Some(at)).await?;
+            results.retain(|(m, _)| {
+                let then = later
+                    .iter()
+                    .find(|r| r.market_id == m.id && r.field == "status")
+                    .and_then(|r| r.old_value.as_deref()?.parse().ok())
+                    .unwrap_or(m.status);
+                recorded.contains(&m.id) && status.is_none_or(|s| then == s)
+            });
+        }
+
+        results.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then(b.volume.cmp(&a.volume))
+        });
+        results.truncate(limit.max(0) as usize);
+
+        Ok(results)
+    }
+
+    async fn update(&self, id: Uuid, update: UpdateMarket) -> StoreResult<Market> {
+        let mut markets = self.markets.write().unwrap();
+        let market = markets
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or(StoreError::NotFound)?;
+
+        if let Some(yes_price) = update.yes_price {
+            market.yes_price = yes_price;
+        }
+        if let Some(no_price) = update.no_price {
+            market.no_price = no_price;
+        }
+        if let Some(volume) = update.volume {
+            market.volume = volume;
+        }
+        if let Some(volume_24h) = update.volume_24h {
+            market.volume_24h = volume_24h;
+        }
+        if update.liquidity.is_some() {
+            market.liquidity = update.liquidity;
+        }
+        if let Some(status) = update.status {
+            market.status = status;
+        }
+        if update.close_at.is_some() {
+            market.close_at = update.close_at;
+        }
+        market.updated_at = Utc::now();
+
+        Ok(market.clone())
+    }
+
+    async fn set_taxonomy(
+        &self,
+        id: Uuid,
+        category: Option<Category>,
+        tags: Option<&[String]>,
+    ) -> StoreResult<()> {
+        let mut markets = self.markets.write().unwrap();
+        let market = markets
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or(StoreError::NotFound)?;
+
+        market.category = category;
+        market.tags = tags.map(<[String]>::to_vec);
+        market.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn taxonomy(&self, tag_limit: i64) -> StoreResult<Taxonomy> {
+        let markets = self.markets.read().unwrap();
+        let mut categories: Vec<(Category, i64)> = Vec::new();
+        let mut tags: Vec<(String, i64)> = Vec::new();
+
+        for market in markets.iter() {
+            if let Some(category) = market.category {
+                match categories.iter_mut().find(|(c, _)| *c == category) {
+                    Some((_, count)) => *count += 1,
+                    None => categories.push((category, 1)),
+                }
+            }
+            for tag in market.tags.iter().flatten() {
+                match tags.iter_mut().find(|(t, _)| t == tag) {
+                    Some((_, count)) => *count += 1,
+                    None => tags.push((tag.clone(), 1)),
+                }
+            }
+        }
+
+        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tags.truncate(tag_limit.max(0) as usize);
+        Ok(taxonomy(categories, tags))
+    }
+
+    async fn record_revisions(
+        &self,
+        market_id: Uuid,
+        changes: &[MarketChange],
+        changed_at: DateTime<Utc>,
+    ) -> StoreResult<()> {
+        let mut revisions = self.revisions.write().unwrap();
+
+        for change in changes {
+            revisions.push(MarketRevision {
+                id: Uuid::new_v4(),
+                market_id,
+                field: change.field.to_string(),
+                old_value: change.old_value.clone(),
+                new_value: change.new_value.clone(),
+                changed_at,
+            });
+        }
+
+        Ok(())
+    }
+
+    async fn revisions(
+        &self,
+        market_ids: &[Uuid],
+        after: Option<DateTime<Utc>>,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        let mut revisions: Vec<MarketRevision> = self
+            .revisions
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|r| market_ids.contains(&r.market_id))
+            .filter(|r| after.is_none_or(|after| r.changed_at > after))
+            .cloned()
+            .collect();
+
+        // Stable, so revisions written together keep their order
+        revisions.sort_by_key(|r| r.changed_at);
+        Ok(revisions)
+    }
+
+    async fn latest_revisions(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        let mut revisions = self.revisions(&[market_id], None).await?;
+        revisions.reverse();
+        revisions.truncate(limit.max(0) as usize);
+        Ok(revisions)
+    }
+}
+
+#[async_trait]
+impl HistoryStore for MemoryStore {
+    async fn record_snapshot(
+        &self,
+        market: &Market,
+        recorded_at: DateTime<Utc>,
+    ) -> StoreResult<Option<PriceHistory>> {
+        let mut history = self.history.write().unwrap();
+
+        if history
+            .iter()
+            .any(|h| h.market_id == market.id && h.recorded_at == recorded_at)
+        {
+            return Ok(None);
+        }
+
+        let snapshot = PriceHistory {
+            id: Uuid::new_v4(),
+            market_id: market.id,
+            yes_price: market.yes_price,
+            no_price: market.no_price,
+            volume: market.volume,
+            volume_24h: market.volume_24h,
+            liquidity: market.liquidity,
+            recorded_at,
+        };
+        history.push(snapshot.clone());
+        Ok(Some(snapshot))
+    }
+
+    async fn span(&self, market_id: Uuid) -> StoreResult<HistorySpan> {
+        let history = self.history.read().unwrap();
+        let times: Vec<_> = history
+            .iter()
+            .filter(|h| h.market_id == market_id)
+            .map(|h| h.recorded_at)
+            .collect();
+
+        Ok(HistorySpan {
+            first_at: times.iter().min().copied(),
+            last_at: times.iter().max().copied(),
+            snapshots: times.len() as i64,
+        })
+    }
+
+    async fn snapshots_at(
+        &self,
+        market_ids: &[Uuid],
+        at: DateTime<Utc>,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let history = self.history.read().unwrap();
+
+        Ok(market_ids
+            .iter()
+            .filter_map(|id| {
+                history
+                    .iter()
+                    .filter(|h| h.market_id == *id && h.recorded_at <= at)
+                    .max_by_key(|h| h.recorded_at)
+                    .cloned()
+            })
+            .collect())
+    }
+
+    async fn import_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        let mut history = self.history.write().unwrap();
+        let mut inserted = 0;
+
+        for snapshot in snapshots {
+            if history
+                .iter()
+                .any(|h| h.market_id == snapshot.market_id && h.recorded_at == snapshot.recorded_at)
+            {
+                continue;
+            }
+
+            history.push(PriceHistory {
+                id: Uuid::new_v4(),
+                market_id: snapshot.market_id,
+                yes_price: snapshot.yes_price,
+                no_price: snapshot.no_price,
+                volume: snapshot.volume,
+                volume_24h: snapshot.volume_24h,
+                liquidity: snapshot.liquidity,
+                recorded_at: snapshot.recorded_at,
+            });
+            inserted += 1;
+        }
+
+        Ok(inserted)
+    }
+
+    async fn replace_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        let mut history = self.history.write().unwrap();
+
+        for snapshot in snapshots {
+            history.retain(|h| {
+                h.market_id != snapshot.market_id || h.recorded_at != snapshot.recorded_at
+            });
+            history.push(PriceHistory {
+                id: Uuid::new_v4(),
+                market_id: snapshot.market_id,
+                yes_price: snapshot.yes_price,
+                no_price: snapshot.no_price,
+                volume: snapshot.volume,
+                volume_24h: snapshot.volume_24h,
+                liquidity: snapshot.liquidity,
+                recorded_at: snapshot.recorded_at,
+            });
+        }
+
+        Ok(snapshots.len() as u64)
+    }
+
+    async fn get_history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        hours: Option<i64>,
+        _resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let since = hours.map(|h| Utc::now() - Duration::hours(h));
+        Ok(self.history(market_id, limit, since, None, include_anomalies))
+    }
+
+    async fn history_between(
+        &self,
+        market_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+        _resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        Ok(self.history(market_id, limit, Some(from), Some(to), include_anomalies))
+    }
+
+    fn export(
+        &self,
+        market_ids: Vec<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<HistoryExportRow>> {
+        let markets = self.markets.read().unwrap();
+
+        let mut rows: Vec<HistoryExportRow> = self
+            .history
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|h| market_ids.is_empty() || market_ids.contains(&h.market_id))
+            .filter(|h| from.is_none_or(|from| h.recorded_at >= from))
+            .filter(|h| to.is_none_or(|to| h.recorded_at < to))
+            .filter_map(|h| {
+                let market = markets.iter().find(|m| m.id == h.market_id)?;
+                Some(HistoryExportRow {
+                    market_id: h.market_id,
+                    source: market.source,
+                    source_id: market.source_id.clone(),
+                    recorded_at: h.recorded_at,
+                    yes_price: h.yes_price,
+                    no_price: h.no_price,
+                    volume: h.volume,
+                    volume_24h: h.volume_24h,
+                    liquidity: h.liquidity,
+                })
+            })
+            .collect();
+
+        rows.sort_by_key(|r| (r.market_id, r.recorded_at));
+        Box::pin(stream::iter(rows.into_iter().map(Ok)))
+    }
+}
+
+#[async_trait]
+impl RunStore for MemoryStore {
+    async fn start_cycle(&self, started_at: DateTime<Utc>) -> StoreResult<Uuid> {
+        let id = Uuid::new_v4();
+        self.runs.write().unwrap().push(CollectionRun {
+            id,
+            kind: "cycle".to_string(),
+            parent_id: None,
+            source: None,
+            started_at,
+            finished_at: None,
+            markets_fetched: 0,
+            markets_recorded: 0,
+            markets_failed: 0,
+            error: None,
+            http_status: None,
+        });
+        Ok(id)
+    }
+
+    async fn finish_cycle(
+        &self,
+        id: Uuid,
+        markets_fetched: i32,
+        markets_recorded: i32,
+        error: Option<String>,
+    ) -> StoreResult<()> {
+        let mut runs = self.runs.write().unwrap();
+        let run = runs
+            .iter_mut()
+            .find(|r| r.id == id)
+            .ok_or(StoreError::NotFound)?;
+
+        run.finished_at = Some(Utc::now());
+        run.markets_fetched = markets_fetched;
+        run.markets_recorded = markets_recorded;
+        run.markets_failed = (markets_fetched - markets_recorded).max(0);
+        run.error = error;
+        Ok(())
+    }
+
+    async fn record_source(&self, cycle_id: Uuid, run: SourceRun) -> StoreResult<()> {
+        self.runs.write().unwrap().push(CollectionRun {
+            id: Uuid::new_v4(),
+            kind: "source".to_string(),
+            parent_id: Some(cycle_id),
+            source: Some(run.source),
+            started_at: run.started_at,
+            finished_at: Some(run.finished_at),
+            markets_fetched: run.markets_fetched,
+            markets_recorded: run.markets_recorded,
+            markets_failed: (run.markets_fetched - run.markets_recorded).max(0),
+            error: run.error,
+            http_status: run.http_status,
+        });
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        source: Option<&str>,
+        failed_only: bool,
+    ) -> StoreResult<Vec<CollectionRun>> {
+        let runs = self.runs.read().unwrap();
+        let cycle_start = |run: &CollectionRun| {
+            run.parent_id
+                .and_then(|parent| runs.iter().find(|r| r.id == parent))
+                .map_or(run.started_at, |parent| parent.started_at)
+        };
+
+        let mut matching: Vec<CollectionRun> = runs
+            .iter()
+            .filter(|r| source.is_none_or(|s| r.source.as_deref() == Some(s)))
+            .filter(|r| !failed_only || r.error.is_some() || r.finished_at.is_none())
+            .cloned()
+            .collect();
+
+        // Newest cycle first, the cycle row ahead of its steps, steps in order
+        matching.sort_by(|a, b| {
+            cycle_start(b)
+                .cmp(&cycle_start(a))
+                .then(a.parent_id.is_some().cmp(&b.parent_id.is_some()))
+                .then(a.started_at.cmp(&b.started_at))
+        });
+        matching.truncate(limit.max(0) as usize);
+
+        Ok(matching)
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for MemoryStore {
+    async fn quarantine(&self, markets: &[QuarantinedMarket]) -> StoreResult<()> {
+        let mut quarantine = self.quarantine.write().unwrap();
+
+        for market in markets {
+            let held = quarantine.iter_mut().find(|q| {
+                q.source == market.source
+                    && q.source_id == market.source_id
+                    && q.checks == market.checks
+            });
+            match held {
+                Some(held) => {
+                    held.reason = market.reason.clone();
+                    held.payload = market.payload.clone();
+                    held.last_seen_at = market.last_seen_at;
+                }
+                None => quarantine.push(market.clone()),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn quarantined(
+        &self,
+        since: DateTime<Utc>,
+        source: Option<MarketSource>,
+        limit: i64,
+    ) -> StoreResult<Vec<QuarantinedMarket>> {
+        let mut matching: Vec<QuarantinedMarket> = self
+            .quarantine
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|q| q.last_seen_at >= since)
+            .filter(|q| source.is_none_or(|s| q.source == s))
+            .cloned()
+            .collect();
+
+        matching.sort_by_key(|q| std::cmp::Reverse(q.last_seen_at));
+        matching.truncate(limit.max(0) as usize);
+        Ok(matching)
+    }
+
+    async fn quarantine_summary(&self, since: DateTime<Utc>) -> StoreResult<Vec<SourceQuality>> {
+        let mut totals: Vec<(MarketSource, i64)> = Vec::new();
+        let mut checks: Vec<(MarketSource, String, i64)> = Vec::new();
+
+        for q in self.quarantine.read().unwrap().iter() {
+            if q.last_seen_at < since {
+                continue;
+            }
+            match totals.iter_mut().find(|(s, _)| *s == q.source) {
+                Some((_, count)) => *count += 1,
+                None => totals.push((q.source, 1)),
+            }
+            for check in &q.checks {
+                match checks.iter_mut().find(|(s, c, _)| *s == q.source && c == check) {
+                    Some((_, _, count)) => *count += 1,
+                    None => checks.push((q.source, check.clone(), 1)),
+                }
+            }
+        }
+
+        Ok(summarize(totals, checks))
+    }
+}
+
+#[async_trait]
+impl AnomalyStore for MemoryStore {
+    async fn flag(&self, anomalies: &[Anomaly]) -> StoreResult<()> {
+        let mut flagged = self.anomalies.write().unwrap();
+
+        for anomaly in anomalies {
+            let exists = flagged.iter().any(|a| {
+                a.market_id == anomaly.market_id
+                    && a.recorded_at == anomaly.recorded_at
+                    && a.kind == anomaly.kind
+            });
+            if !exists {
+                flagged.push(anomaly.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn anomalies(
+        &self,
+        market_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> StoreResult<Vec<Anomaly>> {
+        let mut matching: Vec<Anomaly> = self
+            .anomalies
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|a| since.is_none_or(|since| a.recorded_at >= since))
+            .filter(|a| market_id.is_none_or(|id| a.market_id == id))
+            .cloned()
+            .collect();
+
+        matching.sort_by(|a, b| {
+            b.recorded_at
+                .cmp(&a.recorded_at)
+                .then_with(|| a.kind.cmp(&b.kind))
+        });
+        matching.truncate(limit.max(0) as usize);
+        Ok(matching)
+    }
+}
+
+/// A sortable metric as a number, timestamps as seconds since the epoch
+fn metric_value(metrics: &MarketMetrics, key: &str) -> Option<f64> {
+    let decimal = |d: Option<Decimal>| d.and_then(|d| f64::try_from(d).ok());
+
+    match key {
+        "volatility_24h" => metrics.volatility_24h,
+        "volatility_7d" => metrics.volatility_7d,
+        "volatility_30d" => metrics.volatility_30d,
+        "momentum_24h" => decimal(metrics.momentum_24h),
+        "momentum_7d" => decimal(metrics.momentum_7d),
+        "max_drawdown_30d" => decimal(metrics.max_drawdown_30d),
+        "last_move_at" => metrics.last_move_at.map(|t| t.timestamp() as f64),
+        "volume_acceleration" => metrics.volume_acceleration,
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl MetricsStore for MemoryStore {
+    async fn save_metrics(&self, metrics: &[MarketMetrics]) -> StoreResult<()> {
+        let mut stored = self.metrics.write().unwrap();
+
+        for m in metrics {
+            stored.retain(|s| s.market_id != m.market_id);
+            stored.push(m.clone());
+        }
+
+        Ok(())
+    }
+
+    async fn metrics(&self, market_id: Uuid) -> StoreResult<MarketMetrics> {
+        self.metrics
+            .read()
+            .unwrap()
+            .iter()
+            .find(|m| m.market_id == market_id)
+            .cloned()
+            .ok_or(StoreError::NotFound)
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for MemoryStore {
+    async fn archive(&self, payload: &RawPayload) -> StoreResult<()> {
+        let mut payloads = self.payloads.write().unwrap();
+        if !payloads
+            .iter()
+            .any(|p| p.source == payload.source && p.fetched_at == payload.fetched_at)
+        {
+            payloads.push(payload.clone());
+        }
+        Ok(())
+    }
+
+    fn payloads(
+        &self,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<RawPayload>> {
+        let mut matching: Vec<RawPayload> = self
+            .payloads
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|p| source.is_none_or(|s| p.source == s))
+            .filter(|p| from.is_none_or(|from| p.fetched_at >= from))
+            .filter(|p| to.is_none_or(|to| p.fetched_at < to))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|p| p.fetched_at);
+
+        Box::pin(stream::iter(matching.into_iter().map(Ok)))
+    }
+}
@@ -0,0 +1,561 @@
+//! Storage traits for markets, their price history, collection runs,
+//! quarantined markets, flagged snapshots, derived metrics and archived
+//! venue responses.
+//!
+//! `postgres` holds the production implementation shared by the API and the
+//! worker, `sqlite` (behind the `sqlite` feature) serves single-user local
+//! deployments, and `memory` keeps everything in process for tests.
+
+mod memory;
+mod postgres;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use postgres::{
+    PgAnomalyStore, PgArchiveStore, PgHistoryStore, PgMarketStore, PgMetricsStore,
+    PgQuarantineStore, PgRunStore,
+};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{
+    SqliteAnomalyStore, SqliteArchiveStore, SqliteHistoryStore, SqliteMarketStore,
+    SqliteMetricsStore, SqliteQuarantineStore, SqliteRunStore,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::BoxStream;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::models::{
+    Anomaly, Category, CategoryCount, CheckCount, CollectionRun, CreateMarket, Decimal,
+    HistoryExportRow, HistorySpan, Market, MarketChange, MarketMetrics, MarketRef,
+    MarketRevision, MarketSource, MarketStatus, PriceHistory, QuarantinedMarket, Resolution,
+    SourceQuality, TagCount, Taxonomy, UpdateMarket,
+};
+
+#[derive(Debug, Error)]
+pub enum StoreError {
+    #[error("Resource not found")]
+    NotFound,
+
+    #[error(transparent)]
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for StoreError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => StoreError::NotFound,
+            _ => StoreError::Database(err),
+        }
+    }
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+#[async_trait]
+pub trait MarketStore: Send + Sync {
+    /// Insert a market, or refresh its title, prices, status and taxonomy if
+    /// `(source, source_id)` already exists. A placeholder also takes the url
+    /// and stops being a placeholder.
+    async fn upsert(&self, market: CreateMarket) -> StoreResult<Market>;
+
+    /// Insert a placeholder for a market known only from imported history;
+    /// an existing market is returned unchanged
+    async fn create_placeholder(&self, market: CreateMarket) -> StoreResult<Market>;
+
+    async fn get_by_id(&self, id: Uuid) -> StoreResult<Market>;
+
+    /// Look a market up by its venue identity
+    async fn get_by_source(&self, source: MarketSource, source_id: &str) -> StoreResult<Market>;
+
+    /// Markets matching any of `refs`, in no particular order. References
+    /// that match nothing are skipped.
+    async fn lookup(&self, refs: &[MarketRef]) -> StoreResult<Vec<Market>>;
+
+    /// Page through markets matching `filter`, sorted by `sort_by` in
+    /// `order`. Markets without a value sort last.
+    async fn list(
+        &self,
+        limit: i64,
+        offset: i64,
+        sort_by: MarketSort,
+        order: SortOrder,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<Market>>;
+
+    /// Highest-volume markets in `category`
+    async fn top_in_category(&self, category: Category, limit: i64) -> StoreResult<Vec<Market>>;
+
+    /// Full-text search over title and description of markets matching
+    /// `filter`, best match first. With `filter.as_of`, `status` is the
+    /// status a market had then.
+    async fn search(
+        &self,
+        query: &str,
+        limit: i64,
+        status: Option<MarketStatus>,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<(Market, f32)>>;
+
+    /// Apply the fields set in `update`, leaving the rest unchanged
+    async fn update(&self, id: Uuid, update: UpdateMarket) -> StoreResult<Market>;
+
+    /// Replace a market's canonical category and tags
+    async fn set_taxonomy(
+        &self,
+        id: Uuid,
+        category: Option<Category>,
+        tags: Option<&[String]>,
+    ) -> StoreResult<()>;
+
+    /// Market counts per canonical category and for the `tag_limit` most
+    /// used tags
+    async fn taxonomy(&self, tag_limit: i64) -> StoreResult<Taxonomy>;
+
+    /// Record metadata changes observed on a market at `changed_at`
+    async fn record_revisions(
+        &self,
+        market_id: Uuid,
+        changes: &[MarketChange],
+        changed_at: DateTime<Utc>,
+    ) -> StoreResult<()>;
+
+    /// Revisions of `market_ids` made after `after` (all when `None`), oldest first
+    async fn revisions(
+        &self,
+        market_ids: &[Uuid],
+        after: Option<DateTime<Utc>>,
+    ) -> StoreResult<Vec<MarketRevision>>;
+
+    /// The `limit` latest revisions of a market, newest first
+    async fn latest_revisions(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+    ) -> StoreResult<Vec<MarketRevision>>;
+}
+
+#[async_trait]
+pub trait HistoryStore: Send + Sync {
+    /// Snapshot a market's current prices as of `recorded_at`. Returns `None`
+    /// if a snapshot already exists for the market at that instant.
+    async fn record_snapshot(
+        &self,
+        market: &Market,
+        recorded_at: DateTime<Utc>,
+    ) -> StoreResult<Option<PriceHistory>>;
+
+    /// Price history at `resolution`, newest first, limited to the last
+    /// `hours` when set. Snapshots flagged as anomalies are left out unless
+    /// `include_anomalies`; rollups never include them.
+    async fn get_history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        hours: Option<i64>,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>>;
+
+    /// Price history at `resolution` known by some time in `[from, to]`,
+    /// newest first, with anomalies left out as for `get_history`. Rollup
+    /// rows are stamped at the end of their bucket, when their close was
+    /// known, so carrying them forward never looks ahead.
+    async fn history_between(
+        &self,
+        market_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>>;
+
+    /// First and last snapshot times and the number of raw snapshots stored
+    async fn span(&self, market_id: Uuid) -> StoreResult<HistorySpan>;
+
+    /// Latest snapshot of each of `market_ids` at or before `at`. Markets
+    /// with no snapshot by then are left out.
+    async fn snapshots_at(
+        &self,
+        market_ids: &[Uuid],
+        at: DateTime<Utc>,
+    ) -> StoreResult<Vec<PriceHistory>>;
+
+    /// Insert historical snapshots, skipping any whose `(market_id, recorded_at)`
+    /// already exists. Returns the number of rows inserted.
+    async fn import_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64>;
+
+    /// Insert snapshots, overwriting any already recorded for the same
+    /// `(market_id, recorded_at)`. Returns the number of rows written.
+    async fn replace_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64>;
+
+    /// Raw snapshots of `market_ids` (every market when empty) recorded in
+    /// `[from, to)`, ordered by market then time, streamed from the database
+    fn export(
+        &self,
+        market_ids: Vec<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<HistoryExportRow>>;
+}
+
+/// Narrows market listings and searches; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct MarketFilter {
+    pub category: Option<Category>,
+    /// Exact tag, e.g. `country:us`
+    pub tag: Option<String>,
+    /// Only markets from this venue
+    pub source: Option<MarketSource>,
+    /// Only markets with a snapshot at or before this time, their volumes
+    /// sorted and statuses searched as of then. `matches` can't check it.
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+impl MarketFilter {
+    pub fn matches(&self, market: &Market) -> bool {
+        self.category.is_none_or(|c| market.category == Some(c))
+            && self.tag.as_ref().is_none_or(|tag| {
+                market.tags.as_ref().is_some_and(|tags| tags.contains(tag))
+            })
+            && self.source.is_none_or(|s| market.source == s)
+    }
+}
+
+/// Field `MarketStore::list` sorts by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarketSort {
+    #[default]
+    CreatedAt,
+    Volume,
+    Volume24h,
+    CloseAt,
+    /// One of `MarketMetrics::SORTABLE`
+    Metric(&'static str),
+}
+
+impl MarketSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketSort::CreatedAt => "created_at",
+            MarketSort::Volume => "volume",
+            MarketSort::Volume24h => "volume_24h",
+            MarketSort::CloseAt => "close_at",
+            MarketSort::Metric(metric) => metric,
+        }
+    }
+}
+
+impl std::str::FromStr for MarketSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created_at" => Ok(MarketSort::CreatedAt),
+            "volume" => Ok(MarketSort::Volume),
+            "volume_24h" => Ok(MarketSort::Volume24h),
+            "close_at" => Ok(MarketSort::CloseAt),
+            _ => MarketMetrics::SORTABLE
+                .iter()
+                .find(|metric| **metric == s)
+                .map(|metric| MarketSort::Metric(metric))
+                .ok_or_else(|| format!("Unknown sort field: {}", s)),
+        }
+    }
+}
+
+/// Direction of a `MarketStore::list` sort
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Unknown sort order: {}", s)),
+        }
+    }
+}
+
+/// A snapshot taken at a given time, as loaded from an import file
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub market_id: Uuid,
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
+    pub volume: Decimal,
+    pub volume_24h: Decimal,
+    pub liquidity: Option<Decimal>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// A venue API response body as fetched, compressed by the worker
+#[derive(Debug, Clone)]
+pub struct RawPayload {
+    pub source: MarketSource,
+    pub fetched_at: DateTime<Utc>,
+    pub body: Vec<u8>,
+}
+
+/// One source step of a collection cycle, as written by the worker
+#[derive(Debug, Clone)]
+pub struct SourceRun {
+    pub source: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub markets_fetched: i32,
+    pub markets_recorded: i32,
+    pub error: Option<String>,
+    pub http_status: Option<i32>,
+}
+
+#[async_trait]
+pub trait RunStore: Send + Sync {
+    /// Open a cycle row up front, returning its id
+    async fn start_cycle(&self, started_at: DateTime<Utc>) -> StoreResult<Uuid>;
+
+    /// Close a cycle with its totals; failed markets are those fetched but not recorded
+    async fn finish_cycle(
+        &self,
+        id: Uuid,
+        markets_fetched: i32,
+        markets_recorded: i32,
+        error: Option<String>,
+    ) -> StoreResult<()>;
+
+    async fn record_source(&self, cycle_id: Uuid, run: SourceRun) -> StoreResult<()>;
+
+    /// Most recent runs, each cycle followed by its source steps
+    async fn list(
+        &self,
+        limit: i64,
+        source: Option<&str>,
+        failed_only: bool,
+    ) -> StoreResult<Vec<CollectionRun>>;
+}
+
+#[async_trait]
+pub trait QuarantineStore: Send + Sync {
+    /// Hold back markets that failed validation. A market already held for
+    /// the same checks keeps one row: its `quarantined_at` stays, while
+    /// `last_seen_at`, `reason` and `payload` are refreshed.
+    async fn quarantine(&self, markets: &[QuarantinedMarket]) -> StoreResult<()>;
+
+    /// Markets seen failing validation since `since`, most recently seen first
+    async fn quarantined(
+        &self,
+        since: DateTime<Utc>,
+        source: Option<MarketSource>,
+        limit: i64,
+    ) -> StoreResult<Vec<QuarantinedMarket>>;
+
+    /// Per-source totals and failed-check counts of markets seen failing since `since`
+    async fn quarantine_summary(&self, since: DateTime<Utc>) -> StoreResult<Vec<SourceQuality>>;
+}
+
+#[async_trait]
+pub trait AnomalyStore: Send + Sync {
+    /// Flag snapshots. Flagging a snapshot twice for the same kind is a no-op.
+    async fn flag(&self, anomalies: &[Anomaly]) -> StoreResult<()>;
+
+    /// Anomalies on snapshots of `market_id` (every market when `None`)
+    /// recorded since `since` (ever when `None`), newest first
+    async fn anomalies(
+        &self,
+        market_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> StoreResult<Vec<Anomaly>>;
+}
+
+#[async_trait]
+pub trait MetricsStore: Send + Sync {
+    /// Store metrics, replacing any previously computed for the same markets
+    async fn save_metrics(&self, metrics: &[MarketMetrics]) -> StoreResult<()>;
+
+    /// Latest metrics for a market, `NotFound` if none were computed yet
+    async fn metrics(&self, market_id: Uuid) -> StoreResult<MarketMetrics>;
+}
+
+#[async_trait]
+pub trait ArchiveStore: Send + Sync {
+    /// Keep a raw response. Archiving the same `(source, fetched_at)` twice is a no-op.
+    async fn archive(&self, payload: &RawPayload) -> StoreResult<()>;
+
+    /// Archived responses fetched in `[from, to)`, oldest first, streamed
+    /// from the database
+    fn payloads(
+        &self,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<RawPayload>>;
+}
+
+/// Combine per-source totals with `(source, check, count)` rows
+fn summarize(
+    totals: Vec<(MarketSource, i64)>,
+    checks: Vec<(MarketSource, String, i64)>,
+) -> Vec<SourceQuality> {
+    let mut summary: Vec<SourceQuality> = totals
+        .into_iter()
+        .map(|(source, quarantined)| SourceQuality {
+            source,
+            quarantined,
+            checks: Vec::new(),
+        })
+        .collect();
+    summary.sort_by_key(|s| std::cmp::Reverse(s.quarantined));
+
+    for (source, check, count) in checks {
+        if let Some(entry) = summary.iter_mut().find(|s| s.source == source) {
+            entry.checks.push(CheckCount { check, count });
+        }
+    }
+    for entry in &mut summary {
+        entry
+            .checks
+            .sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.check.cmp(&b.check)));
+    }
+
+    summary
+}
+
+/// Combine per-category and per-tag counts, listing every category
+fn taxonomy(categories: Vec<(Category, i64)>, tags: Vec<(String, i64)>) -> Taxonomy {
+    Taxonomy {
+        categories: Category::ALL
+            .into_iter()
+            .map(|category| CategoryCount {
+                category,
+                markets: categories
+                    .iter()
+                    .find(|(c, _)| *c == category)
+                    .map_or(0, |(_, count)| *count),
+            })
+            .collect(),
+        tags: tags
+            .into_iter()
+            .map(|(tag, markets)| TagCount { tag, markets })
+            .collect(),
+    }
+}
+
+/// Connection to the backend named by the `DATABASE_URL` scheme
+#[derive(Clone)]
+pub enum Database {
+    Postgres(PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+}
+
+impl Database {
+    /// Connect to `postgres://...`, or `sqlite:path` when built with the `sqlite` feature
+    pub async fn connect(url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+        if url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            return sqlite::connect(url, max_connections)
+                .await
+                .map(Database::Sqlite);
+
+            #[cfg(not(feature = "sqlite"))]
+            return Err(sqlx::Error::Configuration(
+                "SQLite support is not compiled in; rebuild with --features sqlite".into(),
+            ));
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await?;
+        Ok(Database::Postgres(pool))
+    }
+
+    pub fn markets(&self) -> Arc<dyn MarketStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgMarketStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteMarketStore::new(pool.clone())),
+        }
+    }
+
+    pub fn history(&self) -> Arc<dyn HistoryStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgHistoryStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteHistoryStore::new(pool.clone())),
+        }
+    }
+
+    pub fn runs(&self) -> Arc<dyn RunStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgRunStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteRunStore::new(pool.clone())),
+        }
+    }
+
+    pub fn quarantine(&self) -> Arc<dyn QuarantineStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgQuarantineStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteQuarantineStore::new(pool.clone())),
+        }
+    }
+
+    pub fn anomalies(&self) -> Arc<dyn AnomalyStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgAnomalyStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteAnomalyStore::new(pool.clone())),
+        }
+    }
+
+    pub fn metrics(&self) -> Arc<dyn MetricsStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgMetricsStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteMetricsStore::new(pool.clone())),
+        }
+    }
+
+    pub fn archive(&self) -> Arc<dyn ArchiveStore> {
+        match self {
+            Database::Postgres(pool) => Arc::new(PgArchiveStore::new(pool.clone())),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(pool) => Arc::new(SqliteArchiveStore::new(pool.clone())),
+        }
+    }
+
+    /// The Postgres pool, for features that only exist on Postgres
+    /// (leader election, retention, partitioning)
+    pub fn postgres(&self) -> Option<&PgPool> {
+        match self {
+            Database::Postgres(pool) => Some(pool),
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(_) => None,
+        }
+    }
+}
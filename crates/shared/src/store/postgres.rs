@@ -0,0 +1,1378 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
+use sqlx::{PgPool, Row};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use super::{
+    summarize, taxonomy, AnomalyStore, ArchiveStore, HistoryStore, MarketFilter, MarketStore,
+    MarketSort, MetricsStore, QuarantineStore, RawPayload, RunStore, Snapshot, SortOrder,
+    SourceRun, StoreError, StoreResult,
+};
+use crate::models::{
+    Anomaly, Category, CollectionRun, CreateMarket, HistoryExportRow, HistorySpan, Market,
+    MarketChange, MarketMetrics, MarketRef, MarketRevision, MarketSource, MarketStatus,
+    PriceHistory, QuarantinedMarket, Resolution, SourceQuality, Taxonomy, UpdateMarket,
+};
+
+/// Markets in the `markets` table
+pub struct PgMarketStore {
+    pool: PgPool,
+}
+
+impl PgMarketStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MarketStore for PgMarketStore {
+    async fn upsert(&self, market: CreateMarket) -> StoreResult<Market> {
+        let result = sqlx::query_as::<_, Market>(
+            r#"
+            INSERT INTO markets (
+                source_id, source, title, description, category, tags,
+                yes_price, no_price, volume, volume_24h, liquidity,
+                status, close_at, url, source_category
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (source, source_id)
+            DO UPDATE SET
+                title = EXCLUDED.title,
+                description = EXCLUDED.description,
+                category = EXCLUDED.category,
+                source_category = EXCLUDED.source_category,
+                tags = EXCLUDED.tags,
+                yes_price = EXCLUDED.yes_price,
+                no_price = EXCLUDED.no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity,
+                status = EXCLUDED.status,
+                close_at = EXCLUDED.close_at,
+                url = CASE WHEN markets.placeholder THEN EXCLUDED.url ELSE markets.url END,
+                placeholder = FALSE
+            RETURNING *
+            "#,
+        )
+        .bind(&market.source_id)
+        .bind(market.source)
+        .bind(&market.title)
+        .bind(&market.description)
+        .bind(market.category)
+        .bind(&market.tags)
+        .bind(market.yes_price)
+        .bind(market.no_price)
+        .bind(market.volume)
+        .bind(market.volume_24h)
+        .bind(market.liquidity)
+        .bind(market.status)
+        .bind(market.close_at)
+        .bind(&market.url)
+        .bind(&market.source_category)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn create_placeholder(&self, market: CreateMarket) -> StoreResult<Market> {
+        // The no-op update makes RETURNING give back an existing market too
+        let result = sqlx::query_as::<_, Market>(
+            r#"
+            INSERT INTO markets (
+                source_id, source, title, description, category, tags,
+                yes_price, no_price, volume, volume_24h, liquidity,
+                status, close_at, url, source_category, placeholder
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, TRUE)
+            ON CONFLICT (source, source_id) DO UPDATE SET source_id = markets.source_id
+            RETURNING *
+            "#,
+        )
+        .bind(&market.source_id)
+        .bind(market.source)
+        .bind(&market.title)
+        .bind(&market.description)
+        .bind(market.category)
+        .bind(&market.tags)
+        .bind(market.yes_price)
+        .bind(market.no_price)
+        .bind(market.volume)
+        .bind(market.volume_24h)
+        .bind(market.liquidity)
+        .bind(market.status)
+        .bind(market.close_at)
+        .bind(&market.url)
+        .bind(&market.source_category)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> StoreResult<Market> {
+        sqlx::query_as::<_, Market>("SELECT * FROM markets WHERE id = $1")
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn get_by_source(&self, source: MarketSource, source_id: &str) -> StoreResult<Market> {
+        sqlx::query_as::<_, Market>("SELECT * FROM markets WHERE source = $1 AND source_id = $2")
+            .bind(source)
+            .bind(source_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn lookup(&self, refs: &[MarketRef]) -> StoreResult<Vec<Market>> {
+        let mut ids = Vec::new();
+        let (mut sources, mut source_ids) = (Vec::new(), Vec::new());
+        for market_ref in refs {
+            match market_ref {
+                MarketRef::Id(id) => ids.push(*id),
+                MarketRef::Source { source, source_id } => {
+                    sources.push(source.as_str());
+                    source_ids.push(source_id.as_str());
+                }
+            }
+        }
+
+        let markets = sqlx::query_as::<_, Market>(
+            r#"
+            SELECT * FROM markets
+            WHERE id = ANY($1)
+               OR (source, source_id) IN (SELECT * FROM UNNEST($2::TEXT[], $3::TEXT[]))
+            "#,
+        )
+        .bind(&ids)
+        .bind(&sources)
+        .bind(&source_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(markets)
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        offset: i64,
+        sort_by: MarketSort,
+        order: SortOrder,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<Market>> {
+
+        // As of a time, only markets with a snapshot by then, found the same
+        // way as `snapshots_at`, with volumes taken from it
+        let (snapshot_join, volumes) = match filter.as_of {
+            Some(_) => (
+                r#"
+                CROSS JOIN LATERAL (
+                    SELECT volume, volume_24h FROM (
+                        (SELECT volume, volume_24h, recorded_at FROM price_history
+                         WHERE market_id = m.id AND recorded_at <= $6
+                         ORDER BY recorded_at DESC LIMIT 1)
+                        UNION ALL
+                        (SELECT volume, volume_24h, bucket FROM price_history_hourly
+                         WHERE market_id = m.id AND bucket <= $6 - INTERVAL '1 hour'
+                         ORDER BY bucket DESC LIMIT 1)
+                        UNION ALL
+                        (SELECT volume, volume_24h, bucket FROM price_history_daily
+                         WHERE market_id = m.id AND bucket <= $6 - INTERVAL '1 day'
+                         ORDER BY bucket DESC LIMIT 1)
+                    ) latest
+                    ORDER BY recorded_at DESC LIMIT 1
+                ) s
+                "#,
+                "s",
+            ),
+            None => ("", "m"),
+        };
+
+        let sort_column = match sort_by {
+            MarketSort::Volume => format!("{}.volume", volumes),
+            MarketSort::CreatedAt => "m.created_at".to_string(),
+            MarketSort::CloseAt => "m.close_at".to_string(),
+            MarketSort::Volume24h => format!("{}.volume_24h", volumes),
+            MarketSort::Metric(metric) => metric.to_string(),
+        };
+
+        let query = format!(
+            r#"
+            SELECT m.* FROM markets m
+            LEFT JOIN market_metrics mm ON mm.market_id = m.id
+            {}
+            WHERE ($3::TEXT IS NULL OR m.category = $3)
+              AND ($4::TEXT IS NULL OR $4 = ANY(m.tags))
+              AND ($5::TEXT IS NULL OR m.source = $5)
+            ORDER BY {} {} NULLS LAST, m.created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            snapshot_join,
+            sort_column,
+            order.as_sql()
+        );
+
+        let mut query = sqlx::query_as::<_, Market>(&query)
+            .bind(limit)
+            .bind(offset)
+            .bind(filter.category)
+            .bind(&filter.tag)
+            .bind(filter.source);
+        if let Some(at) = filter.as_of {
+            query = query.bind(at);
+        }
+
+        query.fetch_all(&self.pool).await.map_err(Into::into)
+    }
+
+    async fn top_in_category(&self, category: Category, limit: i64) -> StoreResult<Vec<Market>> {
+        sqlx::query_as::<_, Market>(
+            r#"
+            SELECT * FROM markets
+            WHERE category = $1
+            ORDER BY volume DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(category)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(Into::into)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        limit: i64,
+        status: Option<MarketStatus>,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<(Market, f32)>> {
+        let mut sql = String::from(
+            r#"
+            SELECT
+                m.*,
+                ts_rank(to_tsvector('english', m.title || ' ' || m.description),
+                        plainto_tsquery('english', $1)) as rank
+            FROM markets m
+            WHERE to_tsvector('english', m.title || ' ' || m.description) @@ plainto_tsquery('english', $1)
+            "#,
+        );
+
+        // $1 is the query; each filter set takes the next placeholder
+        let mut param = 1;
+        let mut condition = |clause: &str| {
+            param += 1;
+            sql.push_str(&clause.replace('?', &format!("${}", param)));
+            param
+        };
+
+        if filter.source.is_some() {
+            condition(" AND m.source = ?");
+        }
+
+        // As of a time, only markets with a snapshot by then, found the same
+        // way as `snapshots_at`
+        let as_of = filter.as_of.map(|_| {
+            condition(
+                r#"
+                AND (EXISTS (SELECT 1 FROM price_history
+                             WHERE market_id = m.id AND recorded_at <= ?)
+                     OR EXISTS (SELECT 1 FROM price_history_hourly
+                                WHERE market_id = m.id AND bucket <= ? - INTERVAL '1 hour')
+                     OR EXISTS (SELECT 1 FROM price_history_daily
+                                WHERE market_id = m.id AND bucket <= ? - INTERVAL '1 day'))
+                "#,
+            )
+        });
+
+        if status.is_some() {
+            match as_of {
+                // The earliest status change since then holds the status back then
+                Some(at) => condition(&format!(
+                    r#"
+                    AND COALESCE(
+                        (SELECT r.old_value FROM market_revisions r
+                         WHERE r.market_id = m.id AND r.field = 'status' AND r.changed_at > ${}
+                         ORDER BY r.changed_at LIMIT 1),
+                        m.status
+                    ) = ?
+                    "#,
+                    at
+                )),
+                None => condition(" AND m.status = ?"),
+            };
+        }
+
+        if filter.category.is_some() {
+            condition(" AND m.category = ?");
+        }
+
+        if filter.tag.is_some() {
+            condition(" AND ? = ANY(m.tags)");
+        }
+
+        sql.push_str(" ORDER BY rank DESC, m.volume DESC LIMIT ");
+        sql.push_str(&limit.max(0).to_string());
+
+        let mut query_builder = sqlx::query(&sql).bind(query);
+
+        if let Some(source) = filter.source {
+            query_builder = query_builder.bind(source);
+        }
+
+        if let Some(at) = filter.as_of {
+            query_builder = query_builder.bind(at);
+        }
+
+        if let Some(st) = status {
+            query_builder = query_builder.bind(st);
+        }
+
+        if let Some(category) = filter.category {
+            query_builder = query_builder.bind(category);
+        }
+
+        if let Some(tag) = &filter.tag {
+            query_builder = query_builder.bind(tag);
+        }
+
+        let rows = query_builder.fetch_all(&self.pool).await?;
+
+        let results = rows
+            .into_iter()
+            .map(|row| {
+                let market = Market {
+                    id: row.get("id"),
+                    source_id: row.get("source_id"),
+                    source: row.get("source"),
+                    title: row.get("title"),
+                    description: row.get("description"),
+                    category: row.get("category"),
+                    source_category: row.get("source_category"),
+                    tags: row.get("tags"),
+                    yes_price: row.get("yes_price"),
+                    no_price: row.get("no_price"),
+                    volume: row.get("volume"),
+                    volume_24h: row.get("volume_24h"),
+                    liquidity: row.get("liquidity"),
+                    status: row.get("status"),
+                    created_at: row.get("created_at"),
+                    updated_at: row.get("updated_at"),
+                    close_at: row.get("close_at"),
+                    url: row.get("url"),
+                    placeholder: row.get("placeholder"),
+                };
+                let rank: f32 = row.get("rank");
+                (market, rank)
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    async fn update(&self, id: Uuid, update: UpdateMarket) -> StoreResult<Market> {
+        // Surface RowNotFound before attempting the update
+        self.get_by_id(id).await?;
+
+        let result = sqlx::query_as::<_, Market>(
+            r#"
+            UPDATE markets
+            SET
+                yes_price = COALESCE($2, yes_price),
+                no_price = COALESCE($3, no_price),
+                volume = COALESCE($4, volume),
+                volume_24h = COALESCE($5, volume_24h),
+                liquidity = COALESCE($6, liquidity),
+                status = COALESCE($7, status),
+                close_at = COALESCE($8, close_at)
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(update.yes_price)
+        .bind(update.no_price)
+        .bind(update.volume)
+        .bind(update.volume_24h)
+        .bind(update.liquidity)
+        .bind(update.status)
+        .bind(update.close_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    async fn set_taxonomy(
+        &self,
+        id: Uuid,
+        category: Option<Category>,
+        tags: Option<&[String]>,
+    ) -> StoreResult<()> {
+        let result = sqlx::query("UPDATE markets SET category = $2, tags = $3 WHERE id = $1")
+            .bind(id)
+            .bind(category)
+            .bind(tags)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn taxonomy(&self, tag_limit: i64) -> StoreResult<Taxonomy> {
+        let categories: Vec<(Category, i64)> = sqlx::query_as(
+            "SELECT category, COUNT(*) FROM markets WHERE category IS NOT NULL GROUP BY category",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tags: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT tag, COUNT(*) AS markets
+            FROM markets, UNNEST(tags) AS tag
+            GROUP BY tag
+            ORDER BY markets DESC, tag
+            LIMIT $1
+            "#,
+        )
+        .bind(tag_limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(taxonomy(categories, tags))
+    }
+
+    async fn record_revisions(
+        &self,
+        market_id: Uuid,
+        changes: &[MarketChange],
+        changed_at: DateTime<Utc>,
+    ) -> StoreResult<()> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO market_revisions (market_id, field, old_value, new_value, changed_at)
+            SELECT $1, c.field, c.old_value, c.new_value, $5
+            FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[]) AS c(field, old_value, new_value)
+            "#,
+        )
+        .bind(market_id)
+        .bind(changes.iter().map(|c| c.field).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.old_value.clone()).collect::<Vec<_>>())
+        .bind(changes.iter().map(|c| c.new_value.clone()).collect::<Vec<_>>())
+        .bind(changed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revisions(
+        &self,
+        market_ids: &[Uuid],
+        after: Option<DateTime<Utc>>,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        let revisions = sqlx::query_as::<_, MarketRevision>(
+            r#"
+            SELECT * FROM market_revisions
+            WHERE market_id = ANY($1)
+              AND ($2::TIMESTAMPTZ IS NULL OR changed_at > $2)
+            ORDER BY changed_at, field
+            "#,
+        )
+        .bind(market_ids)
+        .bind(after)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    async fn latest_revisions(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        let revisions = sqlx::query_as::<_, MarketRevision>(
+            r#"
+            SELECT * FROM market_revisions
+            WHERE market_id = $1
+            ORDER BY changed_at DESC, field DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(market_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revisions)
+    }
+}
+
+/// Raw snapshots in `price_history`, read together with the hourly and
+/// daily rollups
+pub struct PgHistoryStore {
+    pool: PgPool,
+    /// Months whose partition this store has already made sure of
+    partitioned: Mutex<HashSet<NaiveDate>>,
+}
+
+impl PgHistoryStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            partitioned: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Create the monthly partition `at` falls in unless this store already
+    /// has, so a lagging partition job never sends live rows to the default
+    async fn ensure_partition(&self, at: DateTime<Utc>) -> StoreResult<()> {
+        let month = at.date_naive().with_day(1).unwrap();
+        if self.partitioned.lock().unwrap().contains(&month) {
+            return Ok(());
+        }
+
+        sqlx::query("SELECT create_price_history_partition($1)")
+            .bind(month)
+            .execute(&self.pool)
+            .await?;
+        self.partitioned.lock().unwrap().insert(month);
+        Ok(())
+    }
+
+    /// Bulk insert, creating any missing monthly partitions first. Existing
+    /// `(market_id, recorded_at)` rows are overwritten or skipped.
+    async fn insert_snapshots(&self, snapshots: &[Snapshot], overwrite: bool) -> StoreResult<u64> {
+        if snapshots.is_empty() {
+            return Ok(0);
+        }
+
+        let mut months: Vec<NaiveDate> = snapshots
+            .iter()
+            .map(|s| s.recorded_at.date_naive().with_day(1).unwrap())
+            .collect();
+        months.sort();
+        months.dedup();
+
+        sqlx::query("SELECT create_price_history_partition(month) FROM UNNEST($1::DATE[]) AS month")
+            .bind(&months)
+            .execute(&self.pool)
+            .await?;
+
+        let on_conflict = if overwrite {
+            r#"
+            DO UPDATE SET
+                yes_price = EXCLUDED.yes_price,
+                no_price = EXCLUDED.no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity
+            "#
+        } else {
+            "DO NOTHING"
+        };
+
+        let sql = format!(
+            r#"
+            INSERT INTO price_history (
+                market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at
+            )
+            SELECT * FROM UNNEST(
+                $1::UUID[], $2::NUMERIC[], $3::NUMERIC[], $4::NUMERIC[], $5::NUMERIC[], $6::NUMERIC[],
+                $7::TIMESTAMPTZ[]
+            )
+            ON CONFLICT (market_id, recorded_at) {}
+            "#,
+            on_conflict
+        );
+
+        let result = sqlx::query(&sql)
+            .bind(snapshots.iter().map(|s| s.market_id).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.yes_price).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.no_price).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.volume).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.volume_24h).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.liquidity).collect::<Vec<_>>())
+            .bind(snapshots.iter().map(|s| s.recorded_at).collect::<Vec<_>>())
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Price history at `resolution` recorded in `[since, until]`, newest first.
+    ///
+    /// Periods the requested table does not cover are served from the other
+    /// resolutions: coarser rollups for data older than its coverage (raw rows
+    /// past retention), finer tables for recent data not yet rolled up.
+    ///
+    /// With `at_close`, rollup rows are stamped at the end of their bucket,
+    /// and `[since, until]` applies to that stamp.
+    #[allow(clippy::too_many_arguments)]
+    async fn history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        resolution: Resolution,
+        include_anomalies: bool,
+        at_close: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let coverage = self.coverage(market_id).await?;
+        let windows = plan_windows(resolution, &coverage);
+
+        if windows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut parts = Vec::with_capacity(windows.len());
+        for (idx, window) in windows.iter().enumerate() {
+            let (from, until) = (6 + idx * 2, 7 + idx * 2);
+            let (select, time_column, stamp) = match window.resolution {
+                // Rollups are built without flagged snapshots already
+                Resolution::Raw => (
+                    "SELECT id, market_id, yes_price, no_price, volume, volume_24h, liquidity, \
+                     recorded_at FROM price_history ph \
+                     WHERE ($4 OR NOT EXISTS (SELECT 1 FROM anomalies a \
+                     WHERE a.market_id = ph.market_id AND a.recorded_at = ph.recorded_at)) AND"
+                        .to_string(),
+                    "recorded_at",
+                    "recorded_at".to_string(),
+                ),
+                rollup => {
+                    let (table, width) = match rollup {
+                        Resolution::Daily => ("price_history_daily", "1 day"),
+                        _ => ("price_history_hourly", "1 hour"),
+                    };
+                    // A bucket's close is only known once the bucket ends
+                    let stamp = if at_close {
+                        format!("(bucket + INTERVAL '{}')", width)
+                    } else {
+                        "bucket".to_string()
+                    };
+                    (
+                        format!(
+                            "SELECT id, market_id, close_yes_price AS yes_price, \
+                             close_no_price AS no_price, volume, volume_24h, liquidity, \
+                             {} AS recorded_at FROM {} WHERE",
+                            stamp, table
+                        ),
+                        "bucket",
+                        stamp,
+                    )
+                }
+            };
+
+            parts.push(format!(
+                "({select} market_id = $1 \
+                 AND ($2::TIMESTAMPTZ IS NULL OR {stamp} >= $2) \
+                 AND ($5::TIMESTAMPTZ IS NULL OR {stamp} <= $5) \
+                 AND (${from}::TIMESTAMPTZ IS NULL OR {col} >= ${from}) \
+                 AND (${until}::TIMESTAMPTZ IS NULL OR {col} < ${until}))",
+                select = select,
+                stamp = stamp,
+                col = time_column,
+                from = from,
+                until = until,
+            ));
+        }
+
+        let sql = format!(
+            "{} ORDER BY recorded_at DESC LIMIT $3",
+            parts.join(" UNION ALL ")
+        );
+
+        let mut query = sqlx::query_as::<_, PriceHistory>(&sql)
+            .bind(market_id)
+            .bind(since)
+            .bind(limit)
+            .bind(include_anomalies)
+            .bind(until);
+
+        for window in &windows {
+            query = query.bind(window.from).bind(window.until);
+        }
+
+        Ok(query.fetch_all(&self.pool).await?)
+    }
+
+    async fn coverage(&self, market_id: Uuid) -> Result<Coverage, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                (SELECT MIN(recorded_at) FROM price_history WHERE market_id = $1) AS raw_start,
+                (SELECT MIN(bucket) FROM price_history_hourly WHERE market_id = $1) AS hourly_start,
+                (SELECT MAX(bucket) + INTERVAL '1 hour' FROM price_history_hourly WHERE market_id = $1) AS hourly_end,
+                (SELECT MIN(bucket) FROM price_history_daily WHERE market_id = $1) AS daily_start,
+                (SELECT MAX(bucket) + INTERVAL '1 day' FROM price_history_daily WHERE market_id = $1) AS daily_end
+            "#,
+        )
+        .bind(market_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let span = |start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>| {
+            start.zip(end)
+        };
+
+        Ok(Coverage {
+            raw_start: row.get("raw_start"),
+            hourly: span(row.get("hourly_start"), row.get("hourly_end")),
+            daily: span(row.get("daily_start"), row.get("daily_end")),
+        })
+    }
+}
+
+#[async_trait]
+impl HistoryStore for PgHistoryStore {
+    async fn record_snapshot(
+        &self,
+        market: &Market,
+        recorded_at: DateTime<Utc>,
+    ) -> StoreResult<Option<PriceHistory>> {
+        self.ensure_partition(recorded_at).await?;
+
+        let result = sqlx::query_as::<_, PriceHistory>(
+            r#"
+            INSERT INTO price_history (
+                market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (market_id, recorded_at) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(market.id)
+        .bind(market.yes_price)
+        .bind(market.no_price)
+        .bind(market.volume)
+        .bind(market.volume_24h)
+        .bind(market.liquidity)
+        .bind(recorded_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Rollup buckets count towards the first and last times, so markets
+    /// whose raw rows have expired still report their full span
+    async fn span(&self, market_id: Uuid) -> StoreResult<HistorySpan> {
+        let row = sqlx::query(
+            r#"
+            SELECT
+                LEAST(
+                    (SELECT MIN(recorded_at) FROM price_history WHERE market_id = $1),
+                    (SELECT MIN(bucket) FROM price_history_hourly WHERE market_id = $1),
+                    (SELECT MIN(bucket) FROM price_history_daily WHERE market_id = $1)
+                ) AS first_at,
+                COALESCE(
+                    (SELECT MAX(recorded_at) FROM price_history WHERE market_id = $1),
+                    (SELECT MAX(bucket) FROM price_history_hourly WHERE market_id = $1),
+                    (SELECT MAX(bucket) FROM price_history_daily WHERE market_id = $1)
+                ) AS last_at,
+                (SELECT COUNT(*) FROM price_history WHERE market_id = $1) AS snapshots
+            "#,
+        )
+        .bind(market_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(HistorySpan {
+            first_at: row.get("first_at"),
+            last_at: row.get("last_at"),
+            snapshots: row.get("snapshots"),
+        })
+    }
+
+    /// Latest raw snapshot at or before `at`, or the close of the latest
+    /// complete rollup bucket where raw rows have expired
+    async fn snapshots_at(
+        &self,
+        market_ids: &[Uuid],
+        at: DateTime<Utc>,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let snapshots = sqlx::query_as::<_, PriceHistory>(
+            r#"
+            SELECT DISTINCT ON (s.market_id) s.*
+            FROM UNNEST($1::UUID[]) AS ids(market_id)
+            CROSS JOIN LATERAL (
+                (SELECT id, market_id, yes_price, no_price, volume, volume_24h, liquidity,
+                        recorded_at
+                 FROM price_history
+                 WHERE market_id = ids.market_id AND recorded_at <= $2
+                 ORDER BY recorded_at DESC LIMIT 1)
+                UNION ALL
+                (SELECT id, market_id, close_yes_price, close_no_price, volume, volume_24h,
+                        liquidity, bucket
+                 FROM price_history_hourly
+                 WHERE market_id = ids.market_id AND bucket <= $2 - INTERVAL '1 hour'
+                 ORDER BY bucket DESC LIMIT 1)
+                UNION ALL
+                (SELECT id, market_id, close_yes_price, close_no_price, volume, volume_24h,
+                        liquidity, bucket
+                 FROM price_history_daily
+                 WHERE market_id = ids.market_id AND bucket <= $2 - INTERVAL '1 day'
+                 ORDER BY bucket DESC LIMIT 1)
+            ) s
+            ORDER BY s.market_id, s.recorded_at DESC
+            "#,
+        )
+        .bind(market_ids)
+        .bind(at)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(snapshots)
+    }
+
+    /// Bulk insert through `UNNEST`, creating the monthly partitions the
+    /// snapshots fall in first so they don't land in the default partition
+    async fn import_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        self.insert_snapshots(snapshots, false).await
+    }
+
+    async fn replace_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        self.insert_snapshots(snapshots, true).await
+    }
+
+    async fn get_history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        hours: Option<i64>,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let since = hours.map(|h| Utc::now() - Duration::hours(h));
+        self.history(market_id, limit, since, None, resolution, include_anomalies, false)
+            .await
+    }
+
+    async fn history_between(
+        &self,
+        market_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let (since, until) = (Some(from), Some(to));
+        self.history(market_id, limit, since, until, resolution, include_anomalies, true)
+            .await
+    }
+
+    fn export(
+        &self,
+        market_ids: Vec<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<HistoryExportRow>> {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query_as::<_, HistoryExportRow>(
+                r#"
+                SELECT
+                    ph.market_id, m.source, m.source_id, ph.recorded_at,
+                    ph.yes_price, ph.no_price, ph.volume, ph.volume_24h, ph.liquidity
+                FROM price_history ph
+                JOIN markets m ON m.id = ph.market_id
+                WHERE (cardinality($1::UUID[]) = 0 OR ph.market_id = ANY($1))
+                  AND ($2::TIMESTAMPTZ IS NULL OR ph.recorded_at >= $2)
+                  AND ($3::TIMESTAMPTZ IS NULL OR ph.recorded_at < $3)
+                ORDER BY ph.market_id, ph.recorded_at
+                "#,
+            )
+            .bind(market_ids)
+            .bind(from)
+            .bind(to)
+            .fetch(&pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield row;
+            }
+        })
+    }
+}
+
+/// Time range held for a market in each history table
+struct Coverage {
+    raw_start: Option<DateTime<Utc>>,
+    hourly: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    daily: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+/// Slice `[from, until)` of one history table to read; `None` is unbounded
+struct Window {
+    resolution: Resolution,
+    from: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+}
+
+/// Decide which table serves each period so the tables never overlap.
+///
+/// The requested table covers its own range, finer tables continue from
+/// where it ends, and coarser tables fill in everything before it starts.
+fn plan_windows(requested: Resolution, coverage: &Coverage) -> Vec<Window> {
+    let tiers = [
+        (Resolution::Raw, coverage.raw_start.map(|start| (start, None))),
+        (Resolution::Hourly, coverage.hourly.map(|(start, end)| (start, Some(end)))),
+        (Resolution::Daily, coverage.daily.map(|(start, end)| (start, Some(end)))),
+    ];
+    let requested_idx = tiers
+        .iter()
+        .position(|(resolution, _)| *resolution == requested)
+        .unwrap_or(0);
+
+    let mut windows = Vec::new();
+
+    // Requested table, then finer ones for anything newer
+    let mut cursor: Option<DateTime<Utc>> = None;
+    let mut chain_start: Option<DateTime<Utc>> = None;
+    for (resolution, span) in tiers[..=requested_idx].iter().rev() {
+        let Some((start, end)) = span else { continue };
+
+        chain_start.get_or_insert(*start);
+        windows.push(Window {
+            resolution: *resolution,
+            from: cursor,
+            until: *end,
+        });
+
+        match end {
+            Some(end) => cursor = Some(cursor.map_or(*end, |c| c.max(*end))),
+            None => break,
+        }
+    }
+
+    // Coarser tables for anything older
+    let mut until = chain_start;
+    for (resolution, span) in &tiers[requested_idx + 1..] {
+        let Some((start, _)) = span else { continue };
+
+        windows.push(Window {
+            resolution: *resolution,
+            from: None,
+            until,
+        });
+        until = Some(until.map_or(*start, |u| u.min(*start)));
+    }
+
+    windows
+}
+
+
+/// Collection audit log in `collection_runs`
+pub struct PgRunStore {
+    pool: PgPool,
+}
+
+impl PgRunStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RunStore for PgRunStore {
+    async fn start_cycle(&self, started_at: DateTime<Utc>) -> StoreResult<Uuid> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO collection_runs (kind, started_at) VALUES ('cycle', $1) RETURNING id",
+        )
+        .bind(started_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn finish_cycle(
+        &self,
+        id: Uuid,
+        markets_fetched: i32,
+        markets_recorded: i32,
+        error: Option<String>,
+    ) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE collection_runs
+            SET
+                finished_at = NOW(),
+                markets_fetched = $2,
+                markets_recorded = $3,
+                markets_failed = $4,
+                error = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(markets_fetched)
+        .bind(markets_recorded)
+        .bind((markets_fetched - markets_recorded).max(0))
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_source(&self, cycle_id: Uuid, run: SourceRun) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO collection_runs (
+                kind, parent_id, source, started_at, finished_at,
+                markets_fetched, markets_recorded, markets_failed, error, http_status
+            )
+            VALUES ('source', $1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(cycle_id)
+        .bind(&run.source)
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.markets_fetched)
+        .bind(run.markets_recorded)
+        .bind((run.markets_fetched - run.markets_recorded).max(0))
+        .bind(&run.error)
+        .bind(run.http_status)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        source: Option<&str>,
+        failed_only: bool,
+    ) -> StoreResult<Vec<CollectionRun>> {
+        let runs = sqlx::query_as::<_, CollectionRun>(
+            r#"
+            SELECT r.*
+            FROM collection_runs r
+            LEFT JOIN collection_runs p ON p.id = r.parent_id
+            WHERE ($1::TEXT IS NULL OR r.source = $1)
+              AND (NOT $2 OR r.error IS NOT NULL OR r.finished_at IS NULL)
+            ORDER BY COALESCE(p.started_at, r.started_at) DESC,
+                     r.parent_id NULLS FIRST,
+                     r.started_at
+            LIMIT $3
+            "#,
+        )
+        .bind(source)
+        .bind(failed_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(runs)
+    }
+}
+
+/// Markets rejected by validation, in `quarantine`
+pub struct PgQuarantineStore {
+    pool: PgPool,
+}
+
+impl PgQuarantineStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for PgQuarantineStore {
+    async fn quarantine(&self, markets: &[QuarantinedMarket]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for market in markets {
+            sqlx::query(
+                r#"
+                INSERT INTO quarantine (
+                    id, source, source_id, checks, reason, payload, quarantined_at, last_seen_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (source, (COALESCE(source_id, '')), checks) DO UPDATE
+                SET reason = EXCLUDED.reason,
+                    payload = EXCLUDED.payload,
+                    last_seen_at = EXCLUDED.last_seen_at
+                "#,
+            )
+            .bind(market.id)
+            .bind(market.source)
+            .bind(&market.source_id)
+            .bind(&market.checks)
+            .bind(&market.reason)
+            .bind(&market.payload)
+            .bind(market.quarantined_at)
+            .bind(market.last_seen_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn quarantined(
+        &self,
+        since: DateTime<Utc>,
+        source: Option<MarketSource>,
+        limit: i64,
+    ) -> StoreResult<Vec<QuarantinedMarket>> {
+        let markets = sqlx::query_as::<_, QuarantinedMarket>(
+            r#"
+            SELECT * FROM quarantine
+            WHERE last_seen_at >= $1
+              AND ($2::TEXT IS NULL OR source = $2)
+            ORDER BY last_seen_at DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(since)
+        .bind(source)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(markets)
+    }
+
+    async fn quarantine_summary(&self, since: DateTime<Utc>) -> StoreResult<Vec<SourceQuality>> {
+        let totals: Vec<(MarketSource, i64)> = sqlx::query_as(
+            "SELECT source, COUNT(*) FROM quarantine WHERE last_seen_at >= $1 GROUP BY source",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let checks: Vec<(MarketSource, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT source, check_name, COUNT(*)
+            FROM quarantine, UNNEST(checks) AS check_name
+            WHERE last_seen_at >= $1
+            GROUP BY source, check_name
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summarize(totals, checks))
+    }
+}
+
+/// Flagged snapshots in `anomalies`
+pub struct PgAnomalyStore {
+    pool: PgPool,
+}
+
+impl PgAnomalyStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AnomalyStore for PgAnomalyStore {
+    async fn flag(&self, anomalies: &[Anomaly]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for anomaly in anomalies {
+            sqlx::query(
+                r#"
+                INSERT INTO anomalies (market_id, recorded_at, kind, score, detail, detected_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (market_id, recorded_at, kind) DO NOTHING
+                "#,
+            )
+            .bind(anomaly.market_id)
+            .bind(anomaly.recorded_at)
+            .bind(&anomaly.kind)
+            .bind(anomaly.score)
+            .bind(&anomaly.detail)
+            .bind(anomaly.detected_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn anomalies(
+        &self,
+        market_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> StoreResult<Vec<Anomaly>> {
+        let anomalies = sqlx::query_as::<_, Anomaly>(
+            r#"
+            SELECT * FROM anomalies
+            WHERE ($1::TIMESTAMPTZ IS NULL OR recorded_at >= $1)
+              AND ($2::UUID IS NULL OR market_id = $2)
+            ORDER BY recorded_at DESC, kind
+            LIMIT $3
+            "#,
+        )
+        .bind(since)
+        .bind(market_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(anomalies)
+    }
+}
+
+/// Derived per-market metrics in `market_metrics`
+pub struct PgMetricsStore {
+    pool: PgPool,
+}
+
+impl PgMetricsStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MetricsStore for PgMetricsStore {
+    async fn save_metrics(&self, metrics: &[MarketMetrics]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for m in metrics {
+            sqlx::query(
+                r#"
+                INSERT INTO market_metrics (
+                    market_id, volatility_24h, volatility_7d, volatility_30d,
+                    momentum_24h, momentum_7d, max_drawdown_30d, last_move_at,
+                    volume_acceleration, samples, computed_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (market_id) DO UPDATE SET
+                    volatility_24h = EXCLUDED.volatility_24h,
+                    volatility_7d = EXCLUDED.volatility_7d,
+                    volatility_30d = EXCLUDED.volatility_30d,
+                    momentum_24h = EXCLUDED.momentum_24h,
+                    momentum_7d = EXCLUDED.momentum_7d,
+                    max_drawdown_30d = EXCLUDED.max_drawdown_30d,
+                    last_move_at = EXCLUDED.last_move_at,
+                    volume_acceleration = EXCLUDED.volume_acceleration,
+                    samples = EXCLUDED.samples,
+                    computed_at = EXCLUDED.computed_at
+                "#,
+            )
+            .bind(m.market_id)
+            .bind(m.volatility_24h)
+            .bind(m.volatility_7d)
+            .bind(m.volatility_30d)
+            .bind(m.momentum_24h)
+            .bind(m.momentum_7d)
+            .bind(m.max_drawdown_30d)
+            .bind(m.last_move_at)
+            .bind(m.volume_acceleration)
+            .bind(m.samples)
+            .bind(m.computed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn metrics(&self, market_id: Uuid) -> StoreResult<MarketMetrics> {
+        sqlx::query_as::<_, MarketMetrics>("SELECT * FROM market_metrics WHERE market_id = $1")
+            .bind(market_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+/// Raw venue responses in `raw_payloads`
+pub struct PgArchiveStore {
+    pool: PgPool,
+}
+
+impl PgArchiveStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for PgArchiveStore {
+    async fn archive(&self, payload: &RawPayload) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO raw_payloads (source, fetched_at, body)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (source, fetched_at) DO NOTHING
+            "#,
+        )
+        .bind(payload.source)
+        .bind(payload.fetched_at)
+        .bind(&payload.body)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn payloads(
+        &self,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<RawPayload>> {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(
+                r#"
+                SELECT source, fetched_at, body FROM raw_payloads
+                WHERE ($1::TEXT IS NULL OR source = $1)
+                  AND ($2::TIMESTAMPTZ IS NULL OR fetched_at >= $2)
+                  AND ($3::TIMESTAMPTZ IS NULL OR fetched_at < $3)
+                ORDER BY fetched_at, source
+                "#,
+            )
+            .bind(source)
+            .bind(from)
+            .bind(to)
+            .fetch(&pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield RawPayload {
+                    source: row.try_get("source")?,
+                    fetched_at: row.try_get("fetched_at")?,
+                    body: row.try_get("body")?,
+                };
+            }
+        })
+    }
+}
@@ -0,0 +1,1791 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::BoxStream;
+use futures::TryStreamExt;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow};
+use sqlx::types::Json;
+use sqlx::{Row, SqlitePool};
+use std::str::FromStr;
+use uuid::fmt::Hyphenated;
+use uuid::Uuid;
+
+use super::{
+    summarize, taxonomy, AnomalyStore, ArchiveStore, HistoryStore, MarketFilter, MarketStore,
+    MarketSort, MetricsStore, QuarantineStore, RawPayload, RunStore, Snapshot, SortOrder,
+    SourceRun, StoreError, StoreResult,
+};
+use crate::models::{
+    Anomaly, Category, CollectionRun, CreateMarket, Decimal, HistoryExportRow, HistorySpan,
+    Market, MarketChange, MarketMetrics, MarketRef, MarketRevision, MarketSource, MarketStatus,
+    PriceHistory, QuarantinedMarket, Resolution, SourceQuality, Taxonomy, UpdateMarket,
+    AMOUNT_DP, PRICE_DP,
+};
+
+/// Open (creating if missing) the database file named by a `sqlite:` URL
+pub(super) async fn connect(url: &str, max_connections: u32) -> Result<SqlitePool, sqlx::Error> {
+    // WAL lets the API read while the worker writes
+    let options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal);
+
+    SqlitePoolOptions::new()
+        .max_connections(max_connections)
+        .connect_with(options)
+        .await
+}
+
+// SQLite has no decimal type: prices and amounts are stored as REAL and
+// rounded back to their fixed scale when read. A double holds every value at
+// that scale exactly up to ~10^13.
+
+fn decimal(row: &SqliteRow, column: &str, dp: u32) -> Result<Decimal, sqlx::Error> {
+    optional_decimal(row, column, dp)?.ok_or_else(|| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: "unexpected NULL".into(),
+    })
+}
+
+fn optional_decimal(row: &SqliteRow, column: &str, dp: u32) -> Result<Option<Decimal>, sqlx::Error> {
+    let Some(value) = row.try_get::<Option<f64>, _>(column)? else {
+        return Ok(None);
+    };
+
+    Decimal::from_f64(value)
+        .map(|d| Some(d.round_dp(dp)))
+        .ok_or_else(|| sqlx::Error::ColumnDecode {
+            index: column.to_string(),
+            source: format!("{} is not a finite decimal", value).into(),
+        })
+}
+
+fn real(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+fn market_from_row(row: &SqliteRow) -> Result<Market, sqlx::Error> {
+    let tags: Option<Json<Vec<String>>> = row.try_get("tags")?;
+
+    Ok(Market {
+        id: row.try_get::<Hyphenated, _>("id")?.into_uuid(),
+        source_id: row.try_get("source_id")?,
+        source: row.try_get("source")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        category: row.try_get("category")?,
+        source_category: row.try_get("source_category")?,
+        tags: tags.map(|Json(tags)| tags),
+        yes_price: decimal(row, "yes_price", PRICE_DP)?,
+        no_price: decimal(row, "no_price", PRICE_DP)?,
+        volume: decimal(row, "volume", AMOUNT_DP)?,
+        volume_24h: decimal(row, "volume_24h", AMOUNT_DP)?,
+        liquidity: optional_decimal(row, "liquidity", AMOUNT_DP)?,
+        status: row.try_get("status")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        close_at: row.try_get("close_at")?,
+        url: row.try_get("url")?,
+        placeholder: row.try_get("placeholder")?,
+    })
+}
+
+fn history_from_row(row: &SqliteRow) -> Result<PriceHistory, sqlx::Error> {
+    Ok(PriceHistory {
+        id: row.try_get::<Hyphenated, _>("id")?.into_uuid(),
+        market_id: row.try_get::<Hyphenated, _>("market_id")?.into_uuid(),
+        yes_price: decimal(row, "yes_price", PRICE_DP)?,
+        no_price: decimal(row, "no_price", PRICE_DP)?,
+        volume: decimal(row, "volume", AMOUNT_DP)?,
+        volume_24h: decimal(row, "volume_24h", AMOUNT_DP)?,
+        liquidity: optional_decimal(row, "liquidity", AMOUNT_DP)?,
+        recorded_at: row.try_get("recorded_at")?,
+    })
+}
+
+fn revision_from_row(row: &SqliteRow) -> Result<MarketRevision, sqlx::Error> {
+    Ok(MarketRevision {
+        id: row.try_get::<Hyphenated, _>("id")?.into_uuid(),
+        market_id: row.try_get::<Hyphenated, _>("market_id")?.into_uuid(),
+        field: row.try_get("field")?,
+        old_value: row.try_get("old_value")?,
+        new_value: row.try_get("new_value")?,
+        changed_at: row.try_get("changed_at")?,
+    })
+}
+
+fn run_from_row(row: &SqliteRow) -> Result<CollectionRun, sqlx::Error> {
+    let parent_id: Option<Hyphenated> = row.try_get("parent_id")?;
+
+    Ok(CollectionRun {
+        id: row.try_get::<Hyphenated, _>("id")?.into_uuid(),
+        kind: row.try_get("kind")?,
+        parent_id: parent_id.map(Hyphenated::into_uuid),
+        source: row.try_get("source")?,
+        started_at: row.try_get("started_at")?,
+        finished_at: row.try_get("finished_at")?,
+        markets_fetched: row.try_get("markets_fetched")?,
+        markets_recorded: row.try_get("markets_recorded")?,
+        markets_failed: row.try_get("markets_failed")?,
+        error: row.try_get("error")?,
+        http_status: row.try_get("http_status")?,
+    })
+}
+
+fn anomaly_from_row(row: &SqliteRow) -> Result<Anomaly, sqlx::Error> {
+    Ok(Anomaly {
+        market_id: row.try_get::<Hyphenated, _>("market_id")?.into_uuid(),
+        recorded_at: row.try_get("recorded_at")?,
+        kind: row.try_get("kind")?,
+        score: row.try_get("score")?,
+        detail: row.try_get("detail")?,
+        detected_at: row.try_get("detected_at")?,
+    })
+}
+
+fn metrics_from_row(row: &SqliteRow) -> Result<MarketMetrics, sqlx::Error> {
+    Ok(MarketMetrics {
+        market_id: row.try_get::<Hyphenated, _>("market_id")?.into_uuid(),
+        volatility_24h: row.try_get("volatility_24h")?,
+        volatility_7d: row.try_get("volatility_7d")?,
+        volatility_30d: row.try_get("volatility_30d")?,
+        momentum_24h: optional_decimal(row, "momentum_24h", PRICE_DP)?,
+        momentum_7d: optional_decimal(row, "momentum_7d", PRICE_DP)?,
+        max_drawdown_30d: optional_decimal(row, "max_drawdown_30d", PRICE_DP)?,
+        last_move_at: row.try_get("last_move_at")?,
+        volume_acceleration: row.try_get("volume_acceleration")?,
+        samples: row.try_get("samples")?,
+        computed_at: row.try_get("computed_at")?,
+    })
+}
+
+fn quarantined_from_row(row: &SqliteRow) -> Result<QuarantinedMarket, sqlx::Error> {
+    let Json(checks) = row.try_get("checks")?;
+    let Json(payload) = row.try_get("payload")?;
+
+    Ok(QuarantinedMarket {
+        id: row.try_get::<Hyphenated, _>("id")?.into_uuid(),
+        source: row.try_get("source")?,
+        source_id: row.try_get("source_id")?,
+        checks,
+        reason: row.try_get("reason")?,
+        payload,
+        quarantined_at: row.try_get("quarantined_at")?,
+        last_seen_at: row.try_get("last_seen_at")?,
+    })
+}
+
+/// FTS5 query matching every word, like `plainto_tsquery`: each word is
+/// quoted so punctuation is never read as query syntax
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Markets in the `markets` table, searched through `markets_fts`
+pub struct SqliteMarketStore {
+    pool: SqlitePool,
+}
+
+impl SqliteMarketStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MarketStore for SqliteMarketStore {
+    async fn upsert(&self, market: CreateMarket) -> StoreResult<Market> {
+        let now = Utc::now();
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO markets (
+                id, source_id, source, title, description, category, tags,
+                yes_price, no_price, volume, volume_24h, liquidity,
+                status, created_at, updated_at, close_at, url, source_category
+            )
+            VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14, ?15, ?16, ?17
+            )
+            ON CONFLICT (source, source_id)
+            DO UPDATE SET
+                title = excluded.title,
+                description = excluded.description,
+                category = excluded.category,
+                source_category = excluded.source_category,
+                tags = excluded.tags,
+                yes_price = excluded.yes_price,
+                no_price = excluded.no_price,
+                volume = excluded.volume,
+                volume_24h = excluded.volume_24h,
+                liquidity = excluded.liquidity,
+                status = excluded.status,
+                close_at = excluded.close_at,
+                url = CASE WHEN markets.placeholder THEN excluded.url ELSE markets.url END,
+                placeholder = FALSE,
+                updated_at = excluded.updated_at
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(&market.source_id)
+        .bind(market.source)
+        .bind(&market.title)
+        .bind(&market.description)
+        .bind(market.category)
+        .bind(market.tags.as_ref().map(Json))
+        .bind(real(market.yes_price))
+        .bind(real(market.no_price))
+        .bind(real(market.volume))
+        .bind(real(market.volume_24h))
+        .bind(market.liquidity.map(real))
+        .bind(market.status)
+        .bind(now)
+        .bind(market.close_at)
+        .bind(&market.url)
+        .bind(&market.source_category)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(market_from_row(&row)?)
+    }
+
+    async fn create_placeholder(&self, market: CreateMarket) -> StoreResult<Market> {
+        let now = Utc::now();
+
+        // The no-op update makes RETURNING give back an existing market too
+        let row = sqlx::query(
+            r#"
+            INSERT INTO markets (
+                id, source_id, source, title, description, category, tags,
+                yes_price, no_price, volume, volume_24h, liquidity,
+                status, created_at, updated_at, close_at, url, source_category, placeholder
+            )
+            VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?14, ?15, ?16, ?17,
+                TRUE
+            )
+            ON CONFLICT (source, source_id) DO UPDATE SET source_id = markets.source_id
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(&market.source_id)
+        .bind(market.source)
+        .bind(&market.title)
+        .bind(&market.description)
+        .bind(market.category)
+        .bind(market.tags.as_ref().map(Json))
+        .bind(real(market.yes_price))
+        .bind(real(market.no_price))
+        .bind(real(market.volume))
+        .bind(real(market.volume_24h))
+        .bind(market.liquidity.map(real))
+        .bind(market.status)
+        .bind(now)
+        .bind(market.close_at)
+        .bind(&market.url)
+        .bind(&market.source_category)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(market_from_row(&row)?)
+    }
+
+    async fn get_by_id(&self, id: Uuid) -> StoreResult<Market> {
+        let row = sqlx::query("SELECT * FROM markets WHERE id = ?1")
+            .bind(id.hyphenated())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(market_from_row(&row)?)
+    }
+
+    async fn get_by_source(&self, source: MarketSource, source_id: &str) -> StoreResult<Market> {
+        let row = sqlx::query("SELECT * FROM markets WHERE source = ?1 AND source_id = ?2")
+            .bind(source)
+            .bind(source_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(market_from_row(&row)?)
+    }
+
+    /// One query per reference, like `snapshots_at`; lookups are by key
+    async fn lookup(&self, refs: &[MarketRef]) -> StoreResult<Vec<Market>> {
+        let mut markets = Vec::with_capacity(refs.len());
+
+        for market_ref in refs {
+            let found = match market_ref {
+                MarketRef::Id(id) => self.get_by_id(*id).await,
+                MarketRef::Source { source, source_id } => {
+                    self.get_by_source(*source, source_id).await
+                }
+            };
+            match found {
+                Ok(market) if !markets.iter().any(|m: &Market| m.id == market.id) => {
+                    markets.push(market)
+                }
+                Ok(_) | Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(markets)
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        offset: i64,
+        sort_by: MarketSort,
+        order: SortOrder,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<Market>> {
+
+        // As of a time, volumes come from the latest snapshot by then
+        let as_of_volume = |column: &str| match filter.as_of {
+            Some(_) => format!(
+                "(SELECT ph.{} FROM price_history ph \
+                 WHERE ph.market_id = m.id AND ph.recorded_at <= ?5 \
+                 ORDER BY ph.recorded_at DESC LIMIT 1)",
+                column
+            ),
+            None => format!("m.{}", column),
+        };
+
+        let sort_column = match sort_by {
+            MarketSort::Volume => as_of_volume("volume"),
+            MarketSort::CreatedAt => "m.created_at".to_string(),
+            MarketSort::CloseAt => "m.close_at".to_string(),
+            MarketSort::Volume24h => as_of_volume("volume_24h"),
+            MarketSort::Metric(metric) => metric.to_string(),
+        };
+
+        let query = format!(
+            r#"
+            SELECT m.* FROM markets m
+            LEFT JOIN market_metrics mm ON mm.market_id = m.id
+            WHERE (?3 IS NULL OR m.category = ?3)
+              AND (?4 IS NULL OR EXISTS (SELECT 1 FROM json_each(m.tags) WHERE value = ?4))
+              AND (?5 IS NULL OR EXISTS (
+                  SELECT 1 FROM price_history ph
+                  WHERE ph.market_id = m.id AND ph.recorded_at <= ?5
+              ))
+              AND (?6 IS NULL OR m.source = ?6)
+            ORDER BY {} {} NULLS LAST, m.created_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+            sort_column,
+            order.as_sql()
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(limit)
+            .bind(offset)
+            .bind(filter.category)
+            .bind(&filter.tag)
+            .bind(filter.as_of)
+            .bind(filter.source)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(market_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn top_in_category(&self, category: Category, limit: i64) -> StoreResult<Vec<Market>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM markets
+            WHERE category = ?1
+            ORDER BY volume DESC
+            LIMIT ?2
+            "#,
+        )
+        .bind(category)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(market_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        limit: i64,
+        status: Option<MarketStatus>,
+        filter: &MarketFilter,
+    ) -> StoreResult<Vec<(Market, f32)>> {
+        let fts = fts_query(query);
+        if fts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // bm25() is lower for better matches; negate it so higher ranks first
+        let rows = sqlx::query(
+            r#"
+            SELECT m.*, -bm25(markets_fts) AS rank
+            FROM markets_fts
+            JOIN markets m ON m.rowid = markets_fts.rowid
+            WHERE markets_fts MATCH ?1
+              AND (?2 IS NULL OR m.source = ?2)
+              AND (?5 IS NULL OR m.category = ?5)
+              AND (?6 IS NULL OR EXISTS (SELECT 1 FROM json_each(m.tags) WHERE value = ?6))
+              AND (?7 IS NULL OR EXISTS (
+                  SELECT 1 FROM price_history ph
+                  WHERE ph.market_id = m.id AND ph.recorded_at <= ?7
+              ))
+              -- As of a time, the earliest status change since holds the status back then
+              AND (?3 IS NULL OR COALESCE(
+                  (SELECT r.old_value FROM market_revisions r
+                   WHERE ?7 IS NOT NULL AND r.market_id = m.id AND r.field = 'status'
+                     AND r.changed_at > ?7
+                   ORDER BY r.changed_at LIMIT 1),
+                  m.status
+              ) = ?3)
+            ORDER BY rank DESC, m.volume DESC
+            LIMIT ?4
+            "#,
+        )
+        .bind(fts)
+        .bind(filter.source)
+        .bind(status)
+        .bind(limit)
+        .bind(filter.category)
+        .bind(&filter.tag)
+        .bind(filter.as_of)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let results = rows
+            .iter()
+            .map(|row| {
+                let rank: f64 = row.try_get("rank")?;
+                Ok((market_from_row(row)?, rank as f32))
+            })
+            .collect::<Result<_, sqlx::Error>>()?;
+
+        Ok(results)
+    }
+
+    async fn update(&self, id: Uuid, update: UpdateMarket) -> StoreResult<Market> {
+        // Surface NotFound before attempting the update
+        self.get_by_id(id).await?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE markets
+            SET
+                yes_price = COALESCE(?2, yes_price),
+                no_price = COALESCE(?3, no_price),
+                volume = COALESCE(?4, volume),
+                volume_24h = COALESCE(?5, volume_24h),
+                liquidity = COALESCE(?6, liquidity),
+                status = COALESCE(?7, status),
+                close_at = COALESCE(?8, close_at),
+                updated_at = ?9
+            WHERE id = ?1
+            RETURNING *
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(update.yes_price.map(real))
+        .bind(update.no_price.map(real))
+        .bind(update.volume.map(real))
+        .bind(update.volume_24h.map(real))
+        .bind(update.liquidity.map(real))
+        .bind(update.status)
+        .bind(update.close_at)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(market_from_row(&row)?)
+    }
+
+    async fn set_taxonomy(
+        &self,
+        id: Uuid,
+        category: Option<Category>,
+        tags: Option<&[String]>,
+    ) -> StoreResult<()> {
+        let result = sqlx::query(
+            "UPDATE markets SET category = ?2, tags = ?3, updated_at = ?4 WHERE id = ?1",
+        )
+        .bind(id.hyphenated())
+        .bind(category)
+        .bind(tags.map(Json))
+        .bind(Utc::now())
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(StoreError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn taxonomy(&self, tag_limit: i64) -> StoreResult<Taxonomy> {
+        let categories: Vec<(Category, i64)> = sqlx::query_as(
+            "SELECT category, COUNT(*) FROM markets WHERE category IS NOT NULL GROUP BY category",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let tags: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT t.value AS tag, COUNT(*) AS markets
+            FROM markets m, json_each(m.tags) t
+            GROUP BY t.value
+            ORDER BY markets DESC, tag
+            LIMIT ?1
+            "#,
+        )
+        .bind(tag_limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(taxonomy(categories, tags))
+    }
+
+    async fn record_revisions(
+        &self,
+        market_id: Uuid,
+        changes: &[MarketChange],
+        changed_at: DateTime<Utc>,
+    ) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for change in changes {
+            sqlx::query(
+                r#"
+                INSERT INTO market_revisions (id, market_id, field, old_value, new_value, changed_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+            )
+            .bind(Uuid::new_v4().hyphenated())
+            .bind(market_id.hyphenated())
+            .bind(change.field)
+            .bind(&change.old_value)
+            .bind(&change.new_value)
+            .bind(changed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn revisions(
+        &self,
+        market_ids: &[Uuid],
+        after: Option<DateTime<Utc>>,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        if market_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders: Vec<String> =
+            (0..market_ids.len()).map(|i| format!("?{}", i + 2)).collect();
+        let sql = format!(
+            "SELECT * FROM market_revisions \
+             WHERE (?1 IS NULL OR changed_at > ?1) AND market_id IN ({}) \
+             ORDER BY changed_at, field",
+            placeholders.join(", ")
+        );
+
+        let mut query = sqlx::query(&sql).bind(after);
+        for id in market_ids {
+            query = query.bind(id.hyphenated());
+        }
+
+        let rows = query.fetch_all(&self.pool).await?;
+        Ok(rows.iter().map(revision_from_row).collect::<Result<_, _>>()?)
+    }
+
+    async fn latest_revisions(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+    ) -> StoreResult<Vec<MarketRevision>> {
+        let rows = sqlx::query(
+            "SELECT * FROM market_revisions WHERE market_id = ?1 \
+             ORDER BY changed_at DESC, field DESC LIMIT ?2",
+        )
+        .bind(market_id.hyphenated())
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(revision_from_row).collect::<Result<_, _>>()?)
+    }
+}
+
+/// Raw snapshots in `price_history`; hourly and daily resolutions keep the
+/// last snapshot of each bucket, computed on read
+pub struct SqliteHistoryStore {
+    pool: SqlitePool,
+}
+
+impl SqliteHistoryStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert in one transaction; existing `(market_id, recorded_at)` rows
+    /// are overwritten or skipped
+    /// Price history at `resolution` recorded in `[since, until]`, newest first.
+    /// With `at_close`, buckets are stamped at their end, and `[since, until]`
+    /// applies to that stamp.
+    #[allow(clippy::too_many_arguments)]
+    async fn history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        resolution: Resolution,
+        include_anomalies: bool,
+        at_close: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let snapshots = "SELECT * FROM price_history ph \
+                         WHERE market_id = ?1 AND (?2 IS NULL OR recorded_at >= ?2) \
+                         AND (?5 IS NULL OR recorded_at <= ?5) \
+                         AND (?4 OR NOT EXISTS (SELECT 1 FROM anomalies a \
+                         WHERE a.market_id = ph.market_id AND a.recorded_at = ph.recorded_at))";
+
+        // Timestamps are RFC 3339 in UTC, so a bucket is a prefix of the text
+        let bucket = match resolution {
+            Resolution::Raw => None,
+            Resolution::Hourly => {
+                Some(("substr(recorded_at, 1, 13) || ':00:00+00:00'", "+1 hour"))
+            }
+            Resolution::Daily => {
+                Some(("substr(recorded_at, 1, 10) || 'T00:00:00+00:00'", "+1 day"))
+            }
+        };
+
+        let sql = match bucket {
+            None => format!("{} ORDER BY recorded_at DESC LIMIT ?3", snapshots),
+            // With a single MAX(), SQLite takes the bare columns from the latest row
+            Some((bucket, _)) if !at_close => format!(
+                "SELECT id, market_id, yes_price, no_price, volume, volume_24h, liquidity, \
+                 bucket AS recorded_at \
+                 FROM (SELECT *, MAX(recorded_at), {} AS bucket FROM ({}) GROUP BY bucket) \
+                 ORDER BY recorded_at DESC LIMIT ?3",
+                bucket, snapshots
+            ),
+            // A bucket's close is only known once the bucket ends. Snapshots are
+            // read from a bucket earlier, then the buckets kept by their end.
+            Some((bucket, width)) => format!(
+                "SELECT * FROM (SELECT id, market_id, yes_price, no_price, volume, \
+                 volume_24h, liquidity, \
+                 strftime('%Y-%m-%dT%H:%M:%S+00:00', bucket, '{}') AS recorded_at \
+                 FROM (SELECT *, MAX(recorded_at), {} AS bucket FROM ({}) GROUP BY bucket)) \
+                 WHERE (?6 IS NULL OR recorded_at >= ?6) AND (?5 IS NULL OR recorded_at <= ?5) \
+                 ORDER BY recorded_at DESC LIMIT ?3",
+                width,
+                bucket,
+                snapshots.replace("?2", "?7")
+            ),
+        };
+
+        // Buckets are built from the filtered snapshots, so flagged points
+        // only ever appear at raw resolution
+        let rows = sqlx::query(&sql)
+            .bind(market_id.hyphenated())
+            .bind(since)
+            .bind(limit)
+            .bind(include_anomalies && resolution == Resolution::Raw)
+            .bind(until)
+            .bind(since)
+            .bind(since.zip(resolution.bucket_width()).map(|(since, width)| since - width))
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(history_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn insert_snapshots(&self, snapshots: &[Snapshot], overwrite: bool) -> StoreResult<u64> {
+        let on_conflict = if overwrite {
+            r#"
+            DO UPDATE SET
+                yes_price = excluded.yes_price,
+                no_price = excluded.no_price,
+                volume = excluded.volume,
+                volume_24h = excluded.volume_24h,
+                liquidity = excluded.liquidity
+            "#
+        } else {
+            "DO NOTHING"
+        };
+        let sql = format!(
+            r#"
+            INSERT INTO price_history (
+                id, market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (market_id, recorded_at) {}
+            "#,
+            on_conflict
+        );
+
+        let mut tx = self.pool.begin().await?;
+        let mut written = 0;
+
+        for snapshot in snapshots {
+            let result = sqlx::query(&sql)
+                .bind(Uuid::new_v4().hyphenated())
+                .bind(snapshot.market_id.hyphenated())
+                .bind(real(snapshot.yes_price))
+                .bind(real(snapshot.no_price))
+                .bind(real(snapshot.volume))
+                .bind(real(snapshot.volume_24h))
+                .bind(snapshot.liquidity.map(real))
+                .bind(snapshot.recorded_at)
+                .execute(&mut *tx)
+                .await?;
+
+            written += result.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(written)
+    }
+}
+
+#[async_trait]
+impl HistoryStore for SqliteHistoryStore {
+    async fn record_snapshot(
+        &self,
+        market: &Market,
+        recorded_at: DateTime<Utc>,
+    ) -> StoreResult<Option<PriceHistory>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO price_history (
+                id, market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            ON CONFLICT (market_id, recorded_at) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(market.id.hyphenated())
+        .bind(real(market.yes_price))
+        .bind(real(market.no_price))
+        .bind(real(market.volume))
+        .bind(real(market.volume_24h))
+        .bind(market.liquidity.map(real))
+        .bind(recorded_at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(history_from_row).transpose()?)
+    }
+
+    async fn span(&self, market_id: Uuid) -> StoreResult<HistorySpan> {
+        let row = sqlx::query(
+            "SELECT MIN(recorded_at) AS first_at, MAX(recorded_at) AS last_at, \
+             COUNT(*) AS snapshots FROM price_history WHERE market_id = ?1",
+        )
+        .bind(market_id.hyphenated())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(HistorySpan {
+            first_at: row.try_get("first_at")?,
+            last_at: row.try_get("last_at")?,
+            snapshots: row.try_get("snapshots")?,
+        })
+    }
+
+    async fn snapshots_at(
+        &self,
+        market_ids: &[Uuid],
+        at: DateTime<Utc>,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let mut snapshots = Vec::with_capacity(market_ids.len());
+
+        for id in market_ids {
+            let row = sqlx::query(
+                "SELECT * FROM price_history WHERE market_id = ?1 AND recorded_at <= ?2 \
+                 ORDER BY recorded_at DESC LIMIT 1",
+            )
+            .bind(id.hyphenated())
+            .bind(at)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if let Some(row) = row {
+                snapshots.push(history_from_row(&row)?);
+            }
+        }
+
+        Ok(snapshots)
+    }
+
+    /// One transaction for the whole batch; SQLite commits are the slow part
+    async fn import_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        self.insert_snapshots(snapshots, false).await
+    }
+
+    async fn replace_snapshots(&self, snapshots: &[Snapshot]) -> StoreResult<u64> {
+        self.insert_snapshots(snapshots, true).await
+    }
+
+    async fn get_history(
+        &self,
+        market_id: Uuid,
+        limit: i64,
+        hours: Option<i64>,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let since = hours.map(|h| Utc::now() - Duration::hours(h));
+        self.history(market_id, limit, since, None, resolution, include_anomalies, false)
+            .await
+    }
+
+    async fn history_between(
+        &self,
+        market_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        limit: i64,
+        resolution: Resolution,
+        include_anomalies: bool,
+    ) -> StoreResult<Vec<PriceHistory>> {
+        let (since, until) = (Some(from), Some(to));
+        self.history(market_id, limit, since, until, resolution, include_anomalies, true)
+            .await
+    }
+
+    fn export(
+        &self,
+        market_ids: Vec<Uuid>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<HistoryExportRow>> {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut sql = String::from(
+                "SELECT ph.*, m.source, m.source_id \
+                 FROM price_history ph JOIN markets m ON m.id = ph.market_id \
+                 WHERE (?1 IS NULL OR ph.recorded_at >= ?1) \
+                   AND (?2 IS NULL OR ph.recorded_at < ?2)",
+            );
+            if !market_ids.is_empty() {
+                let placeholders: Vec<String> =
+                    (0..market_ids.len()).map(|i| format!("?{}", i + 3)).collect();
+                sql.push_str(&format!(" AND ph.market_id IN ({})", placeholders.join(", ")));
+            }
+            sql.push_str(" ORDER BY ph.market_id, ph.recorded_at");
+
+            let mut query = sqlx::query(&sql).bind(from).bind(to);
+            for id in &market_ids {
+                query = query.bind(id.hyphenated());
+            }
+
+            let mut rows = query.fetch(&pool);
+            while let Some(row) = rows.try_next().await? {
+                let snapshot = history_from_row(&row)?;
+                yield HistoryExportRow {
+                    market_id: snapshot.market_id,
+                    source: row.try_get("source")?,
+                    source_id: row.try_get("source_id")?,
+                    recorded_at: snapshot.recorded_at,
+                    yes_price: snapshot.yes_price,
+                    no_price: snapshot.no_price,
+                    volume: snapshot.volume,
+                    volume_24h: snapshot.volume_24h,
+                    liquidity: snapshot.liquidity,
+                };
+            }
+        })
+    }
+}
+
+/// Collection audit log in `collection_runs`
+pub struct SqliteRunStore {
+    pool: SqlitePool,
+}
+
+impl SqliteRunStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RunStore for SqliteRunStore {
+    async fn start_cycle(&self, started_at: DateTime<Utc>) -> StoreResult<Uuid> {
+        let id = Uuid::new_v4();
+
+        sqlx::query("INSERT INTO collection_runs (id, kind, started_at) VALUES (?1, 'cycle', ?2)")
+            .bind(id.hyphenated())
+            .bind(started_at)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(id)
+    }
+
+    async fn finish_cycle(
+        &self,
+        id: Uuid,
+        markets_fetched: i32,
+        markets_recorded: i32,
+        error: Option<String>,
+    ) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE collection_runs
+            SET
+                finished_at = ?2,
+                markets_fetched = ?3,
+                markets_recorded = ?4,
+                markets_failed = ?5,
+                error = ?6
+            WHERE id = ?1
+            "#,
+        )
+        .bind(id.hyphenated())
+        .bind(Utc::now())
+        .bind(markets_fetched)
+        .bind(markets_recorded)
+        .bind((markets_fetched - markets_recorded).max(0))
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_source(&self, cycle_id: Uuid, run: SourceRun) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO collection_runs (
+                id, kind, parent_id, source, started_at, finished_at,
+                markets_fetched, markets_recorded, markets_failed, error, http_status
+            )
+            VALUES (?1, 'source', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )
+        .bind(Uuid::new_v4().hyphenated())
+        .bind(cycle_id.hyphenated())
+        .bind(&run.source)
+        .bind(run.started_at)
+        .bind(run.finished_at)
+        .bind(run.markets_fetched)
+        .bind(run.markets_recorded)
+        .bind((run.markets_fetched - run.markets_recorded).max(0))
+        .bind(&run.error)
+        .bind(run.http_status)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list(
+        &self,
+        limit: i64,
+        source: Option<&str>,
+        failed_only: bool,
+    ) -> StoreResult<Vec<CollectionRun>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT r.*
+            FROM collection_runs r
+            LEFT JOIN collection_runs p ON p.id = r.parent_id
+            WHERE (?1 IS NULL OR r.source = ?1)
+              AND (NOT ?2 OR r.error IS NOT NULL OR r.finished_at IS NULL)
+            ORDER BY COALESCE(p.started_at, r.started_at) DESC,
+                     r.parent_id NULLS FIRST,
+                     r.started_at
+            LIMIT ?3
+            "#,
+        )
+        .bind(source)
+        .bind(failed_only)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(run_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+/// Markets rejected by validation, in `quarantine`
+pub struct SqliteQuarantineStore {
+    pool: SqlitePool,
+}
+
+impl SqliteQuarantineStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl QuarantineStore for SqliteQuarantineStore {
+    async fn quarantine(&self, markets: &[QuarantinedMarket]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for market in markets {
+            sqlx::query(
+                r#"
+                INSERT INTO quarantine (
+                    id, source, source_id, checks, reason, payload, quarantined_at, last_seen_at
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                ON CONFLICT (source, COALESCE(source_id, ''), checks) DO UPDATE
+                SET reason = excluded.reason,
+                    payload = excluded.payload,
+                    last_seen_at = excluded.last_seen_at
+                "#,
+            )
+            .bind(market.id.hyphenated())
+            .bind(market.source)
+            .bind(&market.source_id)
+            .bind(Json(&market.checks))
+            .bind(&market.reason)
+            .bind(Json(&market.payload))
+            .bind(market.quarantined_at)
+            .bind(market.last_seen_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn quarantined(
+        &self,
+        since: DateTime<Utc>,
+        source: Option<MarketSource>,
+        limit: i64,
+    ) -> StoreResult<Vec<QuarantinedMarket>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM quarantine
+            WHERE last_seen_at >= ?1
+              AND (?2 IS NULL OR source = ?2)
+            ORDER BY last_seen_at DESC
+            LIMIT ?3
+            "#,
+        )
+        .bind(since)
+        .bind(source)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(quarantined_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+
+    async fn quarantine_summary(&self, since: DateTime<Utc>) -> StoreResult<Vec<SourceQuality>> {
+        let totals: Vec<(MarketSource, i64)> = sqlx::query_as(
+            "SELECT source, COUNT(*) FROM quarantine WHERE last_seen_at >= ?1 GROUP BY source",
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let checks: Vec<(MarketSource, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT q.source, c.value, COUNT(*)
+            FROM quarantine q, json_each(q.checks) c
+            WHERE q.last_seen_at >= ?1
+            GROUP BY q.source, c.value
+            "#,
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(summarize(totals, checks))
+    }
+}
+
+/// Flagged snapshots in `anomalies`
+pub struct SqliteAnomalyStore {
+    pool: SqlitePool,
+}
+
+impl SqliteAnomalyStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl AnomalyStore for SqliteAnomalyStore {
+    async fn flag(&self, anomalies: &[Anomaly]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for anomaly in anomalies {
+            sqlx::query(
+                r#"
+                INSERT INTO anomalies (market_id, recorded_at, kind, score, detail, detected_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT (market_id, recorded_at, kind) DO NOTHING
+                "#,
+            )
+            .bind(anomaly.market_id.hyphenated())
+            .bind(anomaly.recorded_at)
+            .bind(&anomaly.kind)
+            .bind(anomaly.score)
+            .bind(&anomaly.detail)
+            .bind(anomaly.detected_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn anomalies(
+        &self,
+        market_id: Option<Uuid>,
+        since: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> StoreResult<Vec<Anomaly>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM anomalies
+            WHERE (?1 IS NULL OR recorded_at >= ?1)
+              AND (?2 IS NULL OR market_id = ?2)
+            ORDER BY recorded_at DESC, kind
+            LIMIT ?3
+            "#,
+        )
+        .bind(since)
+        .bind(market_id.map(|id| id.hyphenated()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(anomaly_from_row)
+            .collect::<Result<_, _>>()?)
+    }
+}
+
+/// Derived per-market metrics in `market_metrics`
+pub struct SqliteMetricsStore {
+    pool: SqlitePool,
+}
+
+impl SqliteMetricsStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl MetricsStore for SqliteMetricsStore {
+    async fn save_metrics(&self, metrics: &[MarketMetrics]) -> StoreResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for m in metrics {
+            sqlx::query(
+                r#"
+                INSERT INTO market_metrics (
+                    market_id, volatility_24h, volatility_7d, volatility_30d,
+                    momentum_24h, momentum_7d, max_drawdown_30d, last_move_at,
+                    volume_acceleration, samples, computed_at
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                ON CONFLICT (market_id) DO UPDATE SET
+                    volatility_24h = excluded.volatility_24h,
+                    volatility_7d = excluded.volatility_7d,
+                    volatility_30d = excluded.volatility_30d,
+                    momentum_24h = excluded.momentum_24h,
+                    momentum_7d = excluded.momentum_7d,
+                    max_drawdown_30d = excluded.max_drawdown_30d,
+                    last_move_at = excluded.last_move_at,
+                    volume_acceleration = excluded.volume_acceleration,
+                    samples = excluded.samples,
+                    computed_at = excluded.computed_at
+                "#,
+            )
+            .bind(m.market_id.hyphenated())
+            .bind(m.volatility_24h)
+            .bind(m.volatility_7d)
+            .bind(m.volatility_30d)
+            .bind(m.momentum_24h.map(real))
+            .bind(m.momentum_7d.map(real))
+            .bind(m.max_drawdown_30d.map(real))
+            .bind(m.last_move_at)
+            .bind(m.volume_acceleration)
+            .bind(m.samples)
+            .bind(m.computed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn metrics(&self, market_id: Uuid) -> StoreResult<MarketMetrics> {
+        let row = sqlx::query("SELECT * FROM market_metrics WHERE market_id = ?1")
+            .bind(market_id.hyphenated())
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(metrics_from_row(&row)?)
+    }
+}
+
+/// Raw venue responses in `raw_payloads`
+pub struct SqliteArchiveStore {
+    pool: SqlitePool,
+}
+
+impl SqliteArchiveStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ArchiveStore for SqliteArchiveStore {
+    async fn archive(&self, payload: &RawPayload) -> StoreResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO raw_payloads (source, fetched_at, body)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT (source, fetched_at) DO NOTHING
+            "#,
+        )
+        .bind(payload.source)
+        .bind(payload.fetched_at)
+        .bind(&payload.body)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn payloads(
+        &self,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, StoreResult<RawPayload>> {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::try_stream! {
+            let mut rows = sqlx::query(
+                r#"
+                SELECT source, fetched_at, body FROM raw_payloads
+                WHERE (?1 IS NULL OR source = ?1)
+                  AND (?2 IS NULL OR fetched_at >= ?2)
+                  AND (?3 IS NULL OR fetched_at < ?3)
+                ORDER BY fetched_at, source
+                "#,
+            )
+            .bind(source)
+            .bind(from)
+            .bind(to)
+            .fetch(&pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield RawPayload {
+                    source: row.try_get("source")?,
+                    fetched_at: row.try_get("fetched_at")?,
+                    body: row.try_get("body")?,
+                };
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations::SQLITE_MIGRATOR;
+
+    async fn pool() -> SqlitePool {
+        // A single connection, since every in-memory connection is its own database
+        let pool = connect("sqlite::memory:", 1).await.unwrap();
+        SQLITE_MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    fn market(source_id: &str, title: &str) -> CreateMarket {
+        CreateMarket {
+            source_id: source_id.to_string(),
+            source: MarketSource::Kalshi,
+            title: title.to_string(),
+            description: "Resolves on the official announcement".to_string(),
+            category: None,
+            source_category: None,
+            tags: Some(vec!["person:powell".to_string()]),
+            yes_price: Decimal::new(4, 1),
+            no_price: Decimal::new(6, 1),
+            volume: "123456789.01".parse().unwrap(),
+            volume_24h: Decimal::new(10, 0),
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn upsert_keeps_id_and_updates_search_index() {
+        let markets = SqliteMarketStore::new(pool().await);
+
+        let first = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+        let second = markets.upsert(market("FED", "Fed raises rates")).await.unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.tags, Some(vec!["person:powell".to_string()]));
+        let everything = MarketFilter::default();
+        assert!(markets.search("cuts", 10, None, &everything).await.unwrap().is_empty());
+
+        let found = markets.search("raising rate", 10, None, &everything).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0.id, first.id);
+    }
+
+    #[tokio::test]
+    async fn collecting_a_placeholder_makes_it_real() {
+        let markets = SqliteMarketStore::new(pool().await);
+
+        let imported = market("FED", "FED");
+        let placeholder = markets.create_placeholder(imported.clone()).await.unwrap();
+        assert!(placeholder.placeholder);
+        let again = markets.create_placeholder(imported).await.unwrap();
+        assert_eq!(again.id, placeholder.id);
+
+        let collected = markets
+            .upsert(CreateMarket {
+                url: "https://kalshi.com/markets/FED".to_string(),
+                ..market("FED", "Fed cuts rates")
+            })
+            .await
+            .unwrap();
+        assert_eq!(collected.id, placeholder.id);
+        assert!(!collected.placeholder);
+        assert_eq!(collected.url, "https://kalshi.com/markets/FED");
+
+        // A real market is left alone
+        let existing = markets.create_placeholder(market("FED", "FED")).await.unwrap();
+        assert!(!existing.placeholder);
+        assert_eq!(existing.title, "Fed cuts rates");
+    }
+
+    #[tokio::test]
+    async fn filters_and_counts_by_category_and_tag() {
+        let markets = SqliteMarketStore::new(pool().await);
+        let fed = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+        markets
+            .upsert(CreateMarket {
+                category: Some(Category::Crypto),
+                tags: None,
+                ..market("BTC", "Bitcoin rate of return")
+            })
+            .await
+            .unwrap();
+        markets
+            .set_taxonomy(fed.id, Some(Category::Economics), Some(&["person:powell".to_string()]))
+            .await
+            .unwrap();
+
+        let filter = MarketFilter {
+            category: Some(Category::Economics),
+            tag: Some("person:powell".to_string()),
+            source: None,
+            as_of: None,
+        };
+        let listed =
+            markets.list(10, 0, MarketSort::Volume, SortOrder::Desc, &filter).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, fed.id);
+
+        let crypto = MarketFilter {
+            category: Some(Category::Crypto),
+            tag: None,
+            source: None,
+            as_of: None,
+        };
+        let found = markets.search("rate", 10, None, &crypto).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0.source_id, "BTC");
+
+        let polymarket = MarketFilter {
+            source: Some(MarketSource::Polymarket),
+            ..MarketFilter::default()
+        };
+        let listed = markets.list(10, 0, MarketSort::Volume, SortOrder::Desc, &polymarket);
+        assert!(listed.await.unwrap().is_empty());
+        assert!(markets.search("rate", 10, None, &polymarket).await.unwrap().is_empty());
+
+        let taxonomy = markets.taxonomy(10).await.unwrap();
+        assert_eq!(taxonomy.categories.len(), Category::ALL.len());
+        let economics = taxonomy.categories.iter().find(|c| c.category == Category::Economics);
+        assert_eq!(economics.unwrap().markets, 1);
+        assert_eq!(taxonomy.tags.len(), 1);
+        assert_eq!(taxonomy.tags[0].tag, "person:powell");
+
+        let rejected = sqlx::query("UPDATE markets SET category = 'elections'")
+            .execute(&markets.pool)
+            .await;
+        assert!(rejected.is_err());
+    }
+
+    #[tokio::test]
+    async fn records_and_reads_history() {
+        let pool = pool().await;
+        let markets = SqliteMarketStore::new(pool.clone());
+        let history = SqliteHistoryStore::new(pool);
+
+        let market = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+        history.record_snapshot(&market, Utc::now() - Duration::seconds(1)).await.unwrap();
+        history.record_snapshot(&market, Utc::now()).await.unwrap();
+
+        let raw = history
+            .get_history(market.id, 10, Some(1), Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(raw.len(), 2);
+        assert_eq!(raw[0].volume.to_string(), "123456789.01");
+
+        let hourly = history
+            .get_history(market.id, 10, None, Resolution::Hourly, false)
+            .await
+            .unwrap();
+        assert!(!hourly.is_empty() && hourly.len() <= 2);
+        assert_eq!(hourly[0].yes_price, Decimal::new(4, 1));
+    }
+
+    #[tokio::test]
+    async fn stamps_hourly_closes_at_the_end_of_their_hour() {
+        let pool = pool().await;
+        let markets = SqliteMarketStore::new(pool.clone());
+        let history = SqliteHistoryStore::new(pool);
+        let market = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+
+        let snapshot = |at: &str, yes_tenths: i64| Snapshot {
+            market_id: market.id,
+            yes_price: Decimal::new(yes_tenths, 1),
+            no_price: Decimal::ONE - Decimal::new(yes_tenths, 1),
+            volume: Decimal::ZERO,
+            volume_24h: Decimal::ZERO,
+            liquidity: None,
+            recorded_at: at.parse().unwrap(),
+        };
+        history
+            .import_snapshots(&[
+                snapshot("2024-06-01T10:05:00Z", 4),
+                snapshot("2024-06-01T10:50:00Z", 6),
+                snapshot("2024-06-01T11:10:00Z", 7),
+            ])
+            .await
+            .unwrap();
+        let at = |at: &str| at.parse::<DateTime<Utc>>().unwrap();
+
+        // Charts keep the bucket start
+        let charted = history
+            .get_history(market.id, 10, None, Resolution::Hourly, false)
+            .await
+            .unwrap();
+        assert_eq!(charted[1].recorded_at, at("2024-06-01T10:00:00Z"));
+
+        // The 10:00 hour closed at 0.6, but that was only known at 11:00
+        let closes = history
+            .history_between(
+                market.id,
+                at("2024-06-01T10:00:00Z"),
+                at("2024-06-01T12:00:00Z"),
+                10,
+                Resolution::Hourly,
+                false,
+            )
+            .await
+            .unwrap();
+        let stamps: Vec<_> = closes.iter().map(|h| (h.recorded_at, h.yes_price)).collect();
+        assert_eq!(
+            stamps,
+            [
+                (at("2024-06-01T12:00:00Z"), Decimal::new(7, 1)),
+                (at("2024-06-01T11:00:00Z"), Decimal::new(6, 1)),
+            ]
+        );
+
+        let known_by_half_past = history
+            .history_between(
+                market.id,
+                at("2024-06-01T11:00:00Z"),
+                at("2024-06-01T11:30:00Z"),
+                10,
+                Resolution::Hourly,
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(known_by_half_past.len(), 1);
+        assert_eq!(known_by_half_past[0].yes_price, Decimal::new(6, 1));
+    }
+
+    #[tokio::test]
+    async fn lists_markets_recorded_by_a_time_in_volume_order_then() {
+        let pool = pool().await;
+        let markets = SqliteMarketStore::new(pool.clone());
+        let history = SqliteHistoryStore::new(pool);
+        let day_ago = Utc::now() - Duration::hours(24);
+
+        let mut early = Vec::new();
+        for (id, volume) in [("A", 1), ("B", 2)] {
+            let mut market = markets.upsert(market(id, "Fed cuts rates")).await.unwrap();
+            market.volume = Decimal::from(volume);
+            history.record_snapshot(&market, day_ago).await.unwrap();
+            early.push(market.id);
+        }
+        markets.upsert(market("LATE", "Fed hikes rates")).await.unwrap();
+
+        let filter = MarketFilter {
+            as_of: Some(day_ago + Duration::minutes(1)),
+            ..MarketFilter::default()
+        };
+        let listed =
+            markets.list(1, 0, MarketSort::Volume, SortOrder::Desc, &filter).await.unwrap();
+        assert_eq!(listed.iter().map(|m| m.id).collect::<Vec<_>>(), [early[1]]);
+        let listed =
+            markets.list(10, 0, MarketSort::Volume, SortOrder::Asc, &filter).await.unwrap();
+        assert_eq!(listed.iter().map(|m| m.id).collect::<Vec<_>>(), early);
+    }
+
+    #[tokio::test]
+    async fn searches_markets_recorded_by_a_time_by_their_status_then() {
+        let pool = pool().await;
+        let markets = SqliteMarketStore::new(pool.clone());
+        let history = SqliteHistoryStore::new(pool);
+        let day_ago = Utc::now() - Duration::hours(24);
+
+        let cut = markets.upsert(market("CUT", "Fed cuts rates")).await.unwrap();
+        let hike = markets.upsert(market("HIKE", "Fed hikes rates")).await.unwrap();
+        for market in [&cut, &hike] {
+            history.record_snapshot(market, day_ago).await.unwrap();
+        }
+        markets.upsert(market("LATE", "Fed holds rates")).await.unwrap();
+        // The hike market only opened since
+        let change = MarketChange {
+            field: "status",
+            old_value: Some("closed".to_string()),
+            new_value: Some("open".to_string()),
+        };
+        markets.record_revisions(hike.id, &[change], Utc::now()).await.unwrap();
+
+        let filter = MarketFilter {
+            as_of: Some(day_ago + Duration::minutes(1)),
+            ..MarketFilter::default()
+        };
+        let found = |status| markets.search("rates", 1, Some(status), &filter);
+        let open = found(MarketStatus::Open).await.unwrap();
+        assert_eq!(open.iter().map(|(m, _)| m.id).collect::<Vec<_>>(), [cut.id]);
+        let closed = found(MarketStatus::Closed).await.unwrap();
+        assert_eq!(closed.iter().map(|(m, _)| m.id).collect::<Vec<_>>(), [hike.id]);
+        let recorded = markets.search("rates", 10, None, &filter).await.unwrap();
+        assert_eq!(recorded.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn reads_latest_revisions_newest_first() {
+        let markets = SqliteMarketStore::new(pool().await);
+        let market = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+        let change = |field: &'static str, old: &str, new: &str| MarketChange {
+            field,
+            old_value: Some(old.to_string()),
+            new_value: Some(new.to_string()),
+        };
+
+        let earlier = Utc::now() - Duration::hours(2);
+        markets
+            .record_revisions(market.id, &[change("status", "closed", "open")], earlier)
+            .await
+            .unwrap();
+        markets
+            .record_revisions(
+                market.id,
+                &[change("description", "Resolves on the announcement", "Resolves on the vote")],
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+
+        let latest = markets.latest_revisions(market.id, 1).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].field, "description");
+        assert_eq!(latest[0].new_value.as_deref(), Some("Resolves on the vote"));
+        assert_eq!(markets.latest_revisions(market.id, 10).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn leaves_flagged_snapshots_out_of_history() {
+        let pool = pool().await;
+        let markets = SqliteMarketStore::new(pool.clone());
+        let history = SqliteHistoryStore::new(pool.clone());
+        let anomalies = SqliteAnomalyStore::new(pool);
+
+        let market = markets.upsert(market("FED", "Fed cuts rates")).await.unwrap();
+        history.record_snapshot(&market, Utc::now() - Duration::seconds(1)).await.unwrap();
+        let spike = history.record_snapshot(&market, Utc::now()).await.unwrap().unwrap();
+
+        anomalies
+            .flag(&[Anomaly {
+                market_id: market.id,
+                recorded_at: spike.recorded_at,
+                kind: "price_spike".to_string(),
+                score: Some(9.5),
+                detail: "yes price moved 0.40".to_string(),
+                detected_at: Utc::now(),
+            }])
+            .await
+            .unwrap();
+
+        let raw = history
+            .get_history(market.id, 10, None, Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(raw.len(), 1);
+        assert!(raw[0].recorded_at < spike.recorded_at);
+
+        let all = history
+            .get_history(market.id, 10, None, Resolution::Raw, true)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let flagged = anomalies.anomalies(Some(market.id), None, 10).await.unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].score, Some(9.5));
+    }
+
+    #[tokio::test]
+    async fn lists_cycles_with_their_steps() {
+        let runs = SqliteRunStore::new(pool().await);
+
+        let cycle = runs.start_cycle(Utc::now()).await.unwrap();
+        runs.record_source(
+            cycle,
+            SourceRun {
+                source: "kalshi".to_string(),
+                started_at: Utc::now(),
+                finished_at: Utc::now(),
+                markets_fetched: 5,
+                markets_recorded: 4,
+                error: None,
+                http_status: None,
+            },
+        )
+        .await
+        .unwrap();
+        runs.finish_cycle(cycle, 5, 4, None).await.unwrap();
+
+        let listed = runs.list(10, None, false).await.unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, cycle);
+        assert_eq!(listed[1].parent_id, Some(cycle));
+        assert_eq!(listed[1].markets_failed, 1);
+    }
+
+    #[tokio::test]
+    async fn summarizes_quarantine_by_failed_check() {
+        let quarantine = SqliteQuarantineStore::new(pool().await);
+        let rejected = |source_id: &str, checks: &[&str]| QuarantinedMarket {
+            id: Uuid::new_v4(),
+            source: MarketSource::Kalshi,
+            source_id: Some(source_id.to_string()),
+            checks: checks.iter().map(|c| c.to_string()).collect(),
+            reason: checks.join("; "),
+            payload: serde_json::json!({ "ticker": source_id }),
+            quarantined_at: Utc::now(),
+            last_seen_at: Utc::now(),
+        };
+
+        quarantine
+            .quarantine(&[
+                rejected("A", &["price_range", "price_sum"]),
+                rejected("B", &["price_sum"]),
+            ])
+            .await
+            .unwrap();
+
+        let since = Utc::now() - Duration::hours(1);
+        let summary = quarantine.quarantine_summary(since).await.unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].quarantined, 2);
+        assert_eq!(summary[0].checks[0].check, "price_sum");
+        assert_eq!(summary[0].checks[0].count, 2);
+
+        let recent = quarantine.quarantined(since, None, 10).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].payload["ticker"], "B");
+    }
+
+    #[tokio::test]
+    async fn keeps_one_row_per_market_and_failed_checks() {
+        let quarantine = SqliteQuarantineStore::new(pool().await);
+        let first_seen = Utc::now() - Duration::hours(2);
+        let rejected = |checks: &[&str], yes: u32, seen_at| QuarantinedMarket {
+            id: Uuid::new_v4(),
+            source: MarketSource::Kalshi,
+            source_id: Some("A".to_string()),
+            checks: checks.iter().map(|c| c.to_string()).collect(),
+            reason: format!("yes {yes}"),
+            payload: serde_json::json!({ "yes_bid": yes }),
+            quarantined_at: seen_at,
+            last_seen_at: seen_at,
+        };
+
+        let now = Utc::now();
+        quarantine.quarantine(&[rejected(&["price_sum"], 70, first_seen)]).await.unwrap();
+        quarantine.quarantine(&[rejected(&["price_sum"], 75, now)]).await.unwrap();
+        quarantine.quarantine(&[rejected(&["price_range"], 120, now)]).await.unwrap();
+
+        let since = Utc::now() - Duration::hours(1);
+        let recent = quarantine.quarantined(since, None, 10).await.unwrap();
+        assert_eq!(recent.len(), 2);
+
+        let price_sum = recent.iter().find(|q| q.checks == ["price_sum"]).unwrap();
+        assert_eq!(price_sum.quarantined_at, first_seen);
+        assert_eq!(price_sum.last_seen_at, now);
+        assert_eq!(price_sum.reason, "yes 75");
+        assert_eq!(price_sum.payload["yes_bid"], 75);
+
+        let summary = quarantine.quarantine_summary(since).await.unwrap();
+        assert_eq!(summary[0].quarantined, 2);
+    }
+}
@@ -0,0 +1,292 @@
+use chrono::{Duration, Utc};
+use pm_shared::{Anomaly, Decimal, MarketStatus, PriceHistory};
+
+/// Price changes needed before their spread is trusted
+const MIN_CHANGES: usize = 10;
+
+/// Floor on the standard deviation of price changes, so a market that has
+/// barely moved doesn't flag every ordinary tick
+const MIN_STDEV: f64 = 0.01;
+
+/// Thresholds a new snapshot is scored against
+#[derive(Debug, Clone)]
+pub struct AnomalyDetector {
+    /// |z| of the yes price change at which a snapshot is a spike
+    pub zscore: f64,
+    /// Unflagged snapshots loaded as the market's recent history
+    pub window: i64,
+    /// How long an open market's prices and volumes may sit unchanged
+    pub stale_after: Duration,
+}
+
+impl Default for AnomalyDetector {
+    fn default() -> Self {
+        Self {
+            zscore: 4.0,
+            window: 48,
+            stale_after: Duration::hours(24),
+        }
+    }
+}
+
+/// A market's history before the snapshot being scored
+#[derive(Debug, Default)]
+pub struct Baseline {
+    /// Snapshots not flagged as anomalies, newest first
+    pub unflagged: Vec<PriceHistory>,
+    /// The snapshot just before, flagged or not
+    pub previous: Option<PriceHistory>,
+    /// Latest snapshot at least `stale_after` older
+    pub stale_anchor: Option<PriceHistory>,
+}
+
+impl AnomalyDetector {
+    /// Anomalies found in `latest`, judged against `baseline`.
+    ///
+    /// Jumps and volume drops are measured from both the last unflagged
+    /// snapshot and the previous one, and only count when they hold against
+    /// both: a spike reverting to the last good value is not flagged, nor is
+    /// the second point of a level shift that the first point was flagged for.
+    pub fn score(
+        &self,
+        status: MarketStatus,
+        latest: &PriceHistory,
+        baseline: &Baseline,
+    ) -> Vec<Anomaly> {
+        let Some(last) = baseline.unflagged.first() else {
+            return Vec::new();
+        };
+        let references: Vec<&PriceHistory> = std::iter::once(last)
+            .chain(baseline.previous.as_ref())
+            .collect();
+        let mut anomalies = Vec::new();
+
+        if let Some((mean, stdev)) = change_stats(&baseline.unflagged) {
+            let yes = float(latest.yes_price);
+            let (reference, z) = references
+                .iter()
+                .map(|r| (r, (yes - float(r.yes_price) - mean) / stdev))
+                .min_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+                .expect("references include the last unflagged snapshot");
+
+            if z.abs() >= self.zscore {
+                anomalies.push(self.anomaly(
+                    latest,
+                    "price_spike",
+                    Some(z),
+                    format!(
+                        "yes price moved {} -> {} (z-score {:.1})",
+                        reference.yes_price, latest.yes_price, z
+                    ),
+                ));
+            }
+        }
+
+        let floor = references.iter().map(|r| r.volume).min().unwrap_or(last.volume);
+        if latest.volume < floor {
+            anomalies.push(self.anomaly(
+                latest,
+                "volume_decrease",
+                None,
+                format!("cumulative volume fell from {} to {}", floor, latest.volume),
+            ));
+        }
+
+        if status == MarketStatus::Open {
+            if let Some(anchor) = &baseline.stale_anchor {
+                let unchanged = unchanged(anchor, latest)
+                    && baseline
+                        .unflagged
+                        .iter()
+                        .take_while(|s| s.recorded_at > anchor.recorded_at)
+                        .all(|s| unchanged(s, latest));
+
+                if unchanged {
+                    anomalies.push(self.anomaly(
+                        latest,
+                        "stale",
+                        None,
+                        format!(
+                            "prices and volume unchanged since {}",
+                            anchor.recorded_at.format("%Y-%m-%d %H:%M:%S")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        anomalies
+    }
+
+    fn anomaly(
+        &self,
+        latest: &PriceHistory,
+        kind: &str,
+        score: Option<f64>,
+        detail: String,
+    ) -> Anomaly {
+        Anomaly {
+            market_id: latest.market_id,
+            recorded_at: latest.recorded_at,
+            kind: kind.to_string(),
+            score,
+            detail,
+            detected_at: Utc::now(),
+        }
+    }
+}
+
+/// Mean and standard deviation (floored at `MIN_STDEV`) of the changes
+/// between consecutive snapshots, or `None` with too few to judge by
+fn change_stats(snapshots: &[PriceHistory]) -> Option<(f64, f64)> {
+    let changes: Vec<f64> = snapshots
+        .windows(2)
+        .map(|pair| float(pair[0].yes_price) - float(pair[1].yes_price))
+        .collect();
+    if changes.len() < MIN_CHANGES {
+        return None;
+    }
+
+    let n = changes.len() as f64;
+    let mean = changes.iter().sum::<f64>() / n;
+    let variance = changes.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / n;
+
+    Some((mean, variance.sqrt().max(MIN_STDEV)))
+}
+
+fn unchanged(a: &PriceHistory, b: &PriceHistory) -> bool {
+    a.yes_price == b.yes_price
+        && a.no_price == b.no_price
+        && a.volume == b.volume
+        && a.volume_24h == b.volume_24h
+}
+
+fn float(value: Decimal) -> f64 {
+    f64::try_from(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    fn snapshot(at: DateTime<Utc>, yes_cents: i64, volume: i64) -> PriceHistory {
+        let yes_price = Decimal::new(yes_cents, 2);
+        PriceHistory {
+            id: Uuid::new_v4(),
+            market_id: Uuid::nil(),
+            yes_price,
+            no_price: Decimal::ONE - yes_price,
+            volume: Decimal::from(volume),
+            volume_24h: Decimal::from(10),
+            liquidity: None,
+            recorded_at: at,
+        }
+    }
+
+    /// Hourly snapshots wobbling around 50 cents with growing volume, newest first
+    fn history(now: DateTime<Utc>) -> Vec<PriceHistory> {
+        (1..=24)
+            .map(|h| snapshot(now - Duration::hours(h), 50 + (h % 3) - 1, 1000 - h))
+            .collect()
+    }
+
+    fn kinds(anomalies: &[Anomaly]) -> Vec<&str> {
+        anomalies.iter().map(|a| a.kind.as_str()).collect()
+    }
+
+    #[test]
+    fn flags_spike_but_not_its_reversion() {
+        let detector = AnomalyDetector::default();
+        let now = Utc::now();
+        let unflagged = history(now);
+        let spike = snapshot(now, 0, 1000);
+
+        let baseline = Baseline {
+            previous: unflagged.first().cloned(),
+            unflagged: unflagged.clone(),
+            stale_anchor: None,
+        };
+        let found = detector.score(MarketStatus::Open, &spike, &baseline);
+        assert_eq!(kinds(&found), vec!["price_spike"]);
+        assert!(found[0].score.unwrap() < -detector.zscore);
+
+        // Back to normal next snapshot: the flagged 0 is only the previous point
+        let reverted = snapshot(now + Duration::hours(1), 50, 1001);
+        let baseline = Baseline {
+            previous: Some(spike),
+            unflagged,
+            stale_anchor: None,
+        };
+        assert!(detector.score(MarketStatus::Open, &reverted, &baseline).is_empty());
+    }
+
+    #[test]
+    fn level_shift_is_flagged_once() {
+        let detector = AnomalyDetector::default();
+        let now = Utc::now();
+        let unflagged = history(now);
+        let first = snapshot(now, 90, 1000);
+
+        // The second point at the new level matches the flagged previous one
+        let second = snapshot(now + Duration::hours(1), 90, 1001);
+        let baseline = Baseline {
+            previous: Some(first),
+            unflagged,
+            stale_anchor: None,
+        };
+        assert!(detector.score(MarketStatus::Open, &second, &baseline).is_empty());
+    }
+
+    #[test]
+    fn flags_cumulative_volume_going_backwards() {
+        let detector = AnomalyDetector::default();
+        let now = Utc::now();
+        let unflagged = history(now);
+
+        let baseline = Baseline {
+            previous: unflagged.first().cloned(),
+            unflagged,
+            stale_anchor: None,
+        };
+        let found = detector.score(MarketStatus::Open, &snapshot(now, 50, 10), &baseline);
+        assert_eq!(kinds(&found), vec!["volume_decrease"]);
+    }
+
+    #[test]
+    fn flags_open_market_unchanged_for_too_long() {
+        let detector = AnomalyDetector::default();
+        let now = Utc::now();
+        let unflagged: Vec<PriceHistory> =
+            (1..=30).map(|h| snapshot(now - Duration::hours(h), 42, 500)).collect();
+        let latest = snapshot(now, 42, 500);
+
+        let baseline = Baseline {
+            previous: unflagged.first().cloned(),
+            stale_anchor: unflagged.get(24).cloned(),
+            unflagged,
+        };
+        assert_eq!(
+            kinds(&detector.score(MarketStatus::Open, &latest, &baseline)),
+            vec!["stale"]
+        );
+        assert!(detector.score(MarketStatus::Closed, &latest, &baseline).is_empty());
+    }
+
+    #[test]
+    fn needs_history_before_scoring_prices() {
+        let detector = AnomalyDetector::default();
+        let now = Utc::now();
+        let unflagged: Vec<PriceHistory> = history(now).into_iter().take(3).collect();
+
+        let baseline = Baseline {
+            previous: unflagged.first().cloned(),
+            unflagged,
+            stale_anchor: None,
+        };
+        assert!(detector
+            .score(MarketStatus::Open, &snapshot(now, 5, 1000), &baseline)
+            .is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::{self, BoxStream, StreamExt};
+use pm_shared::store::{ArchiveStore, RawPayload};
+use pm_shared::MarketSource;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// File name timestamp, at the microsecond precision the database keeps
+const FILE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.6fZ";
+
+/// Where raw venue responses are archived, parsed from `RAW_ARCHIVE`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchiveTarget {
+    /// The `raw_payloads` table
+    Table,
+    /// `<dir>/<source>/<YYYY-MM-DD>/<fetched_at>.json.gz` files
+    Directory(PathBuf),
+}
+
+impl FromStr for ArchiveTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_prefix("dir:") {
+            Some(dir) if !dir.is_empty() => Ok(ArchiveTarget::Directory(PathBuf::from(dir))),
+            _ if s.eq_ignore_ascii_case("table") => Ok(ArchiveTarget::Table),
+            _ => bail!("Invalid raw archive '{}', expected table or dir:<path>", s),
+        }
+    }
+}
+
+/// An archived response body, decompressed
+#[derive(Debug)]
+pub struct Archived {
+    pub source: MarketSource,
+    pub fetched_at: DateTime<Utc>,
+    pub body: Vec<u8>,
+}
+
+/// Gzip-compressed copies of venue responses, keyed by source and fetch time
+pub enum RawArchive {
+    Table(Arc<dyn ArchiveStore>),
+    Directory(PathBuf),
+}
+
+impl RawArchive {
+    /// Compress and keep a response body
+    pub async fn store(
+        &self,
+        source: MarketSource,
+        fetched_at: DateTime<Utc>,
+        body: &[u8],
+    ) -> Result<()> {
+        let compressed = gzip(body)?;
+
+        match self {
+            RawArchive::Table(store) => {
+                store
+                    .archive(&RawPayload {
+                        source,
+                        fetched_at,
+                        body: compressed,
+                    })
+                    .await?;
+            }
+            RawArchive::Directory(root) => {
+                let path = file_path(root, source, fetched_at);
+                let dir = path.parent().expect("archive paths have a parent");
+                tokio::fs::create_dir_all(dir)
+                    .await
+                    .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+                // Written aside and renamed so readers never see a partial file
+                let partial = path.with_extension("partial");
+                tokio::fs::write(&partial, compressed)
+                    .await
+                    .with_context(|| format!("Failed to write {}", partial.display()))?;
+                tokio::fs::rename(&partial, &path).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Archived responses fetched in `[from, to)`, oldest first
+    pub fn read(
+        &self,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> BoxStream<'static, Result<Archived>> {
+        match self {
+            RawArchive::Table(store) => store
+                .payloads(source, from, to)
+                .map(|payload| {
+                    let payload = payload?;
+                    Ok(Archived {
+                        source: payload.source,
+                        fetched_at: payload.fetched_at,
+                        body: gunzip(&payload.body)?,
+                    })
+                })
+                .boxed(),
+            RawArchive::Directory(root) => {
+                let files = match list_files(root, source, from, to) {
+                    Ok(files) => files,
+                    Err(e) => return stream::once(async { Err(e) }).boxed(),
+                };
+
+                stream::iter(files)
+                    .then(|(source, fetched_at, path)| async move {
+                        let compressed = tokio::fs::read(&path)
+                            .await
+                            .with_context(|| format!("Failed to read {}", path.display()))?;
+                        Ok(Archived {
+                            source,
+                            fetched_at,
+                            body: gunzip(&compressed)
+                                .with_context(|| format!("Corrupt archive {}", path.display()))?,
+                        })
+                    })
+                    .boxed()
+            }
+        }
+    }
+}
+
+fn file_path(root: &Path, source: MarketSource, fetched_at: DateTime<Utc>) -> PathBuf {
+    root.join(source.as_str())
+        .join(fetched_at.format("%Y-%m-%d").to_string())
+        .join(format!("{}.json.gz", fetched_at.format(FILE_TIME_FORMAT)))
+}
+
+/// Archive files in range, sorted by fetch time. Day directories outside
+/// the range are skipped without being listed.
+fn list_files(
+    root: &Path,
+    source: Option<MarketSource>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+) -> Result<Vec<(MarketSource, DateTime<Utc>, PathBuf)>> {
+    let sources = match source {
+        Some(source) => vec![source],
+        None => MarketSource::ALL.to_vec(),
+    };
+    let mut files = Vec::new();
+
+    for source in sources {
+        let source_dir = root.join(source.as_str());
+        if !source_dir.exists() {
+            continue;
+        }
+
+        for day in std::fs::read_dir(&source_dir)? {
+            let day = day?;
+            let Some(date) = day
+                .file_name()
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, "%Y-%m-%d").ok())
+            else {
+                continue;
+            };
+            if from.is_some_and(|from| date < from.date_naive())
+                || to.is_some_and(|to| date > to.date_naive())
+            {
+                continue;
+            }
+
+            for file in std::fs::read_dir(day.path())? {
+                let path = file?.path();
+                let Some(fetched_at) = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_suffix(".json.gz"))
+                    .and_then(|stem| NaiveDateTime::parse_from_str(stem, FILE_TIME_FORMAT).ok())
+                    .map(|t| t.and_utc())
+                else {
+                    continue;
+                };
+
+                if from.is_none_or(|from| fetched_at >= from) && to.is_none_or(|to| fetched_at < to) {
+                    files.push((source, fetched_at, path));
+                }
+            }
+        }
+    }
+
+    files.sort_by_key(|(source, fetched_at, _)| (*fetched_at, source.as_str()));
+    Ok(files)
+}
+
+fn gzip(body: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+    Ok(encoder.finish()?)
+}
+
+fn gunzip(compressed: &[u8]) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    GzDecoder::new(compressed).read_to_end(&mut body)?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, SubsecRound};
+    use futures::TryStreamExt;
+    use pm_shared::store::MemoryStore;
+    use uuid::Uuid;
+
+    async fn round_trip(archive: RawArchive) {
+        let fetched_at = Utc::now().trunc_subsecs(6);
+        let body = br#"{"markets":[]}"#;
+        archive.store(MarketSource::Kalshi, fetched_at, body).await.unwrap();
+        archive
+            .store(MarketSource::Polymarket, fetched_at - Duration::hours(1), b"[]")
+            .await
+            .unwrap();
+
+        let all: Vec<Archived> = archive.read(None, None, None).try_collect().await.unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].source, MarketSource::Polymarket);
+
+        let since: Vec<Archived> = archive
+            .read(Some(MarketSource::Kalshi), Some(fetched_at), None)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(since.len(), 1);
+        assert_eq!(since[0].fetched_at, fetched_at);
+        assert_eq!(since[0].body, body);
+    }
+
+    #[tokio::test]
+    async fn table_archive_round_trips() {
+        round_trip(RawArchive::Table(Arc::new(MemoryStore::new()))).await;
+    }
+
+    #[tokio::test]
+    async fn directory_archive_round_trips() {
+        let root = std::env::temp_dir().join(format!("pm-archive-{}", Uuid::new_v4()));
+        round_trip(RawArchive::Directory(root.clone())).await;
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn parses_targets() {
+        assert_eq!("table".parse::<ArchiveTarget>().unwrap(), ArchiveTarget::Table);
+        assert_eq!(
+            "dir:/var/lib/pm".parse::<ArchiveTarget>().unwrap(),
+            ArchiveTarget::Directory(PathBuf::from("/var/lib/pm"))
+        );
+        assert!("s3://bucket".parse::<ArchiveTarget>().is_err());
+    }
+}
@@ -1,13 +1,17 @@
-use anyhow::Result;
 use chrono::{DateTime, Utc};
-use pm_shared::CreateMarket;
+use pm_shared::{CreateMarket, Decimal, MarketSource, MarketStatus, AMOUNT_DP, PRICE_DP};
 use serde::Deserialize;
 
+use super::{CollectError, Collected};
+use crate::taxonomy;
+use crate::validate::Issue;
+
 const KALSHI_API: &str = "https://api.elections.kalshi.com/trade-api/v2";
 
 #[derive(Debug, Deserialize)]
 struct KalshiMarketsResponse {
-    markets: Vec<KalshiMarket>,
+    /// Kept raw so rejected markets can be quarantined as sent
+    markets: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -16,11 +20,11 @@ struct KalshiMarket {
     title: String,
     #[serde(default)]
     subtitle: String,
-    yes_ask: Option<f64>,
-    yes_bid: Option<f64>,
-    volume: Option<f64>,
-    volume_24h: Option<f64>,
-    open_interest: Option<f64>,
+    yes_ask: Option<Decimal>,
+    yes_bid: Option<Decimal>,
+    volume: Option<Decimal>,
+    volume_24h: Option<Decimal>,
+    open_interest: Option<Decimal>,
     status: String,
     close_time: Option<String>,
     category: Option<String>,
@@ -28,17 +32,29 @@ struct KalshiMarket {
 
 pub struct KalshiCollector {
     client: reqwest::Client,
+    base_url: String,
 }
 
 impl KalshiCollector {
     pub fn new() -> Self {
         Self {
             client: reqwest::Client::new(),
+            base_url: KALSHI_API.to_string(),
+        }
+    }
+
+    /// Collector against another Kalshi-compatible API
+    #[cfg(test)]
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.to_string(),
         }
     }
 
-    pub async fn fetch_markets(&self, limit: usize) -> Result<Vec<CreateMarket>> {
-        let url = format!("{}/markets?limit={}&status=open", KALSHI_API, limit);
+    /// Fetch the raw markets response body
+    pub async fn fetch(&self, limit: usize) -> Result<Vec<u8>, CollectError> {
+        let url = format!("{}/markets?limit={}&status=open", self.base_url, limit);
 
         tracing::info!("Fetching markets from Kalshi: {}", url);
 
@@ -49,42 +65,85 @@ impl KalshiCollector {
             .await?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Kalshi API error: {}", response.status());
+            return Err(CollectError::Status {
+                venue: MarketSource::Kalshi,
+                status: response.status(),
+            });
         }
 
-        let data: KalshiMarketsResponse = response.json().await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Convert a markets response body, as fetched or from the archive
+    pub fn parse(&self, body: &[u8]) -> Result<Vec<Collected>, CollectError> {
+        let data: KalshiMarketsResponse = serde_json::from_slice(body)?;
 
-        tracing::info!("Fetched {} markets from Kalshi", data.markets.len());
+        tracing::debug!("Parsed {} Kalshi markets", data.markets.len());
 
-        let create_markets = data.markets
+        let collected = data.markets
             .into_iter()
-            .filter_map(|m| self.convert_market(m))
+            .map(|payload| self.collect(payload))
             .collect();
 
-        Ok(create_markets)
+        Ok(collected)
     }
 
-    fn convert_market(&self, market: KalshiMarket) -> Option<CreateMarket> {
-        // Calculate midpoint price from bid/ask
-        let yes_price = match (market.yes_bid, market.yes_ask) {
-            (Some(bid), Some(ask)) => ((bid + ask) / 2.0) as f32 / 100.0,
-            (Some(bid), None) => (bid as f32) / 100.0,
-            (None, Some(ask)) => (ask as f32) / 100.0,
-            (None, None) => 0.5,
+    fn collect(&self, payload: serde_json::Value) -> Collected {
+        let source_id = payload
+            .get("ticker")
+            .and_then(|t| t.as_str())
+            .map(str::to_string);
+
+        let market = serde_json::from_value::<KalshiMarket>(payload.clone())
+            .map_err(|e| vec![Issue::new("malformed", e.to_string())])
+            .and_then(|m| self.convert_market(m));
+
+        Collected {
+            source_id,
+            payload,
+            market,
+        }
+    }
+
+    fn convert_market(&self, market: KalshiMarket) -> Result<CreateMarket, Vec<Issue>> {
+        let mut issues = Vec::new();
+
+        let status = canonical_status(&market.status);
+        if status.is_none() {
+            issues.push(Issue::new(
+                "unknown_status",
+                format!("unknown status {:?}", market.status),
+            ));
+        }
+
+        // Calculate midpoint price from bid/ask, quoted in cents
+        let yes_cents = match (market.yes_bid, market.yes_ask) {
+            (Some(bid), Some(ask)) => Some((bid + ask) / Decimal::TWO),
+            (Some(bid), None) => Some(bid),
+            (None, Some(ask)) => Some(ask),
+            (None, None) => None,
+        };
+        if yes_cents.is_none() {
+            issues.push(Issue::new("missing_price", "no yes bid or ask"));
+        }
+
+        let (Some(status), Some(yes_cents)) = (status, yes_cents) else {
+            return Err(issues);
         };
+        let yes_price = (yes_cents / Decimal::ONE_HUNDRED).round_dp(PRICE_DP);
 
-        let no_price = 1.0 - yes_price;
+        let no_price = Decimal::ONE - yes_price;
 
         let volume = market.volume
-            .map(|v| v as f32)
-            .unwrap_or(0.0);
+            .map(|v| v.round_dp(AMOUNT_DP))
+            .unwrap_or_default();
 
         let volume_24h = market.volume_24h
-            .map(|v| v as f32)
-            .unwrap_or(0.0);
+            .map(|v| v.round_dp(AMOUNT_DP))
+            .unwrap_or_default();
 
         let liquidity = market.open_interest
-            .map(|oi| oi as f32);
+            .map(|oi| oi.round_dp(AMOUNT_DP));
 
         let close_at = market.close_time
             .and_then(|d| DateTime::parse_from_rfc3339(&d).ok())
@@ -92,21 +151,107 @@ impl KalshiCollector {
 
         let url = format!("https://kalshi.com/markets/{}", market.ticker);
 
-        Some(CreateMarket {
+        let taxonomy = taxonomy::classify(
+            MarketSource::Kalshi,
+            market.category.as_deref(),
+            &market.title,
+        );
+
+        Ok(CreateMarket {
             source_id: market.ticker.clone(),
-            source: "kalshi".to_string(),
+            source: MarketSource::Kalshi,
             title: market.title,
             description: market.subtitle,
-            category: market.category,
-            tags: None,
+            category: taxonomy.category,
+            source_category: market.category,
+            tags: taxonomy.tags,
             yes_price,
             no_price,
             volume,
             volume_24h,
             liquidity,
-            status: market.status.to_lowercase(),
+            status,
             close_at,
             url,
         })
     }
 }
+
+/// Map Kalshi's market lifecycle onto the canonical statuses
+fn canonical_status(raw: &str) -> Option<MarketStatus> {
+    match raw.to_lowercase().as_str() {
+        "active" | "open" => Some(MarketStatus::Open),
+        "initialized" | "unopened" | "inactive" | "closed" | "disputed" => {
+            Some(MarketStatus::Closed)
+        }
+        "determined" | "amended" | "finalized" | "settled" => Some(MarketStatus::Resolved),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_venue_statuses() {
+        assert_eq!(canonical_status("active"), Some(MarketStatus::Open));
+        assert_eq!(canonical_status("Closed"), Some(MarketStatus::Closed));
+        assert_eq!(canonical_status("finalized"), Some(MarketStatus::Resolved));
+        assert_eq!(canonical_status("settled"), Some(MarketStatus::Resolved));
+        assert_eq!(canonical_status("paused_forever"), None);
+    }
+
+    #[test]
+    fn market_without_quotes_is_rejected_not_defaulted() {
+        let collected = KalshiCollector::new().collect(serde_json::json!({
+            "ticker": "FED-25DEC",
+            "title": "Fed cuts rates",
+            "status": "active",
+            "volume": 10,
+        }));
+
+        assert_eq!(collected.source_id.as_deref(), Some("FED-25DEC"));
+        let issues = collected.market.unwrap_err();
+        assert_eq!(issues[0].check, "missing_price");
+    }
+
+    #[test]
+    fn converts_cents_to_rounded_prices_and_amounts() {
+        let market = |bid: f64, ask: f64| {
+            KalshiCollector::new()
+                .collect(serde_json::json!({
+                    "ticker": "FED-25DEC",
+                    "title": "Fed cuts rates",
+                    "status": "active",
+                    "yes_bid": bid,
+                    "yes_ask": ask,
+                    "volume": 1234.567,
+                    "volume_24h": 10,
+                    "open_interest": 0.005,
+                }))
+                .market
+                .unwrap()
+        };
+
+        let midpoint = market(33.0, 34.0);
+        assert_eq!(midpoint.yes_price, "0.335".parse::<Decimal>().unwrap());
+        assert_eq!(midpoint.no_price, "0.665".parse::<Decimal>().unwrap());
+        assert_eq!(midpoint.volume, "1234.57".parse::<Decimal>().unwrap());
+        assert_eq!(midpoint.volume_24h, Decimal::from(10));
+        // Amounts round half to even at AMOUNT_DP, so half a cent rounds down to 0
+        assert_eq!(midpoint.liquidity, Some(Decimal::ZERO));
+
+        // A midpoint halfway between two PRICE_DP steps rounds to the even one
+        let down = market(33.0001, 33.0);
+        assert_eq!(down.yes_price, "0.330000".parse::<Decimal>().unwrap());
+        let up = market(33.0003, 33.0);
+        assert_eq!(up.yes_price, "0.330002".parse::<Decimal>().unwrap());
+        assert_eq!(up.no_price, "0.669998".parse::<Decimal>().unwrap());
+
+        let thirds = market(66.6666666, 66.6666666);
+        assert_eq!(thirds.yes_price, "0.666667".parse::<Decimal>().unwrap());
+        assert_eq!(thirds.no_price, "0.333333".parse::<Decimal>().unwrap());
+        assert_eq!(thirds.yes_price + thirds.no_price, Decimal::ONE);
+    }
+}
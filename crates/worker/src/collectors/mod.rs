@@ -3,3 +3,55 @@ pub mod kalshi;
 
 pub use polymarket::PolymarketCollector;
 pub use kalshi::KalshiCollector;
+
+use pm_shared::{CreateMarket, MarketSource};
+
+use crate::validate::Issue;
+
+/// One market as returned by a venue, with the result of converting it
+#[derive(Debug)]
+pub struct Collected {
+    /// Venue id, when the payload had one
+    pub source_id: Option<String>,
+    /// The market object exactly as the venue sent it, kept for quarantine
+    pub payload: serde_json::Value,
+    pub market: Result<CreateMarket, Vec<Issue>>,
+}
+
+/// Failure fetching markets from a venue
+#[derive(Debug, thiserror::Error)]
+pub enum CollectError {
+    #[error("{venue} API error: {status}")]
+    Status {
+        venue: MarketSource,
+        status: reqwest::StatusCode,
+    },
+
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+
+    #[error("invalid response body: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+impl CollectError {
+    /// HTTP status returned by the venue, if the request got that far
+    pub fn http_status(&self) -> Option<u16> {
+        match self {
+            CollectError::Status { status, .. } => Some(status.as_u16()),
+            CollectError::Request(e) => e.status().map(|s| s.as_u16()),
+            CollectError::Decode(_) => None,
+        }
+    }
+
+    /// Coarse error kind used for metrics labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CollectError::Status { .. } => "http_status",
+            CollectError::Request(e) if e.is_timeout() => "timeout",
+            CollectError::Request(e) if e.is_decode() => "decode",
+            CollectError::Request(_) => "network",
+            CollectError::Decode(_) => "decode",
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use anyhow::Result;
 use chrono::{DateTime, Utc};
-use pm_shared::CreateMarket;
+use pm_shared::{
+    parse_decimal, CreateMarket, Decimal, MarketSource, MarketStatus, AMOUNT_DP, PRICE_DP,
+};
 use serde::Deserialize;
 
+use super::{CollectError, Collected};
+use crate::taxonomy;
+use crate::validate::Issue;
+
 const POLYMARKET_API: &str = "https://gamma-api.polymarket.com";
 
 #[derive(Debug, Deserialize)]
@@ -18,11 +23,16 @@ struct PolymarketMarket {
     volume_24hr: Option<String>,
     liquidity: Option<String>,
     active: bool,
+    #[serde(default)]
+    closed: bool,
+    #[serde(rename = "umaResolutionStatus")]
+    uma_resolution_status: Option<String>,
     #[serde(rename = "endDate")]
     end_date: Option<String>,
     #[serde(rename = "category")]
     category: Option<String>,
     #[serde(rename = "outcomes")]
+    #[allow(dead_code)]
     outcomes: Vec<String>,
 }
 
@@ -37,7 +47,8 @@ impl PolymarketCollector {
         }
     }
 
-    pub async fn fetch_markets(&self, limit: usize) -> Result<Vec<CreateMarket>> {
+    /// Fetch the raw markets response body
+    pub async fn fetch(&self, limit: usize) -> Result<Vec<u8>, CollectError> {
         let url = format!("{}/markets?limit={}&active=true", POLYMARKET_API, limit);
 
         tracing::info!("Fetching markets from Polymarket: {}", url);
@@ -49,41 +60,71 @@ impl PolymarketCollector {
             .await?;
 
         if !response.status().is_success() {
-            anyhow::bail!("Polymarket API error: {}", response.status());
+            return Err(CollectError::Status {
+                venue: MarketSource::Polymarket,
+                status: response.status(),
+            });
         }
 
-        let markets: Vec<PolymarketMarket> = response.json().await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Convert a markets response body, as fetched or from the archive
+    pub fn parse(&self, body: &[u8]) -> Result<Vec<Collected>, CollectError> {
+        // Kept raw so rejected markets can be quarantined as sent
+        let markets: Vec<serde_json::Value> = serde_json::from_slice(body)?;
 
-        tracing::info!("Fetched {} markets from Polymarket", markets.len());
+        tracing::debug!("Parsed {} Polymarket markets", markets.len());
 
-        let create_markets = markets
+        let collected = markets
             .into_iter()
-            .filter_map(|m| self.convert_market(m))
+            .map(|payload| self.collect(payload))
             .collect();
 
-        Ok(create_markets)
+        Ok(collected)
     }
 
-    fn convert_market(&self, market: PolymarketMarket) -> Option<CreateMarket> {
-        // Parse prices - typically [yes_price, no_price]
-        let yes_price = market.outcome_prices.get(0)
-            .and_then(|p| p.parse::<f32>().ok())
-            .unwrap_or(0.5);
+    fn collect(&self, payload: serde_json::Value) -> Collected {
+        let source_id = payload
+            .get("conditionId")
+            .and_then(|id| id.as_str())
+            .map(str::to_string);
 
-        let no_price = market.outcome_prices.get(1)
-            .and_then(|p| p.parse::<f32>().ok())
-            .unwrap_or(1.0 - yes_price);
+        let market = serde_json::from_value::<PolymarketMarket>(payload.clone())
+            .map_err(|e| vec![Issue::new("malformed", e.to_string())])
+            .and_then(|m| self.convert_market(m));
+
+        Collected {
+            source_id,
+            payload,
+            market,
+        }
+    }
 
-        let volume = market.volume
-            .and_then(|v| v.parse::<f32>().ok())
-            .unwrap_or(0.0);
+    fn convert_market(&self, market: PolymarketMarket) -> Result<CreateMarket, Vec<Issue>> {
+        let status = canonical_status(&market);
+        let mut issues = Vec::new();
 
-        let volume_24h = market.volume_24hr
-            .and_then(|v| v.parse::<f32>().ok())
-            .unwrap_or(0.0);
+        // Parse prices - typically [yes_price, no_price]
+        let yes_price = number("yes_price", market.outcome_prices.first(), PRICE_DP, &mut issues);
+        let no_price = number("no_price", market.outcome_prices.get(1), PRICE_DP, &mut issues);
+        let volume = number("volume", market.volume.as_ref(), AMOUNT_DP, &mut issues);
+        let volume_24h = number("volume_24h", market.volume_24hr.as_ref(), AMOUNT_DP, &mut issues);
+        let liquidity = number("liquidity", market.liquidity.as_ref(), AMOUNT_DP, &mut issues);
+
+        let Some(yes_price) = yes_price else {
+            if market.outcome_prices.is_empty() {
+                issues.push(Issue::new("missing_price", "no outcome prices"));
+            }
+            return Err(issues);
+        };
+        if !issues.is_empty() {
+            return Err(issues);
+        }
 
-        let liquidity = market.liquidity
-            .and_then(|l| l.parse::<f32>().ok());
+        let no_price = no_price.unwrap_or(Decimal::ONE - yes_price);
+        let volume = volume.unwrap_or_default();
+        let volume_24h = volume_24h.unwrap_or_default();
 
         let close_at = market.end_date
             .and_then(|d| DateTime::parse_from_rfc3339(&d).ok())
@@ -91,21 +132,94 @@ impl PolymarketCollector {
 
         let url = format!("https://polymarket.com/event/{}", market.condition_id);
 
-        Some(CreateMarket {
+        let taxonomy = taxonomy::classify(
+            MarketSource::Polymarket,
+            market.category.as_deref(),
+            &market.question,
+        );
+
+        Ok(CreateMarket {
             source_id: market.condition_id,
-            source: "polymarket".to_string(),
+            source: MarketSource::Polymarket,
             title: market.question,
             description: market.description.unwrap_or_default(),
-            category: market.category,
-            tags: None,
+            category: taxonomy.category,
+            source_category: market.category,
+            tags: taxonomy.tags,
             yes_price,
             no_price,
             volume,
             volume_24h,
             liquidity,
-            status: if market.active { "open" } else { "closed" }.to_string(),
+            status,
             close_at,
             url,
         })
     }
 }
+
+/// Parse an optional decimal field, noting one that is present but not a number
+fn number(field: &str, raw: Option<&String>, dp: u32, issues: &mut Vec<Issue>) -> Option<Decimal> {
+    let raw = raw?;
+    match parse_decimal(raw) {
+        Some(value) => Some(value.round_dp(dp)),
+        None => {
+            issues.push(Issue::new(
+                "unparsable_number",
+                format!("{} {:?} is not a number", field, raw),
+            ));
+            None
+        }
+    }
+}
+
+/// Polymarket reports trading state as flags; a market only counts as
+/// resolved once the UMA oracle has settled it
+fn canonical_status(market: &PolymarketMarket) -> MarketStatus {
+    if market.uma_resolution_status.as_deref() == Some("resolved") {
+        MarketStatus::Resolved
+    } else if market.active && !market.closed {
+        MarketStatus::Open
+    } else {
+        MarketStatus::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload() -> serde_json::Value {
+        serde_json::json!({
+            "conditionId": "0xabc",
+            "question": "Will it rain?",
+            "outcomePrices": ["6.5e-1", "0.3500004"],
+            "outcomes": ["Yes", "No"],
+            "volume": "1234.567",
+            "volume24hr": "1e2",
+            "active": true,
+        })
+    }
+
+    #[test]
+    fn parses_prices_in_either_notation_and_rounds_them() {
+        let market = PolymarketCollector::new().collect(payload()).market.unwrap();
+
+        assert_eq!(market.yes_price, "0.65".parse::<Decimal>().unwrap());
+        assert_eq!(market.no_price, "0.35".parse::<Decimal>().unwrap());
+        assert_eq!(market.volume, "1234.57".parse::<Decimal>().unwrap());
+        assert_eq!(market.volume_24h, Decimal::from(100));
+        assert_eq!(market.liquidity, None);
+    }
+
+    #[test]
+    fn rejects_a_number_field_that_does_not_parse() {
+        let mut payload = payload();
+        payload["liquidity"] = serde_json::json!("not a number");
+
+        let issues = PolymarketCollector::new().collect(payload).market.unwrap_err();
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].check, "unparsable_number");
+    }
+}
@@ -1,19 +1,48 @@
+use chrono::Duration;
 use std::env;
 
+use crate::anomaly::AnomalyDetector;
+use crate::archive::ArchiveTarget;
+use crate::partitions::PartitionExpiry;
+use crate::shard::{Shard, ShardStrategy};
+
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub database_url: String,
+    pub database_url: Option<String>,
+    pub auto_migrate: bool,
     pub collection_interval_seconds: u64,
     pub tracked_markets_limit: usize,
     pub enabled: bool,
+    pub max_cycle_seconds: u64,
+    pub shutdown_grace_seconds: u64,
+    pub shard: Shard,
+    pub leader_poll_seconds: u64,
+    pub http_host: String,
+    pub http_port: u16,
+    pub health_stale_intervals: u64,
+    pub retention_enabled: bool,
+    pub raw_retention_days: i64,
+    pub hourly_retention_days: i64,
+    pub retention_interval_seconds: u64,
+    pub partition_months_ahead: u32,
+    pub partition_expiry: PartitionExpiry,
+    pub raw_archive: Option<ArchiveTarget>,
+    pub anomaly_detector: AnomalyDetector,
+    pub market_metrics_enabled: bool,
+    pub market_metrics_interval_seconds: u64,
 }
 
 impl Config {
     pub fn from_env() -> anyhow::Result<Self> {
         dotenvy::dotenv().ok();
 
-        let database_url = env::var("DATABASE_URL")
-            .expect("DATABASE_URL must be set");
+        // Only required when writing to the database (not in --dry-run mode)
+        let database_url = env::var("DATABASE_URL").ok();
+
+        // Apply pending migrations on startup instead of requiring `pm-worker migrate`
+        let auto_migrate = env::var("AUTO_MIGRATE")
+            .unwrap_or_else(|_| "false".to_string())
+            .parse()?;
 
         let collection_interval_seconds = env::var("COLLECTION_INTERVAL_SECONDS")
             .unwrap_or_else(|_| "3600".to_string())
@@ -27,11 +56,131 @@ impl Config {
             .unwrap_or_else(|_| "true".to_string())
             .parse()?;
 
+        // Venue requests still pending this long into a cycle are abandoned;
+        // writes already under way are not cut short by it
+        let max_cycle_seconds = env::var("MAX_CYCLE_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()?;
+
+        // How long in-flight writes may keep running after SIGTERM/SIGINT
+        let shutdown_grace_seconds = env::var("SHUTDOWN_GRACE_SECONDS")
+            .unwrap_or_else(|_| "20".to_string())
+            .parse()?;
+
+        // Replicas with the same shard elect one leader; different shards split the work
+        let shard_strategy: ShardStrategy = env::var("SHARD_STRATEGY")
+            .unwrap_or_else(|_| "source".to_string())
+            .parse()
+            .map_err(anyhow::Error::msg)?;
+
+        let shard = match env::var("WORKER_SHARD") {
+            Ok(spec) => Shard::parse(&spec, shard_strategy).map_err(anyhow::Error::msg)?,
+            Err(_) => Shard::single(),
+        };
+
+        let leader_poll_seconds = env::var("LEADER_POLL_SECONDS")
+            .unwrap_or_else(|_| "15".to_string())
+            .parse()?;
+
+        // Embedded /healthz and /metrics server
+        let http_host = env::var("WORKER_HTTP_HOST")
+            .unwrap_or_else(|_| "0.0.0.0".to_string());
+
+        let http_port = env::var("WORKER_HTTP_PORT")
+            .unwrap_or_else(|_| "9090".to_string())
+            .parse()?;
+
+        // /healthz fails once the last successful cycle is older than this many intervals
+        let health_stale_intervals = env::var("HEALTH_STALE_INTERVALS")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()?;
+
+        // Raw snapshots are rolled up hourly/daily and deleted after RAW_RETENTION_DAYS;
+        // hourly rollups are deleted after HOURLY_RETENTION_DAYS, daily ones are kept
+        let retention_enabled = env::var("RETENTION_ENABLED")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()?;
+
+        let raw_retention_days = env::var("RAW_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()?;
+
+        let hourly_retention_days = env::var("HOURLY_RETENTION_DAYS")
+            .unwrap_or_else(|_| "365".to_string())
+            .parse()?;
+
+        let retention_interval_seconds = env::var("RETENTION_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()?;
+
+        // price_history is partitioned by month; partitions are created this many
+        // months ahead and expired (drop|detach) once past RAW_RETENTION_DAYS
+        let partition_months_ahead = env::var("PARTITION_MONTHS_AHEAD")
+            .unwrap_or_else(|_| "2".to_string())
+            .parse()?;
+
+        let partition_expiry = env::var("PARTITION_EXPIRY")
+            .unwrap_or_else(|_| "drop".to_string())
+            .parse()?;
+
+        // Keep every venue response (table or dir:<path>) for `pm-worker reprocess`
+        let raw_archive = match env::var("RAW_ARCHIVE") {
+            Ok(target) if !target.is_empty() && target != "off" => Some(target.parse()?),
+            _ => None,
+        };
+
+        // Each new snapshot is scored against the market's last ANOMALY_WINDOW
+        // unflagged snapshots; flagged ones are kept out of rollups and history
+        let anomaly_zscore = env::var("ANOMALY_ZSCORE")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()?;
+
+        let anomaly_window = env::var("ANOMALY_WINDOW")
+            .unwrap_or_else(|_| "48".to_string())
+            .parse()?;
+
+        let stale_hours = env::var("STALE_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()?;
+
+        let anomaly_detector = AnomalyDetector {
+            zscore: anomaly_zscore,
+            window: anomaly_window,
+            stale_after: Duration::hours(stale_hours),
+        };
+
+        // Volatility, momentum and volume metrics served by /api/markets/:id/metrics
+        let market_metrics_enabled = env::var("MARKET_METRICS_ENABLED")
+            .unwrap_or_else(|_| "true".to_string())
+            .parse()?;
+
+        let market_metrics_interval_seconds = env::var("MARKET_METRICS_INTERVAL_SECONDS")
+            .unwrap_or_else(|_| "3600".to_string())
+            .parse()?;
+
         Ok(Config {
             database_url,
+            auto_migrate,
             collection_interval_seconds,
             tracked_markets_limit,
             enabled,
+            max_cycle_seconds,
+            shutdown_grace_seconds,
+            shard,
+            leader_poll_seconds,
+            http_host,
+            http_port,
+            health_stale_intervals,
+            retention_enabled,
+            raw_retention_days,
+            hourly_retention_days,
+            retention_interval_seconds,
+            partition_months_ahead,
+            partition_expiry,
+            raw_archive,
+            anomaly_detector,
+            market_metrics_enabled,
+            market_metrics_interval_seconds,
         })
     }
 }
@@ -0,0 +1,567 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use pm_shared::store::{HistoryStore, MarketStore, Snapshot, StoreError};
+use pm_shared::{
+    parse_decimal, CreateMarket, Decimal, ExportFormat, MarketSource, MarketStatus, AMOUNT_DP,
+    PRICE_DP,
+};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::taxonomy;
+
+/// Snapshots written per store call
+const BATCH_SIZE: usize = 1000;
+
+/// Input columns holding each snapshot field. Defaults match the columns
+/// written by `/api/export/history`, so exports re-import unchanged.
+#[derive(Debug, Clone)]
+pub struct ColumnMap {
+    source: String,
+    source_id: String,
+    title: String,
+    recorded_at: String,
+    yes_price: String,
+    no_price: String,
+    volume: String,
+    volume_24h: String,
+    liquidity: String,
+}
+
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self {
+            source: "source".to_string(),
+            source_id: "source_id".to_string(),
+            title: "title".to_string(),
+            recorded_at: "recorded_at".to_string(),
+            yes_price: "yes_price".to_string(),
+            no_price: "no_price".to_string(),
+            volume: "volume".to_string(),
+            volume_24h: "volume_24h".to_string(),
+            liquidity: "liquidity".to_string(),
+        }
+    }
+}
+
+impl ColumnMap {
+    /// Apply `FIELD=COLUMN` overrides on top of the defaults
+    pub fn with_overrides(overrides: &[String]) -> Result<Self> {
+        let mut map = Self::default();
+
+        for entry in overrides {
+            let (field, column) = entry
+                .split_once('=')
+                .with_context(|| format!("Column mapping must be FIELD=COLUMN, got {}", entry))?;
+
+            let slot = match field.trim() {
+                "source" => &mut map.source,
+                "source_id" => &mut map.source_id,
+                "title" => &mut map.title,
+                "recorded_at" => &mut map.recorded_at,
+                "yes_price" => &mut map.yes_price,
+                "no_price" => &mut map.no_price,
+                "volume" => &mut map.volume,
+                "volume_24h" => &mut map.volume_24h,
+                "liquidity" => &mut map.liquidity,
+                other => anyhow::bail!("Unknown snapshot field in column mapping: {}", other),
+            };
+            *slot = column.trim().to_string();
+        }
+
+        Ok(map)
+    }
+}
+
+/// A row that failed validation, by its line in the input file
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    pub line: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub rows_read: u64,
+    /// Rows that passed validation
+    pub valid: u64,
+    pub inserted: u64,
+    /// Valid rows skipped because a snapshot already exists at that time
+    pub duplicates: u64,
+    pub markets_created: u64,
+    pub rejected: Vec<Rejection>,
+    /// Market ids and time span written, for rolling up afterwards
+    pub market_ids: Vec<Uuid>,
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+/// A validated input row, not yet resolved to a market
+#[derive(Debug, Clone, PartialEq)]
+struct ImportRow {
+    source: MarketSource,
+    source_id: String,
+    title: Option<String>,
+    recorded_at: DateTime<Utc>,
+    yes_price: Decimal,
+    no_price: Decimal,
+    volume: Decimal,
+    volume_24h: Decimal,
+    liquidity: Option<Decimal>,
+}
+
+/// Loads historical snapshots from CSV or JSON Lines files.
+///
+/// Markets are resolved by `(source, source_id)` and created when missing;
+/// snapshots that already exist for a market at the same instant are skipped,
+/// so re-running an import is harmless.
+pub struct Importer {
+    markets: Arc<dyn MarketStore>,
+    history: Arc<dyn HistoryStore>,
+    columns: ColumnMap,
+    default_source: Option<MarketSource>,
+    dry_run: bool,
+    resolved: HashMap<(MarketSource, String), Option<Uuid>>,
+}
+
+impl Importer {
+    pub fn new(
+        markets: Arc<dyn MarketStore>,
+        history: Arc<dyn HistoryStore>,
+        columns: ColumnMap,
+        default_source: Option<MarketSource>,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            markets,
+            history,
+            columns,
+            default_source,
+            dry_run,
+            resolved: HashMap::new(),
+        }
+    }
+
+    pub async fn import_file(&mut self, path: &Path, format: ExportFormat) -> Result<ImportReport> {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let records: Box<dyn Iterator<Item = (u64, Result<Record, String>)>> = match format {
+            ExportFormat::Csv => Box::new(csv_records(file)?),
+            ExportFormat::Jsonl => Box::new(jsonl_records(file)),
+            ExportFormat::Parquet => {
+                anyhow::bail!("Parquet files cannot be imported; use CSV or JSON Lines")
+            }
+        };
+
+        let mut report = ImportReport::default();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+        for (line, record) in records {
+            report.rows_read += 1;
+
+            match record.and_then(|r| self.parse_row(&r)) {
+                Ok(row) => {
+                    report.valid += 1;
+                    batch.push(row);
+                }
+                Err(reason) => report.rejected.push(Rejection { line, reason }),
+            }
+
+            if batch.len() >= BATCH_SIZE {
+                self.write_batch(std::mem::take(&mut batch), &mut report).await?;
+            }
+        }
+        self.write_batch(batch, &mut report).await?;
+
+        Ok(report)
+    }
+
+    fn parse_row(&self, record: &Record) -> Result<ImportRow, String> {
+        let c = &self.columns;
+
+        let source = match record.get(&c.source) {
+            Some(s) => s.parse::<MarketSource>()?,
+            None => self
+                .default_source
+                .ok_or_else(|| format!("missing {} (or pass --source)", c.source))?,
+        };
+        let source_id = required(record, &c.source_id)?.to_string();
+        let recorded_at = parse_timestamp(required(record, &c.recorded_at)?)?;
+        if recorded_at > Utc::now() {
+            return Err(format!("{} is in the future", c.recorded_at));
+        }
+
+        let yes_price = probability(record, &c.yes_price)?
+            .ok_or_else(|| format!("missing {}", c.yes_price))?;
+        let no_price = probability(record, &c.no_price)?.unwrap_or(Decimal::ONE - yes_price);
+
+        Ok(ImportRow {
+            source,
+            source_id,
+            title: record.get(&c.title).cloned(),
+            recorded_at,
+            yes_price,
+            no_price,
+            volume: amount(record, &c.volume)?.unwrap_or_default(),
+            volume_24h: amount(record, &c.volume_24h)?.unwrap_or_default(),
+            liquidity: amount(record, &c.liquidity)?,
+        })
+    }
+
+    async fn write_batch(&mut self, rows: Vec<ImportRow>, report: &mut ImportReport) -> Result<()> {
+        let mut snapshots = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let Some(market_id) = self.resolve_market(&row, report).await? else {
+                // Dry run: the market would be created by a real import
+                continue;
+            };
+
+            if !report.market_ids.contains(&market_id) {
+                report.market_ids.push(market_id);
+            }
+            report.span = Some(match report.span {
+                Some((from, to)) => (from.min(row.recorded_at), to.max(row.recorded_at)),
+                None => (row.recorded_at, row.recorded_at),
+            });
+
+            snapshots.push(Snapshot {
+                market_id,
+                yes_price: row.yes_price,
+                no_price: row.no_price,
+                volume: row.volume,
+                volume_24h: row.volume_24h,
+                liquidity: row.liquidity,
+                recorded_at: row.recorded_at,
+            });
+        }
+
+        if self.dry_run || snapshots.is_empty() {
+            return Ok(());
+        }
+
+        let inserted = self.history.import_snapshots(&snapshots).await?;
+        report.inserted += inserted;
+        report.duplicates += snapshots.len() as u64 - inserted;
+
+        Ok(())
+    }
+
+    /// Find the row's market, creating a placeholder from the row if this is a
+    /// real import
+    async fn resolve_market(
+        &mut self,
+        row: &ImportRow,
+        report: &mut ImportReport,
+    ) -> Result<Option<Uuid>> {
+        let key = (row.source, row.source_id.clone());
+        if let Some(id) = self.resolved.get(&key) {
+            return Ok(*id);
+        }
+
+        let id = match self.markets.get_by_source(row.source, &row.source_id).await {
+            Ok(market) => Some(market.id),
+            Err(StoreError::NotFound) if self.dry_run => {
+                report.markets_created += 1;
+                None
+            }
+            Err(StoreError::NotFound) => {
+                let title = row.title.clone().unwrap_or_else(|| row.source_id.clone());
+                let taxonomy = taxonomy::classify(row.source, None, &title);
+                let market = self
+                    .markets
+                    .create_placeholder(CreateMarket {
+                        source_id: row.source_id.clone(),
+                        source: row.source,
+                        title,
+                        description: String::new(),
+                        category: taxonomy.category,
+                        source_category: None,
+                        tags: taxonomy.tags,
+                        yes_price: row.yes_price,
+                        no_price: row.no_price,
+                        volume: row.volume,
+                        volume_24h: row.volume_24h,
+                        liquidity: row.liquidity,
+                        // Stand-ins until a collector sees the market, which
+                        // records no revisions against a placeholder
+                        status: MarketStatus::Closed,
+                        close_at: None,
+                        url: String::new(),
+                    })
+                    .await?;
+                report.markets_created += 1;
+                Some(market.id)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        self.resolved.insert(key, id);
+        Ok(id)
+    }
+}
+
+/// One input row as column name to raw text; blank values are omitted
+type Record = HashMap<String, String>;
+
+fn csv_records(file: File) -> Result<impl Iterator<Item = (u64, Result<Record, String>)>> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(file);
+    let headers = reader.headers().context("Failed to read CSV header")?.clone();
+
+    Ok(reader.into_records().map(move |record| match record {
+        Ok(record) => {
+            let line = record.position().map_or(0, |p| p.line());
+            let fields = headers
+                .iter()
+                .zip(record.iter())
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(column, value)| (column.to_string(), value.to_string()))
+                .collect();
+            (line, Ok(fields))
+        }
+        Err(e) => {
+            let line = e.position().map_or(0, |p| p.line());
+            (line, Err(e.to_string()))
+        }
+    }))
+}
+
+fn jsonl_records(file: File) -> impl Iterator<Item = (u64, Result<Record, String>)> {
+    BufReader::new(file)
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i as u64 + 1, line))
+        .filter(|(_, line)| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .map(|(line_no, line)| {
+            let record = line.map_err(|e| e.to_string()).and_then(|line| {
+                let object: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&line).map_err(|e| format!("invalid JSON: {}", e))?;
+
+                Ok(object
+                    .into_iter()
+                    .filter_map(|(column, value)| match value {
+                        serde_json::Value::Null => None,
+                        serde_json::Value::String(s) if s.trim().is_empty() => None,
+                        serde_json::Value::String(s) => Some((column, s.trim().to_string())),
+                        other => Some((column, other.to_string())),
+                    })
+                    .collect())
+            });
+            (line_no, record)
+        })
+}
+
+fn required<'a>(record: &'a Record, column: &str) -> Result<&'a str, String> {
+    record
+        .get(column)
+        .map(String::as_str)
+        .ok_or_else(|| format!("missing {}", column))
+}
+
+fn number(record: &Record, column: &str) -> Result<Option<Decimal>, String> {
+    match record.get(column) {
+        None => Ok(None),
+        Some(raw) => parse_decimal(raw)
+            .map(Some)
+            .ok_or_else(|| format!("{} is not a number: {}", column, raw)),
+    }
+}
+
+fn probability(record: &Record, column: &str) -> Result<Option<Decimal>, String> {
+    match number(record, column)? {
+        Some(p) if !(Decimal::ZERO..=Decimal::ONE).contains(&p) => {
+            Err(format!("{} must be between 0 and 1, got {}", column, p))
+        }
+        p => Ok(p.map(|p| p.round_dp(PRICE_DP))),
+    }
+}
+
+fn amount(record: &Record, column: &str) -> Result<Option<Decimal>, String> {
+    match number(record, column)? {
+        Some(v) if v.is_sign_negative() && !v.is_zero() => {
+            Err(format!("{} must not be negative, got {}", column, v))
+        }
+        v => Ok(v.map(|v| v.round_dp(AMOUNT_DP))),
+    }
+}
+
+/// RFC 3339, `YYYY-MM-DD HH:MM:SS[.f]` in UTC, or Unix seconds/milliseconds
+fn parse_timestamp(raw: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(ts.with_timezone(&Utc));
+    }
+
+    for format in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(ts) = NaiveDateTime::parse_from_str(raw, format) {
+            return Ok(ts.and_utc());
+        }
+    }
+
+    if let Ok(epoch) = raw.parse::<i64>() {
+        // Anything past 1e11 seconds (year 5138) is taken to be milliseconds
+        let ts = if epoch.abs() >= 100_000_000_000 {
+            Utc.timestamp_millis_opt(epoch).single()
+        } else {
+            Utc.timestamp_opt(epoch, 0).single()
+        };
+        if let Some(ts) = ts {
+            return Ok(ts);
+        }
+    }
+
+    Err(format!("unrecognised timestamp: {}", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::MemoryStore;
+    use pm_shared::Resolution;
+    use std::io::Write;
+
+    fn importer(store: &Arc<MemoryStore>, columns: ColumnMap) -> Importer {
+        Importer::new(store.clone(), store.clone(), columns, None, false)
+    }
+
+    fn write_temp(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", Uuid::new_v4(), name));
+        File::create(&path).unwrap().write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn imports_mapped_csv_idempotently() {
+        let store = Arc::new(MemoryStore::new());
+        let columns = ColumnMap::with_overrides(&[
+            "source_id=ticker".to_string(),
+            "recorded_at=ts".to_string(),
+            "yes_price=yes".to_string(),
+        ])
+        .unwrap();
+        let path = write_temp(
+            "history.csv",
+            "source,ticker,ts,yes\n\
+             kalshi,T1,2024-01-01T00:00:00Z,0.4\n\
+             kalshi,T1,1704070800,0.45\n\
+             kalshi,T1,2024-01-01 02:00:00,1.5\n\
+             venue,T2,2024-01-01T00:00:00Z,0.5\n",
+        );
+
+        let report = importer(&store, columns.clone())
+            .import_file(&path, ExportFormat::Csv)
+            .await
+            .unwrap();
+        assert_eq!(report.rows_read, 4);
+        assert_eq!(report.inserted, 2);
+        assert_eq!(report.markets_created, 1);
+        assert_eq!(
+            report.rejected.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+
+        let market = store.get_by_source(MarketSource::Kalshi, "T1").await.unwrap();
+        let history = store
+            .get_history(market.id, 10, None, Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].no_price, Decimal::new(55, 2));
+
+        // Second run finds the market and skips existing snapshots
+        let report = importer(&store, columns)
+            .import_file(&path, ExportFormat::Csv)
+            .await
+            .unwrap();
+        assert_eq!(report.inserted, 0);
+        assert_eq!(report.duplicates, 2);
+        assert_eq!(report.markets_created, 0);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn first_collection_of_an_imported_market_records_no_revisions() {
+        use crate::anomaly::AnomalyDetector;
+        use crate::recorder::MarketRecorder;
+
+        let store = Arc::new(MemoryStore::new());
+        let path = write_temp(
+            "history.csv",
+            "source,source_id,recorded_at,yes_price\n\
+             kalshi,T1,2024-01-01T00:00:00Z,0.4\n",
+        );
+        importer(&store, ColumnMap::default())
+            .import_file(&path, ExportFormat::Csv)
+            .await
+            .unwrap();
+        let imported = store.get_by_source(MarketSource::Kalshi, "T1").await.unwrap();
+        assert!(imported.placeholder);
+
+        let recorder = MarketRecorder::new(
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            AnomalyDetector::default(),
+        );
+        let collected = recorder
+            .record_market(
+                CreateMarket {
+                    source_id: "T1".to_string(),
+                    source: MarketSource::Kalshi,
+                    title: "Fed cuts rates in March".to_string(),
+                    description: "Resolves on the FOMC statement".to_string(),
+                    category: None,
+                    source_category: None,
+                    tags: None,
+                    yes_price: Decimal::new(45, 2),
+                    no_price: Decimal::new(55, 2),
+                    volume: Decimal::ZERO,
+                    volume_24h: Decimal::ZERO,
+                    liquidity: None,
+                    status: MarketStatus::Open,
+                    close_at: None,
+                    url: "https://kalshi.com/markets/T1".to_string(),
+                },
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(collected.id, imported.id);
+        assert!(!collected.placeholder);
+        assert_eq!(collected.url, "https://kalshi.com/markets/T1");
+        assert!(store.revisions(&[imported.id], None).await.unwrap().is_empty());
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[tokio::test]
+    async fn jsonl_uses_default_source_and_reports_bad_lines() {
+        let store = Arc::new(MemoryStore::new());
+        let path = write_temp(
+            "history.jsonl",
+            "{\"source_id\":\"0xabc\",\"recorded_at\":\"2024-01-01T00:00:00Z\",\"yes_price\":0.2,\"liquidity\":null}\n\
+             \n\
+             not json\n",
+        );
+
+        let mut importer = Importer::new(
+            store.clone(),
+            store.clone(),
+            ColumnMap::default(),
+            Some(MarketSource::Polymarket),
+            false,
+        );
+        let report = importer.import_file(&path, ExportFormat::Jsonl).await.unwrap();
+
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(report.rejected[0].line, 3);
+        assert!(store.get_by_source(MarketSource::Polymarket, "0xabc").await.is_ok());
+
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::Result;
+use sqlx::{Connection, PgConnection};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::shard::Shard;
+
+/// First key of the two-key advisory lock; the second key is the shard index
+const LOCK_NAMESPACE: i32 = 0x706d_7768; // "pmwh"
+
+/// Leader election through a session-scoped Postgres advisory lock.
+///
+/// The lock lives on a dedicated connection outside the pool, so it is
+/// released exactly when that session ends, whether the leader exits
+/// cleanly, crashes, or loses its network.
+pub struct LeaderElection {
+    database_url: String,
+    shard: Shard,
+    poll_interval: Duration,
+}
+
+impl LeaderElection {
+    pub fn new(database_url: String, shard: Shard, poll_interval: Duration) -> Self {
+        Self {
+            database_url,
+            shard,
+            poll_interval,
+        }
+    }
+
+    /// Try once to become leader, returning the lock-holding connection on success
+    pub async fn try_acquire(&self) -> Result<Option<PgConnection>> {
+        let mut conn = PgConnection::connect(&self.database_url).await?;
+
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1, $2)")
+            .bind(LOCK_NAMESPACE)
+            .bind(self.shard.index as i32)
+            .fetch_one(&mut conn)
+            .await?;
+
+        if acquired {
+            Ok(Some(conn))
+        } else {
+            conn.close().await.ok();
+            Ok(None)
+        }
+    }
+
+    /// Stand by until this replica becomes leader. Returns `None` on shutdown.
+    pub async fn wait_for_leadership(&self, shutdown: &CancellationToken) -> Option<PgConnection> {
+        let mut announced = false;
+
+        loop {
+            match self.try_acquire().await {
+                Ok(Some(conn)) => {
+                    tracing::info!("Acquired leadership for shard {}", self.shard);
+                    return Some(conn);
+                }
+                Ok(None) if !announced => {
+                    tracing::info!(
+                        "Another worker leads shard {}, standing by",
+                        self.shard
+                    );
+                    announced = true;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::error!("Leader election failed: {}", e),
+            }
+
+            tokio::select! {
+                _ = shutdown.cancelled() => return None,
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+        }
+    }
+
+    /// Keep the lock session alive, cancelling `leadership` if it is lost.
+    /// A heartbeat unanswered within the poll interval counts as lost, since
+    /// a session on a dead network can hang instead of failing.
+    ///
+    /// Once `leadership` is cancelled the lock is released and the
+    /// connection closed so a standby can take over immediately.
+    pub fn hold(&self, mut conn: PgConnection, leadership: CancellationToken) -> JoinHandle<()> {
+        let shard = self.shard;
+        let poll_interval = self.poll_interval;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = leadership.cancelled() => break,
+                    _ = tokio::time::sleep(poll_interval) => {
+                        let heartbeat = sqlx::query("SELECT 1").execute(&mut conn);
+                        let lost = match tokio::time::timeout(poll_interval, heartbeat).await {
+                            Ok(Ok(_)) => None,
+                            Ok(Err(e)) => Some(e.to_string()),
+                            Err(_) => Some(format!("no answer within {:?}", poll_interval)),
+                        };
+                        if let Some(reason) = lost {
+                            tracing::error!("Lost leader session for shard {}: {}", shard, reason);
+                            leadership.cancel();
+                            return;
+                        }
+                    }
+                }
+            }
+
+            let _ = sqlx::query("SELECT pg_advisory_unlock($1, $2)")
+                .bind(LOCK_NAMESPACE)
+                .bind(shard.index as i32)
+                .execute(&mut conn)
+                .await;
+            conn.close().await.ok();
+            tracing::info!("Released leadership for shard {}", shard);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Postgres stand-in that completes the startup handshake and then
+    /// never answers a query
+    async fn hanging_server() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut len = [0u8; 4];
+            socket.read_exact(&mut len).await.unwrap();
+            let mut startup = vec![0u8; u32::from_be_bytes(len) as usize - 4];
+            socket.read_exact(&mut startup).await.unwrap();
+            // AuthenticationOk, then ReadyForQuery while idle
+            let ready = [b'R', 0, 0, 0, 8, 0, 0, 0, 0, b'Z', 0, 0, 0, 5, b'I'];
+            socket.write_all(&ready).await.unwrap();
+            let mut queries = Vec::new();
+            socket.read_to_end(&mut queries).await.ok();
+        });
+        format!("postgres://pm@{}/pm?sslmode=disable", addr)
+    }
+
+    #[tokio::test]
+    async fn unanswered_heartbeat_loses_leadership() {
+        let url = hanging_server().await;
+        let conn = PgConnection::connect(&url).await.unwrap();
+        let election = LeaderElection::new(url, Shard::single(), Duration::from_millis(50));
+        let leadership = CancellationToken::new();
+
+        let held = election.hold(conn, leadership.clone());
+
+        tokio::time::timeout(Duration::from_secs(5), leadership.cancelled())
+            .await
+            .expect("leadership kept through an unanswered heartbeat");
+        held.await.unwrap();
+    }
+}
@@ -1,30 +1,245 @@
+mod anomaly;
+mod archive;
 mod collectors;
 mod config;
+mod import;
+mod leader;
+mod market_metrics;
+mod metrics;
+mod partitions;
 mod recorder;
+mod reprocess;
+mod retention;
+mod runs;
 mod scheduler;
+mod server;
+mod shard;
+mod shutdown;
+mod taxonomy;
+mod validate;
 
-use anyhow::Result;
-use sqlx::postgres::PgPoolOptions;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Parser, Subcommand};
+use pm_shared::store::Database;
+use pm_shared::{migrations, ExportFormat, MarketSource};
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+use archive::{ArchiveTarget, RawArchive};
 use config::Config;
+use import::{ColumnMap, Importer};
+use leader::LeaderElection;
+use market_metrics::MetricsJob;
+use metrics::Metrics;
+use partitions::PartitionManager;
+use recorder::MarketRecorder;
+use reprocess::Reprocessor;
+use retention::RetentionJob;
+use runs::RunLog;
 use scheduler::Scheduler;
+use shard::Shard;
+
+#[derive(Parser)]
+#[command(name = "pm-worker")]
+#[command(about = "Prediction Market History Tracker collection worker", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Fetch and convert markets, printing them as JSON lines instead of writing to the database
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Only collect from this source (polymarket, kalshi)
+    #[arg(long, global = true)]
+    source: Option<MarketSource>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Collect on a fixed interval until stopped (default)
+    Run,
+
+    /// Run a single collection cycle and exit, non-zero if any source failed
+    Once,
+
+    /// Apply pending database migrations and exit
+    Migrate,
+
+    /// Roll up and expire old price history and maintain its partitions once, then exit
+    Retention,
+
+    /// Recompute volatility, momentum and volume metrics for every market once, then exit
+    Metrics,
+
+    /// Re-derive canonical categories and title tags for every stored market, then exit.
+    ///
+    /// With --dry-run, markets that would change are counted but not written.
+    Taxonomy,
+
+    /// Import historical snapshots from a CSV or JSON Lines file.
+    ///
+    /// With --dry-run, rows are validated and reported but nothing is written.
+    /// --source sets the venue for files without a source column.
+    Import {
+        /// File to read
+        path: PathBuf,
+
+        /// csv or jsonl; inferred from the file extension when omitted
+        #[arg(long)]
+        format: Option<ExportFormat>,
+
+        /// Read a snapshot field from a differently named column (repeatable),
+        /// e.g. --map source_id=ticker --map recorded_at=timestamp
+        #[arg(long = "map", value_name = "FIELD=COLUMN")]
+        columns: Vec<String>,
+
+        /// Write every rejected row's line number and reason to this CSV file
+        #[arg(long)]
+        rejects: Option<PathBuf>,
+    },
+
+    /// Re-convert archived venue responses to rebuild markets and price history.
+    ///
+    /// Reads the archive named by RAW_ARCHIVE. With --dry-run, responses are
+    /// converted and validated but nothing is written.
+    Reprocess {
+        /// Only responses fetched at or after this time (RFC 3339)
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+
+        /// Only responses fetched before this time (RFC 3339)
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+    },
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
+    let cli = Cli::parse();
+
+    // Initialize tracing (stderr keeps stdout clean for --dry-run output)
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "pm_worker=info".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
     // Load configuration
     let config = Config::from_env()?;
+    let command = cli.command.unwrap_or(Commands::Run);
+
+    if matches!(command, Commands::Migrate) {
+        let db = connect(&config).await?;
+        migrations::run(&db).await?;
+        tracing::info!(
+            "Database migrated to version {}",
+            migrations::database_version(&db).await?
+        );
+        return Ok(());
+    }
+
+    if matches!(command, Commands::Retention) {
+        let db = connect(&config).await?;
+        migrations::check_version(&db).await?;
+        let pool = db
+            .postgres()
+            .context("Retention and partitioning require PostgreSQL")?
+            .clone();
+        if config.retention_enabled {
+            retention_job(&config, pool.clone()).run_once().await?;
+        }
+        partition_manager(&config, pool).run_once().await?;
+        return Ok(());
+    }
 
-    if !config.enabled {
+    if matches!(command, Commands::Metrics) {
+        let db = connect(&config).await?;
+        migrations::check_version(&db).await?;
+        let written = metrics_job(&config, &db).run_once().await?;
+        println!("Recomputed metrics for {} markets", written);
+        return Ok(());
+    }
+
+    if matches!(command, Commands::Taxonomy) {
+        let db = connect(&config).await?;
+        migrations::check_version(&db).await?;
+        let changed = taxonomy::backfill(db.markets(), cli.dry_run).await?;
+        if cli.dry_run {
+            println!("Would re-tag {} markets", changed);
+        } else {
+            println!("Re-tagged {} markets", changed);
+        }
+        return Ok(());
+    }
+
+    if let Commands::Import { path, format, columns, rejects } = &command {
+        let format = format
+            .or_else(|| ExportFormat::from_path(path))
+            .context("Cannot infer the file format from its extension; pass --format csv|jsonl")?;
+        let db = connect(&config).await?;
+        migrations::check_version(&db).await?;
+
+        let mut importer = Importer::new(
+            db.markets(),
+            db.history(),
+            ColumnMap::with_overrides(columns)?,
+            cli.source,
+            cli.dry_run,
+        );
+        let report = importer.import_file(path, format).await?;
+
+        // Backfilled hours before the rollup watermark are not picked up by the regular pass
+        if let (Some(pool), Some((from, to))) = (db.postgres(), report.span) {
+            if config.retention_enabled && !cli.dry_run {
+                let (hourly, daily) = retention_job(&config, pool.clone())
+                    .rollup_backfill(&report.market_ids, from, to)
+                    .await?;
+                tracing::info!("Rolled up {} hourly and {} daily backfilled buckets", hourly, daily);
+            }
+        }
+
+        print_import_report(&report, cli.dry_run);
+        if let Some(rejects) = rejects {
+            write_rejects(rejects, &report.rejected)?;
+        }
+        return Ok(());
+    }
+
+    if let Commands::Reprocess { from, to } = &command {
+        let target = config
+            .raw_archive
+            .clone()
+            .context("RAW_ARCHIVE must name the archive to reprocess (table or dir:<path>)")?;
+        let db = connect(&config).await?;
+        migrations::check_version(&db).await?;
+
+        let archive = raw_archive(target, &db);
+        let reprocessor = Reprocessor::new(db.markets(), db.history(), cli.dry_run);
+        let report = reprocessor.run(&archive, cli.source, *from, *to).await?;
+
+        // Rewritten hours may already be rolled up
+        if let (Some(pool), Some((from, to))) = (db.postgres(), report.span) {
+            if config.retention_enabled && !cli.dry_run {
+                let (hourly, daily) = retention_job(&config, pool.clone())
+                    .rollup_reprocessed(&report.market_ids, from, to)
+                    .await?;
+                tracing::info!("Rolled up {} hourly and {} daily reprocessed buckets", hourly, daily);
+            }
+        }
+
+        print_reprocess_report(&report, cli.dry_run);
+        return Ok(());
+    }
+
+    if matches!(command, Commands::Run) && !config.enabled {
         tracing::info!("Worker is disabled via WORKER_ENABLED=false");
         return Ok(());
     }
@@ -33,17 +248,381 @@ async fn main() -> Result<()> {
     tracing::info!("Collection interval: {}s", config.collection_interval_seconds);
     tracing::info!("Tracked markets limit: {}", config.tracked_markets_limit);
 
-    // Create database connection pool
-    let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&config.database_url)
-        .await?;
+    let sources = collection_sources(cli.source, &config.shard)?;
+
+    if config.shard.is_sharded() {
+        tracing::info!(
+            "Shard {} ({:?} strategy), sources: {:?}",
+            config.shard,
+            config.shard.strategy,
+            sources
+        );
+    }
+
+    if sources.is_empty() {
+        tracing::warn!("Shard {} has no sources assigned, nothing to do", config.shard);
+        return Ok(());
+    }
+
+    let (recorder, run_log, archive, election, maintenance) = if cli.dry_run {
+        tracing::info!("Dry run: markets will be printed, not recorded");
+        (None, None, None, None, None)
+    } else {
+        let db = connect(&config).await?;
+
+        if config.auto_migrate {
+            migrations::run(&db).await?;
+        }
+
+        let pending = migrations::check_version(&db).await?;
+        if pending > 0 {
+            tracing::warn!(
+                "Database is {} migration(s) behind; run `pm-worker migrate`",
+                pending
+            );
+        }
+
+        let metrics_job = config
+            .market_metrics_enabled
+            .then(|| metrics_job(&config, &db));
+
+        // Leader election, retention and partitioning need Postgres; a SQLite
+        // database is local to a single worker and keeps raw history only
+        let (election, maintenance) = match db.postgres() {
+            Some(pool) => {
+                let election = LeaderElection::new(
+                    config.database_url.clone().unwrap_or_default(),
+                    config.shard,
+                    Duration::from_secs(config.leader_poll_seconds),
+                );
+
+                // Retention and partitioning are table-wide, so only the leader of shard 0 runs them
+                let maintenance = (config.shard.index == 0).then(|| Maintenance {
+                    retention: config
+                        .retention_enabled
+                        .then(|| retention_job(&config, pool.clone())),
+                    partitions: Some(partition_manager(&config, pool.clone())),
+                    metrics: metrics_job,
+                });
+
+                (Some(election), maintenance)
+            }
+            None => {
+                tracing::info!("SQLite database: running without leader election or retention");
+                let maintenance = Maintenance {
+                    retention: None,
+                    partitions: None,
+                    metrics: metrics_job,
+                };
+                (None, Some(maintenance))
+            }
+        };
+
+        let archive = config.raw_archive.clone().map(|target| raw_archive(target, &db));
+
+        (
+            Some(MarketRecorder::new(
+                db.markets(),
+                db.history(),
+                db.quarantine(),
+                db.anomalies(),
+                config.anomaly_detector.clone(),
+            )),
+            Some(RunLog::new(db.runs())),
+            archive,
+            election,
+            maintenance,
+        )
+    };
+
+    let shutdown_token = CancellationToken::new();
+    shutdown::listen_for_signals(shutdown_token.clone());
+
+    let metrics = Arc::new(Metrics::new()?);
+
+    if matches!(command, Commands::Run) {
+        let addr = format!("{}:{}", config.http_host, config.http_port);
+        let max_staleness =
+            (config.collection_interval_seconds * config.health_stale_intervals) as i64;
+        let (metrics, shutdown) = (metrics.clone(), shutdown_token.clone());
+
+        tokio::spawn(async move {
+            if let Err(e) = server::serve(addr, metrics, max_staleness, shutdown).await {
+                tracing::error!("Health server failed: {}", e);
+            }
+        });
+    }
+
+    let scheduler = Scheduler::new(
+        config,
+        recorder,
+        run_log,
+        archive,
+        sources,
+        shutdown_token.clone(),
+        metrics.clone(),
+    );
+
+    match command {
+        Commands::Run => match &election {
+            Some(election) => {
+                run_as_leader(
+                    &scheduler,
+                    maintenance.as_ref(),
+                    election,
+                    &metrics,
+                    &shutdown_token,
+                )
+                .await?
+            }
+            None => {
+                metrics.set_leader(true);
+                let maintenance_loop = async {
+                    if let Some(maintenance) = &maintenance {
+                        maintenance.run(&shutdown_token).await;
+                    }
+                };
+                let (result, ()) = tokio::join!(scheduler.run(&shutdown_token), maintenance_loop);
+                result?
+            }
+        },
+        Commands::Once => {
+            // Held until exit so a concurrent `once` or `run` skips this shard
+            let _lock = match &election {
+                Some(election) => match election.try_acquire().await? {
+                    Some(conn) => Some(conn),
+                    None => {
+                        tracing::info!("Another worker holds the lock, skipping cycle");
+                        return Ok(());
+                    }
+                },
+                None => None,
+            };
+
+            let report = scheduler.run_once(&shutdown_token).await;
+            for source in &report.sources {
+                match &source.error {
+                    None => tracing::info!(
+                        "{}: fetched {}, quarantined {}, recorded {}",
+                        source.source,
+                        source.fetched,
+                        source.quarantined,
+                        source.recorded
+                    ),
+                    Some(e) => tracing::error!("{}: failed: {}", source.source, e),
+                }
+            }
+            if !report.is_success() {
+                std::process::exit(1);
+            }
+        }
+        Commands::Migrate
+        | Commands::Retention
+        | Commands::Metrics
+        | Commands::Taxonomy
+        | Commands::Import { .. }
+        | Commands::Reprocess { .. } => {
+            unreachable!("handled before collection setup")
+        }
+    }
+
+    Ok(())
+}
+
+/// Sources this worker collects: the one named by `--source`, or those
+/// assigned to its shard.
+///
+/// `--source` is refused on a sharded worker: the leader lock is keyed on the
+/// shard, so collecting another shard's source under it would race that
+/// shard's leader.
+fn collection_sources(source: Option<MarketSource>, shard: &Shard) -> Result<Vec<MarketSource>> {
+    match source {
+        Some(_) if shard.is_sharded() => anyhow::bail!(
+            "--source can't be combined with sharding (shard {}); unset WORKER_SHARD to use it",
+            shard
+        ),
+        Some(source) => Ok(vec![source]),
+        None => Ok(shard.sources(&MarketSource::ALL)),
+    }
+}
+
+async fn connect(config: &Config) -> Result<Database> {
+    let database_url = config
+        .database_url
+        .as_deref()
+        .context("DATABASE_URL must be set")?;
+
+    // Postgres or SQLite, depending on the URL scheme
+    let db = Database::connect(database_url, 5).await?;
 
     tracing::info!("Connected to database");
+    Ok(db)
+}
+
+fn raw_archive(target: ArchiveTarget, db: &Database) -> RawArchive {
+    match target {
+        ArchiveTarget::Table => RawArchive::Table(db.archive()),
+        ArchiveTarget::Directory(dir) => RawArchive::Directory(dir),
+    }
+}
+
+fn retention_job(config: &Config, pool: PgPool) -> RetentionJob {
+    RetentionJob::new(
+        pool,
+        config.raw_retention_days,
+        config.hourly_retention_days,
+        Duration::from_secs(config.retention_interval_seconds),
+    )
+}
+
+fn metrics_job(config: &Config, db: &Database) -> MetricsJob {
+    MetricsJob::new(
+        db.markets(),
+        db.history(),
+        db.metrics(),
+        Duration::from_secs(config.market_metrics_interval_seconds),
+    )
+}
+
+fn partition_manager(config: &Config, pool: PgPool) -> PartitionManager {
+    PartitionManager::new(
+        pool,
+        config.partition_months_ahead,
+        config.retention_enabled.then_some(config.raw_retention_days),
+        config.partition_expiry,
+        Duration::from_secs(config.retention_interval_seconds),
+    )
+}
+
+fn print_import_report(report: &import::ImportReport, dry_run: bool) {
+    println!("Rows read:        {}", report.rows_read);
+    println!("Rejected:         {}", report.rejected.len());
+    if dry_run {
+        println!("Valid:            {}", report.valid);
+        println!("Markets to create: {}", report.markets_created);
+    } else {
+        println!("Inserted:         {}", report.inserted);
+        println!("Already present:  {}", report.duplicates);
+        println!("Markets created:  {}", report.markets_created);
+    }
+
+    const SHOWN: usize = 20;
+    for rejection in report.rejected.iter().take(SHOWN) {
+        println!("  line {}: {}", rejection.line, rejection.reason);
+    }
+    if report.rejected.len() > SHOWN {
+        println!("  ... and {} more", report.rejected.len() - SHOWN);
+    }
+}
+
+fn print_reprocess_report(report: &reprocess::ReprocessReport, dry_run: bool) {
+    println!("Responses read:    {}", report.payloads);
+    println!("Unreadable:        {}", report.unreadable);
+    println!("Valid markets:     {}", report.valid);
+    println!("Quarantined:       {}", report.quarantined);
+    if !dry_run {
+        println!("Snapshots written: {}", report.snapshots_written);
+        println!("Markets rebuilt:   {}", report.markets_rebuilt);
+    }
+    if let Some((first, last)) = report.span {
+        println!("Fetched between:   {} and {}", first, last);
+    }
+}
+
+fn write_rejects(path: &Path, rejected: &[import::Rejection]) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    writer.write_record(["line", "reason"])?;
+    for rejection in rejected {
+        writer.write_record([rejection.line.to_string(), rejection.reason.clone()])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Table-wide housekeeping run alongside collection by the leader of shard 0
+/// (on SQLite, by the only worker)
+struct Maintenance {
+    retention: Option<RetentionJob>,
+    partitions: Option<PartitionManager>,
+    metrics: Option<MetricsJob>,
+}
+
+impl Maintenance {
+    async fn run(&self, stop: &CancellationToken) {
+        let retention_loop = async {
+            if let Some(retention) = &self.retention {
+                retention.run(stop).await;
+            }
+        };
+        let partitions_loop = async {
+            if let Some(partitions) = &self.partitions {
+                partitions.run(stop).await;
+            }
+        };
+        let metrics_loop = async {
+            if let Some(metrics) = &self.metrics {
+                metrics.run(stop).await;
+            }
+        };
+        tokio::join!(retention_loop, partitions_loop, metrics_loop);
+    }
+}
+
+/// Alternate between standby and leading until shutdown
+async fn run_as_leader(
+    scheduler: &Scheduler,
+    maintenance: Option<&Maintenance>,
+    election: &LeaderElection,
+    metrics: &Metrics,
+    shutdown: &CancellationToken,
+) -> Result<()> {
+    while let Some(conn) = election.wait_for_leadership(shutdown).await {
+        let leadership = shutdown.child_token();
+        let heartbeat = election.hold(conn, leadership.clone());
+        metrics.set_leader(true);
+
+        let maintenance_loop = async {
+            if let Some(maintenance) = maintenance {
+                maintenance.run(&leadership).await;
+            }
+        };
+        let (result, ()) = tokio::join!(scheduler.run(&leadership), maintenance_loop);
+
+        metrics.set_leader(false);
+        leadership.cancel();
+        heartbeat.await.ok();
+        result?;
 
-    // Create and run scheduler
-    let scheduler = Scheduler::new(config, pool);
-    scheduler.run().await?;
+        if shutdown.is_cancelled() {
+            break;
+        }
+
+        tracing::warn!("Leadership lost, returning to standby");
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shard::ShardStrategy;
+
+    #[test]
+    fn source_flag_narrows_an_unsharded_worker_only() {
+        let shard = Shard::parse("1/2", ShardStrategy::Source).unwrap();
+
+        assert_eq!(
+            collection_sources(None, &Shard::single()).unwrap(),
+            MarketSource::ALL.to_vec()
+        );
+        assert_eq!(
+            collection_sources(Some(MarketSource::Kalshi), &Shard::single()).unwrap(),
+            vec![MarketSource::Kalshi]
+        );
+        assert_eq!(collection_sources(None, &shard).unwrap(), vec![MarketSource::ALL[1]]);
+        // Shard 1's lock would cover collecting shard 0's source
+        assert!(collection_sources(Some(MarketSource::ALL[0]), &shard).is_err());
+    }
+}
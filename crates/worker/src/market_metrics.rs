@@ -0,0 +1,151 @@
+use anyhow::Result;
+use chrono::Utc;
+use pm_shared::metrics;
+use pm_shared::store::{
+    HistoryStore, MarketFilter, MarketSort, MarketStore, MetricsStore, SortOrder,
+};
+use pm_shared::Resolution;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// Markets loaded per page
+const PAGE_SIZE: i64 = 500;
+
+/// Snapshots read per market: 30 days of hourly closes, plus the raw
+/// snapshots of the hours not rolled up yet
+const HISTORY_LIMIT: i64 = 10_000;
+
+/// Recomputes volatility, momentum and volume metrics for every market from
+/// its last 30 days of hourly history, leaving flagged anomalies out
+pub struct MetricsJob {
+    markets: Arc<dyn MarketStore>,
+    history: Arc<dyn HistoryStore>,
+    metrics: Arc<dyn MetricsStore>,
+    interval: Duration,
+}
+
+impl MetricsJob {
+    pub fn new(
+        markets: Arc<dyn MarketStore>,
+        history: Arc<dyn HistoryStore>,
+        metrics: Arc<dyn MetricsStore>,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            markets,
+            history,
+            metrics,
+            interval,
+        }
+    }
+
+    /// Recompute metrics on the configured interval until `stop` is cancelled
+    pub async fn run(&self, stop: &CancellationToken) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            tokio::select! {
+                _ = stop.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.run_once().await {
+                tracing::error!("Metrics pass failed: {}", e);
+            }
+        }
+    }
+
+    /// Recompute every market's metrics, returning how many were written
+    pub async fn run_once(&self) -> Result<usize> {
+        let hours = metrics::LOOKBACK.num_hours();
+        let mut offset = 0;
+        let mut written = 0;
+        let everything = MarketFilter::default();
+
+        loop {
+            let page = self
+                .markets
+                .list(PAGE_SIZE, offset, MarketSort::CreatedAt, SortOrder::Asc, &everything)
+                .await?;
+
+            let now = Utc::now();
+            let mut computed = Vec::with_capacity(page.len());
+            for market in &page {
+                let history = self
+                    .history
+                    .get_history(market.id, HISTORY_LIMIT, Some(hours), Resolution::Hourly, false)
+                    .await?;
+                computed.push(metrics::compute(market.id, &history, now));
+            }
+
+            self.metrics.save_metrics(&computed).await?;
+            written += computed.len();
+
+            if (page.len() as i64) < PAGE_SIZE {
+                break;
+            }
+            offset += PAGE_SIZE;
+        }
+
+        tracing::info!("Metrics: recomputed {} markets", written);
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use pm_shared::store::MemoryStore;
+    use pm_shared::{CreateMarket, Decimal, MarketSource, MarketStatus};
+
+    #[tokio::test]
+    async fn computes_metrics_for_every_market() {
+        let store = Arc::new(MemoryStore::new());
+        let market = store
+            .upsert(CreateMarket {
+                source_id: "m1".into(),
+                source: MarketSource::Polymarket,
+                title: "Test".into(),
+                description: String::new(),
+                category: None,
+                source_category: None,
+                tags: None,
+                yes_price: Decimal::new(5, 1),
+                no_price: Decimal::new(5, 1),
+                volume: Decimal::from(100),
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                status: MarketStatus::Open,
+                close_at: None,
+                url: String::new(),
+            })
+            .await
+            .unwrap();
+
+        let now = Utc::now();
+        for h in (0..30).rev() {
+            let mut at = market.clone();
+            at.yes_price = Decimal::new(40 + h, 2);
+            store
+                .record_snapshot(&at, now - ChronoDuration::hours(h))
+                .await
+                .unwrap();
+        }
+
+        let job = MetricsJob::new(
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            Duration::from_secs(3600),
+        );
+        assert_eq!(job.run_once().await.unwrap(), 1);
+
+        let metrics = store.metrics(market.id).await.unwrap();
+        assert_eq!(metrics.samples, 30);
+        assert_eq!(metrics.momentum_24h, Some(Decimal::new(-24, 2)));
+        assert!(metrics.volatility_24h.unwrap() > 0.0);
+    }
+}
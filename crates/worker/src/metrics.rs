@@ -0,0 +1,116 @@
+use chrono::Utc;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Collection metrics exported at `/metrics`, plus the state `/healthz` needs
+pub struct Metrics {
+    registry: Registry,
+    pub markets_fetched: IntCounterVec,
+    pub markets_recorded: IntCounterVec,
+    pub markets_quarantined: IntCounterVec,
+    pub fetch_duration: HistogramVec,
+    pub errors: IntCounterVec,
+    pub cycle_duration: Histogram,
+    pub leader: IntGauge,
+    last_success: IntGauge,
+    /// Unix timestamp health is measured from until the first successful cycle
+    baseline: AtomicI64,
+}
+
+impl Metrics {
+    pub fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("pm_worker".to_string()), None)?;
+
+        let markets_fetched = IntCounterVec::new(
+            Opts::new("markets_fetched_total", "Markets fetched from a venue"),
+            &["source"],
+        )?;
+        let markets_recorded = IntCounterVec::new(
+            Opts::new("markets_recorded_total", "Markets written to the database"),
+            &["source"],
+        )?;
+        let markets_quarantined = IntCounterVec::new(
+            Opts::new(
+                "markets_quarantined_total",
+                "Markets that failed validation and were quarantined",
+            ),
+            &["source"],
+        )?;
+        let fetch_duration = HistogramVec::new(
+            HistogramOpts::new("fetch_duration_seconds", "Venue API fetch latency")
+                .buckets(vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["source"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("errors_total", "Collection errors by source and kind"),
+            &["source", "kind"],
+        )?;
+        let cycle_duration = Histogram::with_opts(
+            HistogramOpts::new("cycle_duration_seconds", "Duration of a collection cycle")
+                .buckets(vec![1.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0]),
+        )?;
+        let leader = IntGauge::new("leader", "1 while this replica holds its shard lock")?;
+        let last_success = IntGauge::new(
+            "last_success_timestamp_seconds",
+            "Unix time of the last fully successful cycle",
+        )?;
+
+        registry.register(Box::new(markets_fetched.clone()))?;
+        registry.register(Box::new(markets_recorded.clone()))?;
+        registry.register(Box::new(markets_quarantined.clone()))?;
+        registry.register(Box::new(fetch_duration.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(cycle_duration.clone()))?;
+        registry.register(Box::new(leader.clone()))?;
+        registry.register(Box::new(last_success.clone()))?;
+
+        Ok(Self {
+            registry,
+            markets_fetched,
+            markets_recorded,
+            markets_quarantined,
+            fetch_duration,
+            errors,
+            cycle_duration,
+            leader,
+            last_success,
+            baseline: AtomicI64::new(Utc::now().timestamp()),
+        })
+    }
+
+    pub fn record_error(&self, source: &str, kind: &str) {
+        self.errors.with_label_values(&[source, kind]).inc();
+    }
+
+    /// Mark this replica as leader (or not), restarting the staleness clock on promotion
+    pub fn set_leader(&self, leader: bool) {
+        if leader {
+            self.baseline.store(Utc::now().timestamp(), Ordering::Relaxed);
+            self.last_success.set(0);
+        }
+        self.leader.set(leader as i64);
+    }
+
+    pub fn mark_success(&self) {
+        self.last_success.set(Utc::now().timestamp());
+    }
+
+    /// Seconds since the last successful cycle, or since promotion if none yet
+    pub fn seconds_since_success(&self) -> i64 {
+        let last = match self.last_success.get() {
+            0 => self.baseline.load(Ordering::Relaxed),
+            ts => ts,
+        };
+        Utc::now().timestamp() - last
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn encode(&self) -> prometheus::Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
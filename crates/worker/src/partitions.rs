@@ -0,0 +1,284 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// What happens to a monthly partition once it falls out of the raw window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionExpiry {
+    /// Drop the partition and its rows
+    Drop,
+    /// Detach it into a standalone table left for archiving
+    Detach,
+}
+
+impl FromStr for PartitionExpiry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "drop" => Ok(PartitionExpiry::Drop),
+            "detach" => Ok(PartitionExpiry::Detach),
+            _ => bail!("Invalid partition expiry '{}', expected drop or detach", s),
+        }
+    }
+}
+
+/// Partitions touched by one maintenance pass
+#[derive(Debug, Default)]
+pub struct PartitionReport {
+    pub created: Vec<String>,
+    pub expired: Vec<String>,
+    /// Upcoming months whose partition table exists but is detached, left as is
+    pub detached: Vec<String>,
+}
+
+/// Maintains the monthly partitions of `price_history`.
+///
+/// Creates partitions for the current month and `months_ahead` after it so
+/// inserts never land in the default partition, and expires partitions
+/// whose whole month is past the raw retention window and already rolled
+/// up into `price_history_hourly`.
+pub struct PartitionManager {
+    pool: PgPool,
+    months_ahead: u32,
+    /// `None` keeps every partition (retention disabled)
+    raw_retention_days: Option<i64>,
+    expiry: PartitionExpiry,
+    interval: Duration,
+}
+
+impl PartitionManager {
+    pub fn new(
+        pool: PgPool,
+        months_ahead: u32,
+        raw_retention_days: Option<i64>,
+        expiry: PartitionExpiry,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            months_ahead,
+            raw_retention_days,
+            expiry,
+            interval,
+        }
+    }
+
+    /// Maintain partitions on the configured interval until `stop` is cancelled
+    pub async fn run(&self, stop: &CancellationToken) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            tokio::select! {
+                _ = stop.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.run_once().await {
+                tracing::error!("Partition maintenance failed: {}", e);
+            }
+        }
+    }
+
+    pub async fn run_once(&self) -> Result<PartitionReport> {
+        let (created, detached) = self.create_upcoming().await?;
+        let report = PartitionReport {
+            created,
+            expired: self.expire_old().await?,
+            detached,
+        };
+
+        if !report.detached.is_empty() {
+            tracing::warn!(
+                "Detached tables block upcoming partitions, rows go to the default: [{}]",
+                report.detached.join(", ")
+            );
+        }
+
+        if !report.created.is_empty() || !report.expired.is_empty() {
+            tracing::info!(
+                "Partitions: created [{}], {:?} [{}]",
+                report.created.join(", "),
+                self.expiry,
+                report.expired.join(", ")
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Create any missing partitions from this month through `months_ahead`,
+    /// returning those created and the detached tables in the way of others
+    async fn create_upcoming(&self) -> Result<(Vec<String>, Vec<String>)> {
+        let existing = self.partitions().await?;
+        let mut created = Vec::new();
+        let mut detached = Vec::new();
+
+        for month in upcoming_months(Utc::now().date_naive(), self.months_ahead) {
+            if existing.iter().any(|(_, m)| *m == month) {
+                continue;
+            }
+
+            // The partition function returns the name of a table it finds
+            // without attaching it, so check for a detached one first
+            let name = partition_name(month);
+            let taken: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+                .bind(&name)
+                .fetch_one(&self.pool)
+                .await?;
+            if taken {
+                detached.push(name);
+                continue;
+            }
+
+            // Rows already in the default partition for this month move into it
+            let name = sqlx::query_scalar::<_, String>("SELECT create_price_history_partition($1)")
+                .bind(month)
+                .fetch_one(&self.pool)
+                .await
+                .with_context(|| {
+                    let month = month.format("%Y-%m");
+                    format!("Could not create price_history partition for {}", month)
+                })?;
+            created.push(name);
+        }
+
+        Ok((created, detached))
+    }
+
+    /// Detach or drop partitions that are fully expired and rolled up
+    async fn expire_old(&self) -> Result<Vec<String>> {
+        let Some(days) = self.raw_retention_days else {
+            return Ok(Vec::new());
+        };
+
+        // Same cutoff as the raw delete, capped at the hourly rollup watermark;
+        // nothing expires before the first rollup
+        let cutoff: Option<chrono::DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            SELECT LEAST(date_trunc('hour', NOW() - INTERVAL '1 day' * $1, 'UTC'), rolled_through)
+            FROM rollup_watermarks
+            WHERE rollup = 'hourly'
+            "#,
+        )
+        .bind(days)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(cutoff) = cutoff else {
+            return Ok(Vec::new());
+        };
+
+        let mut expired = Vec::new();
+        for (name, month) in self.partitions().await? {
+            if !is_expired(month, cutoff) {
+                continue;
+            }
+
+            // Partition names come from the catalog and match price_history_YYYY_MM
+            let sql = match self.expiry {
+                PartitionExpiry::Drop => format!("DROP TABLE {}", name),
+                PartitionExpiry::Detach => {
+                    format!("ALTER TABLE price_history DETACH PARTITION {}", name)
+                }
+            };
+            sqlx::query(&sql).execute(&self.pool).await?;
+            expired.push(name);
+        }
+
+        Ok(expired)
+    }
+
+    /// Monthly partitions currently attached, with the first day of their month
+    async fn partitions(&self) -> Result<Vec<(String, NaiveDate)>> {
+        let names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT c.relname::TEXT
+            FROM pg_inherits i
+            JOIN pg_class c ON c.oid = i.inhrelid
+            WHERE i.inhparent = 'price_history'::regclass
+            ORDER BY c.relname
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(names
+            .into_iter()
+            .filter_map(|name| {
+                let month = partition_month(&name)?;
+                Some((name, month))
+            })
+            .collect())
+    }
+}
+
+/// First days of the months from `today`'s through `months_ahead` after it
+fn upcoming_months(today: NaiveDate, months_ahead: u32) -> Vec<NaiveDate> {
+    let this_month = today.with_day(1).expect("day 1 is valid");
+    (0..=months_ahead)
+        .map(|offset| this_month + Months::new(offset))
+        .collect()
+}
+
+/// Name of the partition for `month`, as `create_price_history_partition` makes it
+fn partition_name(month: NaiveDate) -> String {
+    format!("price_history_{}", month.format("%Y_%m"))
+}
+
+/// Parse the month out of a `price_history_YYYY_MM` partition name
+fn partition_month(name: &str) -> Option<NaiveDate> {
+    let suffix = name.strip_prefix("price_history_")?;
+    NaiveDate::parse_from_str(&format!("{}_01", suffix), "%Y_%m_%d").ok()
+}
+
+/// Whether the whole of `month` lies before `cutoff`
+fn is_expired(month: NaiveDate, cutoff: DateTime<Utc>) -> bool {
+    let end = (month + Months::new(1)).and_hms_opt(0, 0, 0).unwrap().and_utc();
+    end <= cutoff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn upcoming_months_cross_year_ends() {
+        assert_eq!(
+            upcoming_months(date("2024-11-30"), 2),
+            vec![date("2024-11-01"), date("2024-12-01"), date("2025-01-01")]
+        );
+        assert_eq!(upcoming_months(date("2024-02-29"), 0), vec![date("2024-02-01")]);
+    }
+
+    #[test]
+    fn partition_names_round_trip() {
+        for month in upcoming_months(date("2024-12-15"), 2) {
+            assert_eq!(partition_month(&partition_name(month)), Some(month));
+        }
+        assert_eq!(partition_name(date("2025-01-01")), "price_history_2025_01");
+        assert_eq!(partition_month("price_history_default"), None);
+        assert_eq!(partition_month("price_history_hourly"), None);
+        assert_eq!(partition_month("price_history_2024_13"), None);
+    }
+
+    #[test]
+    fn expires_only_months_wholly_before_the_cutoff() {
+        let cutoff: DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        assert!(is_expired(date("2024-02-01"), cutoff));
+        assert!(is_expired(date("2023-12-01"), cutoff));
+        assert!(!is_expired(date("2024-03-01"), cutoff));
+
+        let mid_month: DateTime<Utc> = "2024-02-29T23:00:00Z".parse().unwrap();
+        assert!(!is_expired(date("2024-02-01"), mid_month));
+        assert!(is_expired(date("2024-01-01"), mid_month));
+    }
+}
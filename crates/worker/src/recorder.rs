@@ -1,86 +1,128 @@
 use anyhow::Result;
-use pm_shared::{CreateMarket, Market};
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use pm_shared::store::{AnomalyStore, HistoryStore, MarketStore, QuarantineStore, StoreError};
+use pm_shared::{CreateMarket, Market, PriceHistory, QuarantinedMarket, Resolution};
+use std::sync::Arc;
+
+use crate::anomaly::{AnomalyDetector, Baseline};
 
 pub struct MarketRecorder {
-    pool: PgPool,
+    markets: Arc<dyn MarketStore>,
+    history: Arc<dyn HistoryStore>,
+    quarantine: Arc<dyn QuarantineStore>,
+    anomalies: Arc<dyn AnomalyStore>,
+    detector: AnomalyDetector,
 }
 
 impl MarketRecorder {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(
+        markets: Arc<dyn MarketStore>,
+        history: Arc<dyn HistoryStore>,
+        quarantine: Arc<dyn QuarantineStore>,
+        anomalies: Arc<dyn AnomalyStore>,
+        detector: AnomalyDetector,
+    ) -> Self {
+        Self {
+            markets,
+            history,
+            quarantine,
+            anomalies,
+            detector,
+        }
     }
 
-    /// Upsert market and record price history snapshot
-    pub async fn record_market(&self, market: CreateMarket) -> Result<Market> {
-        // Upsert market
-        let updated_market = sqlx::query_as::<_, Market>(
-            r#"
-            INSERT INTO markets (
-                source_id, source, title, description, category, tags,
-                yes_price, no_price, volume, volume_24h, liquidity,
-                status, close_at, url
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
-            ON CONFLICT (source, source_id)
-            DO UPDATE SET
-                title = EXCLUDED.title,
-                description = EXCLUDED.description,
-                yes_price = EXCLUDED.yes_price,
-                no_price = EXCLUDED.no_price,
-                volume = EXCLUDED.volume,
-                volume_24h = EXCLUDED.volume_24h,
-                liquidity = EXCLUDED.liquidity,
-                status = EXCLUDED.status,
-                close_at = EXCLUDED.close_at
-            RETURNING *
-            "#,
-        )
-        .bind(&market.source_id)
-        .bind(&market.source)
-        .bind(&market.title)
-        .bind(&market.description)
-        .bind(&market.category)
-        .bind(&market.tags)
-        .bind(market.yes_price)
-        .bind(market.no_price)
-        .bind(market.volume)
-        .bind(market.volume_24h)
-        .bind(market.liquidity)
-        .bind(&market.status)
-        .bind(market.close_at)
-        .bind(&market.url)
-        .fetch_one(&self.pool)
-        .await?;
-
-        // Record price history snapshot
-        let _ = sqlx::query(
-            r#"
-            INSERT INTO price_history (
-                market_id, yes_price, no_price, volume, volume_24h, liquidity
-            )
-            VALUES ($1, $2, $3, $4, $5, $6)
-            ON CONFLICT (market_id, recorded_at) DO NOTHING
-            "#,
-        )
-        .bind(updated_market.id)
-        .bind(updated_market.yes_price)
-        .bind(updated_market.no_price)
-        .bind(updated_market.volume)
-        .bind(updated_market.volume_24h)
-        .bind(updated_market.liquidity)
-        .execute(&self.pool)
-        .await?;
+    /// Upsert market, record the metadata it changed, and record a price
+    /// history snapshot at `recorded_at`, flagging it if it looks anomalous
+    pub async fn record_market(
+        &self,
+        market: CreateMarket,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<Market> {
+        let changes = match self.markets.get_by_source(market.source, &market.source_id).await {
+            // An imported placeholder's metadata was never the venue's to change
+            Ok(previous) if previous.placeholder => Vec::new(),
+            Ok(previous) => previous.changes(&market),
+            Err(StoreError::NotFound) => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        let updated_market = self.markets.upsert(market).await?;
+        if !changes.is_empty() {
+            self.markets
+                .record_revisions(updated_market.id, &changes, updated_market.updated_at)
+                .await?;
+        }
+        if let Some(snapshot) = self.history.record_snapshot(&updated_market, recorded_at).await? {
+            // The snapshot is kept either way; a failed check only loses the flag
+            if let Err(e) = self.flag_anomalies(&updated_market, &snapshot).await {
+                tracing::warn!("Anomaly check failed for market {}: {}", updated_market.id, e);
+            }
+        }
 
         Ok(updated_market)
     }
 
-    /// Batch record multiple markets
-    pub async fn record_markets_batch(&self, markets: Vec<CreateMarket>) -> Result<usize> {
+    /// Score a just-recorded snapshot against the market's recent history
+    async fn flag_anomalies(&self, market: &Market, latest: &PriceHistory) -> Result<()> {
+        let before = |s: &PriceHistory| s.recorded_at < latest.recorded_at;
+
+        let unflagged: Vec<PriceHistory> = self
+            .history
+            .get_history(market.id, self.detector.window + 1, None, Resolution::Raw, false)
+            .await?
+            .into_iter()
+            .filter(before)
+            .collect();
+        if unflagged.is_empty() {
+            return Ok(());
+        }
+
+        let previous = self
+            .history
+            .get_history(market.id, 2, None, Resolution::Raw, true)
+            .await?
+            .into_iter()
+            .find(before);
+        let stale_anchor = self
+            .history
+            .snapshots_at(&[market.id], latest.recorded_at - self.detector.stale_after)
+            .await?
+            .pop();
+
+        let baseline = Baseline {
+            unflagged,
+            previous,
+            stale_anchor,
+        };
+        let anomalies = self.detector.score(market.status, latest, &baseline);
+        if anomalies.is_empty() {
+            return Ok(());
+        }
+
+        for anomaly in &anomalies {
+            tracing::warn!(
+                "Flagged {} snapshot of {} market {}: {}",
+                anomaly.kind,
+                market.source,
+                market.source_id,
+                anomaly.detail
+            );
+        }
+        self.anomalies.flag(&anomalies).await?;
+
+        Ok(())
+    }
+
+    /// Batch record multiple markets fetched at `recorded_at`
+    pub async fn record_markets_batch(
+        &self,
+        markets: Vec<CreateMarket>,
+        recorded_at: DateTime<Utc>,
+    ) -> Result<usize> {
         let mut count = 0;
 
         for market in markets {
-            match self.record_market(market).await {
+            match self.record_market(market, recorded_at).await {
                 Ok(_) => count += 1,
                 Err(e) => {
                     tracing::error!("Failed to record market: {}", e);
@@ -90,4 +132,166 @@ impl MarketRecorder {
 
         Ok(count)
     }
+
+    /// Hold back markets that failed validation instead of recording them
+    pub async fn quarantine(&self, markets: &[QuarantinedMarket]) -> Result<()> {
+        if !markets.is_empty() {
+            self.quarantine.quarantine(markets).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use pm_shared::store::{MarketFilter, MarketSort, MemoryStore, SortOrder};
+    use pm_shared::{Decimal, MarketSource, MarketStatus};
+
+    fn market(source_id: &str, yes_cents: i64) -> CreateMarket {
+        let yes_price = Decimal::new(yes_cents, 2);
+        CreateMarket {
+            source_id: source_id.to_string(),
+            source: MarketSource::Kalshi,
+            title: format!("Market {}", source_id),
+            description: String::new(),
+            category: None,
+            source_category: None,
+            tags: None,
+            yes_price,
+            no_price: Decimal::ONE - yes_price,
+            volume: Decimal::from(100),
+            volume_24h: Decimal::from(10),
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    /// Recorder keeping everything in `store`
+    fn recorder(store: &Arc<MemoryStore>) -> MarketRecorder {
+        MarketRecorder::new(
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            AnomalyDetector::default(),
+        )
+    }
+
+    #[tokio::test]
+    async fn records_market_and_snapshot() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = recorder(&store);
+
+        let recorded = recorder.record_market(market("A", 40), Utc::now()).await.unwrap();
+
+        let history = store
+            .get_history(recorded.id, 10, None, Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].yes_price, Decimal::new(40, 2));
+    }
+
+    #[tokio::test]
+    async fn rerecording_updates_the_same_market() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = recorder(&store);
+
+        let first = recorder.record_market(market("A", 40), Utc::now()).await.unwrap();
+        let second = recorder.record_market(market("A", 60), Utc::now()).await.unwrap();
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(store.get_by_id(first.id).await.unwrap().yes_price, Decimal::new(60, 2));
+    }
+
+    #[tokio::test]
+    async fn records_metadata_changes_as_revisions() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = recorder(&store);
+
+        let first = recorder.record_market(market("A", 40), Utc::now()).await.unwrap();
+        recorder.record_market(market("A", 50), Utc::now()).await.unwrap();
+        assert!(store.revisions(&[first.id], None).await.unwrap().is_empty());
+
+        let mut closed = market("A", 50);
+        closed.status = MarketStatus::Closed;
+        recorder.record_market(closed, Utc::now()).await.unwrap();
+
+        let revisions = store.revisions(&[first.id], None).await.unwrap();
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].field, "status");
+        assert_eq!(revisions[0].old_value.as_deref(), Some("open"));
+        assert_eq!(revisions[0].new_value.as_deref(), Some("closed"));
+
+        let mut reworded = market("A", 50);
+        reworded.status = MarketStatus::Closed;
+        reworded.description = "Resolves on the final certified count".to_string();
+        recorder.record_market(reworded, Utc::now()).await.unwrap();
+
+        let latest = store.latest_revisions(first.id, 1).await.unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].field, "description");
+        assert_eq!(latest[0].old_value, Some(market("A", 50).description));
+        assert_eq!(
+            latest[0].new_value.as_deref(),
+            Some("Resolves on the final certified count")
+        );
+        assert_eq!(
+            store.get_by_id(first.id).await.unwrap().description,
+            "Resolves on the final certified count"
+        );
+    }
+
+    #[tokio::test]
+    async fn flags_spikes_and_hides_them_from_history() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = recorder(&store);
+        let start = Utc::now() - Duration::hours(24);
+
+        let mut recorded = None;
+        for hour in 0..20 {
+            let at = start + Duration::hours(hour);
+            recorded = Some(recorder.record_market(market("A", 40 + hour % 2), at).await.unwrap());
+        }
+        let id = recorded.unwrap().id;
+        let spike_at = start + Duration::hours(20);
+        recorder.record_market(market("A", 99), spike_at).await.unwrap();
+        recorder
+            .record_market(market("A", 41), spike_at + Duration::hours(1))
+            .await
+            .unwrap();
+
+        let flagged = store.anomalies(Some(id), None, 10).await.unwrap();
+        assert_eq!(flagged.len(), 1);
+        assert_eq!((flagged[0].kind.as_str(), flagged[0].recorded_at), ("price_spike", spike_at));
+
+        let history = store
+            .get_history(id, 100, None, Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 21);
+        assert!(history.iter().all(|h| h.recorded_at != spike_at));
+    }
+
+    #[tokio::test]
+    async fn batch_counts_recorded_markets() {
+        let store = Arc::new(MemoryStore::new());
+        let recorder = recorder(&store);
+
+        let count = recorder
+            .record_markets_batch(vec![market("A", 40), market("B", 50)], Utc::now())
+            .await
+            .unwrap();
+
+        assert_eq!(count, 2);
+        let listed = store
+            .list(10, 0, MarketSort::CreatedAt, SortOrder::Desc, &MarketFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+    }
 }
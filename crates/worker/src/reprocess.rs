@@ -0,0 +1,260 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use pm_shared::store::{HistoryStore, MarketStore, Snapshot, StoreError};
+use pm_shared::{CreateMarket, Market, MarketSource};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::archive::RawArchive;
+use crate::collectors::{CollectError, Collected, KalshiCollector, PolymarketCollector};
+use crate::validate;
+
+/// Totals from one reprocessing run
+#[derive(Debug, Default)]
+pub struct ReprocessReport {
+    pub payloads: usize,
+    /// Archived responses the current collectors can no longer parse
+    pub unreadable: usize,
+    pub valid: usize,
+    pub quarantined: usize,
+    pub snapshots_written: u64,
+    /// Markets whose row was rewritten from their newest reprocessed payload
+    pub markets_rebuilt: usize,
+    pub market_ids: Vec<Uuid>,
+    /// Earliest and latest fetch time replayed
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+}
+
+/// Replays archived venue responses through the current collectors.
+///
+/// Each response's snapshots are rewritten at its fetch time, which is when
+/// the original collection recorded them. A `markets` row is only rebuilt
+/// from a response at least as new as the market's latest snapshot, so
+/// replaying an old range never rolls a market back. Markets failing
+/// validation are counted but not quarantined again, and no revisions are
+/// recorded.
+pub struct Reprocessor {
+    markets: Arc<dyn MarketStore>,
+    history: Arc<dyn HistoryStore>,
+    polymarket: PolymarketCollector,
+    kalshi: KalshiCollector,
+    /// Parse and validate only, writing nothing
+    dry_run: bool,
+}
+
+impl Reprocessor {
+    pub fn new(
+        markets: Arc<dyn MarketStore>,
+        history: Arc<dyn HistoryStore>,
+        dry_run: bool,
+    ) -> Self {
+        Self {
+            markets,
+            history,
+            polymarket: PolymarketCollector::new(),
+            kalshi: KalshiCollector::new(),
+            dry_run,
+        }
+    }
+
+    pub async fn run(
+        &self,
+        archive: &RawArchive,
+        source: Option<MarketSource>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    ) -> Result<ReprocessReport> {
+        let mut report = ReprocessReport::default();
+        let mut market_ids = HashSet::new();
+        let mut payloads = archive.read(source, from, to);
+
+        while let Some(archived) = payloads.try_next().await? {
+            report.payloads += 1;
+
+            let collected = match self.parse(archived.source, &archived.body) {
+                Ok(collected) => collected,
+                Err(e) => {
+                    tracing::warn!(
+                        "Skipping {} response fetched at {}: {}",
+                        archived.source,
+                        archived.fetched_at,
+                        e
+                    );
+                    report.unreadable += 1;
+                    continue;
+                }
+            };
+
+            let (markets, quarantined) = validate::screen(archived.source, collected);
+            report.valid += markets.len();
+            report.quarantined += quarantined.len();
+
+            report.span = Some(match report.span {
+                Some((first, _)) => (first, archived.fetched_at),
+                None => (archived.fetched_at, archived.fetched_at),
+            });
+
+            if self.dry_run || markets.is_empty() {
+                continue;
+            }
+
+            let ids = self.replay(&mut report, markets, archived.fetched_at).await?;
+            market_ids.extend(ids);
+        }
+
+        report.market_ids = market_ids.into_iter().collect();
+        Ok(report)
+    }
+
+    /// Rewrite one response's snapshots, rebuilding market rows it is the
+    /// latest observation of. Returns the ids of the markets it covered.
+    async fn replay(
+        &self,
+        report: &mut ReprocessReport,
+        markets: Vec<CreateMarket>,
+        fetched_at: DateTime<Utc>,
+    ) -> Result<Vec<Uuid>> {
+        let mut existing: HashMap<String, Market> = HashMap::new();
+        for market in &markets {
+            match self.markets.get_by_source(market.source, &market.source_id).await {
+                Ok(found) => {
+                    existing.insert(market.source_id.clone(), found);
+                }
+                Err(StoreError::NotFound) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let existing_ids: Vec<Uuid> = existing.values().map(|m| m.id).collect();
+        let latest: HashMap<Uuid, DateTime<Utc>> = self
+            .history
+            .snapshots_at(&existing_ids, Utc::now())
+            .await?
+            .into_iter()
+            .map(|s| (s.market_id, s.recorded_at))
+            .collect();
+
+        let mut snapshots = Vec::with_capacity(markets.len());
+        for market in markets {
+            let known = existing.remove(&market.source_id);
+            let is_latest = known
+                .as_ref()
+                .and_then(|m| latest.get(&m.id))
+                .is_none_or(|latest| *latest <= fetched_at);
+
+            let market_id = match known {
+                Some(known) if !is_latest => known.id,
+                _ => {
+                    report.markets_rebuilt += 1;
+                    self.markets.upsert(market.clone()).await?.id
+                }
+            };
+
+            snapshots.push(Snapshot {
+                market_id,
+                yes_price: market.yes_price,
+                no_price: market.no_price,
+                volume: market.volume,
+                volume_24h: market.volume_24h,
+                liquidity: market.liquidity,
+                recorded_at: fetched_at,
+            });
+        }
+
+        report.snapshots_written += self.history.replace_snapshots(&snapshots).await?;
+        Ok(snapshots.iter().map(|s| s.market_id).collect())
+    }
+
+    fn parse(&self, source: MarketSource, body: &[u8]) -> Result<Vec<Collected>, CollectError> {
+        match source {
+            MarketSource::Polymarket => self.polymarket.parse(body),
+            MarketSource::Kalshi => self.kalshi.parse(body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomaly::AnomalyDetector;
+    use crate::recorder::MarketRecorder;
+    use chrono::{Duration, SubsecRound};
+    use pm_shared::store::MemoryStore;
+    use pm_shared::{Decimal, Resolution};
+
+    fn response(yes_bid: i64, yes_ask: i64) -> Vec<u8> {
+        serde_json::json!({
+            "markets": [{
+                "ticker": "FED-25DEC",
+                "title": "Fed cuts rates",
+                "status": "active",
+                "yes_bid": yes_bid,
+                "yes_ask": yes_ask,
+                "volume": 100,
+                "volume_24h": 10,
+            }]
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn rewrites_snapshots_without_rolling_back_markets() {
+        let store = Arc::new(MemoryStore::new());
+        let archive = RawArchive::Table(store.clone());
+        let recorder = MarketRecorder::new(
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            store.clone(),
+            AnomalyDetector::default(),
+        );
+        let kalshi = KalshiCollector::new();
+        let now = Utc::now().trunc_subsecs(6);
+
+        // Two cycles collected and archived; the older one converted differently
+        for (fetched_at, body) in [
+            (now - Duration::hours(2), response(40, 42)),
+            (now - Duration::hours(1), response(60, 62)),
+        ] {
+            archive.store(MarketSource::Kalshi, fetched_at, &body).await.unwrap();
+            let (markets, _) = validate::screen(MarketSource::Kalshi, kalshi.parse(&body).unwrap());
+            recorder.record_markets_batch(markets, fetched_at).await.unwrap();
+        }
+        let market = store.get_by_source(MarketSource::Kalshi, "FED-25DEC").await.unwrap();
+        store
+            .replace_snapshots(&[Snapshot {
+                market_id: market.id,
+                yes_price: Decimal::new(5, 1),
+                no_price: Decimal::new(5, 1),
+                volume: Decimal::ZERO,
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                recorded_at: now - Duration::hours(2),
+            }])
+            .await
+            .unwrap();
+
+        // Replaying only the older response repairs its snapshot but keeps the newer market row
+        let reprocessor = Reprocessor::new(store.clone(), store.clone(), false);
+        let report = reprocessor
+            .run(&archive, None, None, Some(now - Duration::minutes(90)))
+            .await
+            .unwrap();
+
+        assert_eq!((report.payloads, report.valid, report.snapshots_written), (1, 1, 1));
+        assert_eq!(report.markets_rebuilt, 0);
+
+        let history = store
+            .get_history(market.id, 10, None, Resolution::Raw, false)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].yes_price, Decimal::new(41, 2));
+
+        let market = store.get_by_id(market.id).await.unwrap();
+        assert_eq!(market.yes_price, Decimal::new(61, 2));
+    }
+}
@@ -0,0 +1,414 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use std::time::Duration;
+use tokio::time;
+use tokio_util::sync::CancellationToken;
+
+/// Rows touched by one retention pass
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub hourly_rolled: u64,
+    pub daily_rolled: u64,
+    pub raw_deleted: u64,
+    pub hourly_deleted: u64,
+}
+
+/// Downsamples `price_history` into hourly and daily rollups and deletes
+/// raw and hourly rows past their retention windows.
+///
+/// Daily rollups are kept forever. Rolling up is idempotent: each pass
+/// recomputes buckets from the last one it rolled onwards, and records how
+/// far it got in `rollup_watermarks` when it commits. Raw rows and hourly
+/// buckets are only deleted behind those watermarks, never behind buckets
+/// written by a backfill. Snapshots flagged in `anomalies` are left out of
+/// every bucket.
+pub struct RetentionJob {
+    pool: PgPool,
+    raw_retention_days: i64,
+    hourly_retention_days: i64,
+    interval: Duration,
+}
+
+impl RetentionJob {
+    pub fn new(
+        pool: PgPool,
+        raw_retention_days: i64,
+        hourly_retention_days: i64,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            pool,
+            raw_retention_days,
+            hourly_retention_days,
+            interval,
+        }
+    }
+
+    /// Apply retention on the configured interval until `stop` is cancelled
+    pub async fn run(&self, stop: &CancellationToken) {
+        let mut interval = time::interval(self.interval);
+
+        loop {
+            tokio::select! {
+                _ = stop.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            if let Err(e) = self.run_once().await {
+                tracing::error!("Retention pass failed: {}", e);
+            }
+        }
+    }
+
+    pub async fn run_once(&self) -> Result<RetentionReport> {
+        let report = RetentionReport {
+            hourly_rolled: self.rollup_hourly().await?,
+            daily_rolled: self.rollup_daily().await?,
+            raw_deleted: self.delete_expired_raw().await?,
+            hourly_deleted: self.delete_expired_hourly().await?,
+        };
+
+        tracing::info!(
+            "Retention: rolled {} hourly and {} daily buckets, deleted {} raw and {} hourly rows",
+            report.hourly_rolled,
+            report.daily_rolled,
+            report.raw_deleted,
+            report.hourly_deleted
+        );
+
+        Ok(report)
+    }
+
+    /// Roll complete hours of raw snapshots into `price_history_hourly`
+    async fn rollup_hourly(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let until: DateTime<Utc> = sqlx::query_scalar("SELECT date_trunc('hour', NOW(), 'UTC')")
+            .fetch_one(&mut *tx)
+            .await?;
+
+        // The last hour rolled is recomputed for snapshots recorded late into it
+        let result = sqlx::query(
+            r#"
+            WITH watermark AS (
+                SELECT COALESCE(
+                    (SELECT rolled_through - INTERVAL '1 hour' FROM rollup_watermarks
+                     WHERE rollup = 'hourly'),
+                    '-infinity'::TIMESTAMPTZ
+                ) AS since
+            )
+            INSERT INTO price_history_hourly (
+                market_id, bucket,
+                open_yes_price, high_yes_price, low_yes_price, close_yes_price, close_no_price,
+                volume, volume_24h, liquidity, sample_count
+            )
+            SELECT
+                ph.market_id,
+                date_trunc('hour', ph.recorded_at, 'UTC') AS bucket,
+                (array_agg(ph.yes_price ORDER BY ph.recorded_at))[1],
+                MAX(ph.yes_price),
+                MIN(ph.yes_price),
+                (array_agg(ph.yes_price ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.no_price ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.volume ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.volume_24h ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.liquidity ORDER BY ph.recorded_at DESC))[1],
+                COUNT(*)
+            FROM price_history ph, watermark
+            WHERE ph.recorded_at >= watermark.since
+              AND ph.recorded_at < $1
+              AND NOT EXISTS (
+                  SELECT 1 FROM anomalies a
+                  WHERE a.market_id = ph.market_id AND a.recorded_at = ph.recorded_at
+              )
+            GROUP BY ph.market_id, date_trunc('hour', ph.recorded_at, 'UTC')
+            ON CONFLICT (market_id, bucket) DO UPDATE SET
+                open_yes_price = EXCLUDED.open_yes_price,
+                high_yes_price = EXCLUDED.high_yes_price,
+                low_yes_price = EXCLUDED.low_yes_price,
+                close_yes_price = EXCLUDED.close_yes_price,
+                close_no_price = EXCLUDED.close_no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity,
+                sample_count = EXCLUDED.sample_count
+            "#,
+        )
+        .bind(until)
+        .execute(&mut *tx)
+        .await?;
+
+        advance_watermark(&mut tx, "hourly", until).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Roll complete days of hourly buckets into `price_history_daily`, up to
+    /// the hourly watermark
+    async fn rollup_daily(&self) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let until: DateTime<Utc> = sqlx::query_scalar(
+            r#"
+            SELECT LEAST(
+                date_trunc('day', NOW(), 'UTC'),
+                date_trunc('day', (SELECT rolled_through FROM rollup_watermarks
+                                   WHERE rollup = 'hourly'), 'UTC')
+            )
+            "#,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // The last day rolled is recomputed, as hours are
+        let result = sqlx::query(
+            r#"
+            WITH watermark AS (
+                SELECT COALESCE(
+                    (SELECT rolled_through - INTERVAL '1 day' FROM rollup_watermarks
+                     WHERE rollup = 'daily'),
+                    '-infinity'::TIMESTAMPTZ
+                ) AS since
+            )
+            INSERT INTO price_history_daily (
+                market_id, bucket,
+                open_yes_price, high_yes_price, low_yes_price, close_yes_price, close_no_price,
+                volume, volume_24h, liquidity, sample_count
+            )
+            SELECT
+                h.market_id,
+                date_trunc('day', h.bucket, 'UTC') AS bucket,
+                (array_agg(h.open_yes_price ORDER BY h.bucket))[1],
+                MAX(h.high_yes_price),
+                MIN(h.low_yes_price),
+                (array_agg(h.close_yes_price ORDER BY h.bucket DESC))[1],
+                (array_agg(h.close_no_price ORDER BY h.bucket DESC))[1],
+                (array_agg(h.volume ORDER BY h.bucket DESC))[1],
+                (array_agg(h.volume_24h ORDER BY h.bucket DESC))[1],
+                (array_agg(h.liquidity ORDER BY h.bucket DESC))[1],
+                SUM(h.sample_count)
+            FROM price_history_hourly h, watermark
+            WHERE h.bucket >= watermark.since
+              AND h.bucket < $1
+            GROUP BY h.market_id, date_trunc('day', h.bucket, 'UTC')
+            ON CONFLICT (market_id, bucket) DO UPDATE SET
+                open_yes_price = EXCLUDED.open_yes_price,
+                high_yes_price = EXCLUDED.high_yes_price,
+                low_yes_price = EXCLUDED.low_yes_price,
+                close_yes_price = EXCLUDED.close_yes_price,
+                close_no_price = EXCLUDED.close_no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity,
+                sample_count = EXCLUDED.sample_count
+            "#,
+        )
+        .bind(until)
+        .execute(&mut *tx)
+        .await?;
+
+        advance_watermark(&mut tx, "daily", until).await?;
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete raw snapshots older than the raw window, on an hour boundary
+    /// so the oldest remaining raw hour is complete, and only once rolled up
+    async fn delete_expired_raw(&self) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM price_history
+            WHERE recorded_at < date_trunc('hour', NOW() - INTERVAL '1 day' * $1, 'UTC')
+              AND recorded_at < (SELECT rolled_through FROM rollup_watermarks
+                                 WHERE rollup = 'hourly')
+            "#,
+        )
+        .bind(self.raw_retention_days)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete hourly buckets older than the hourly window, once rolled into days
+    async fn delete_expired_hourly(&self) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM price_history_hourly
+            WHERE bucket < date_trunc('day', NOW() - INTERVAL '1 day' * $1, 'UTC')
+              AND bucket < (SELECT rolled_through FROM rollup_watermarks WHERE rollup = 'daily')
+            "#,
+        )
+        .bind(self.hourly_retention_days)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Roll up snapshots imported into `[from, to]` for `market_ids`.
+    ///
+    /// The regular pass only rolls up from its watermark onwards, so
+    /// backfilled hours and days before it would otherwise never be rolled up
+    /// and their raw rows would expire without a trace. Buckets that already
+    /// exist are left alone: their raw rows may be gone, and recomputing them
+    /// from the imported rows alone would lose data.
+    pub async fn rollup_backfill(
+        &self,
+        market_ids: &[Uuid],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<(u64, u64)> {
+        self.rollup_range(market_ids, from, to, false).await
+    }
+
+    /// Roll up snapshots rewritten by `pm-worker reprocess` in `[from, to]`.
+    ///
+    /// Like `rollup_backfill`, but existing buckets are recomputed too as long
+    /// as every row they were built from is still retained: hourly buckets
+    /// within the raw window and daily buckets within the hourly window.
+    /// Older buckets are only filled in if missing.
+    pub async fn rollup_reprocessed(
+        &self,
+        market_ids: &[Uuid],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<(u64, u64)> {
+        self.rollup_range(market_ids, from, to, true).await
+    }
+
+    async fn rollup_range(
+        &self,
+        market_ids: &[Uuid],
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        replace: bool,
+    ) -> Result<(u64, u64)> {
+        let hourly = sqlx::query(
+            r#"
+            INSERT INTO price_history_hourly (
+                market_id, bucket,
+                open_yes_price, high_yes_price, low_yes_price, close_yes_price, close_no_price,
+                volume, volume_24h, liquidity, sample_count
+            )
+            SELECT
+                ph.market_id,
+                date_trunc('hour', ph.recorded_at, 'UTC') AS bucket,
+                (array_agg(ph.yes_price ORDER BY ph.recorded_at))[1],
+                MAX(ph.yes_price),
+                MIN(ph.yes_price),
+                (array_agg(ph.yes_price ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.no_price ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.volume ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.volume_24h ORDER BY ph.recorded_at DESC))[1],
+                (array_agg(ph.liquidity ORDER BY ph.recorded_at DESC))[1],
+                COUNT(*)
+            FROM price_history ph
+            WHERE ph.market_id = ANY($1)
+              AND ph.recorded_at >= date_trunc('hour', $2::TIMESTAMPTZ, 'UTC')
+              AND ph.recorded_at <= $3
+              AND ph.recorded_at < (SELECT rolled_through FROM rollup_watermarks
+                                    WHERE rollup = 'hourly')
+              AND NOT EXISTS (
+                  SELECT 1 FROM anomalies a
+                  WHERE a.market_id = ph.market_id AND a.recorded_at = ph.recorded_at
+              )
+            GROUP BY ph.market_id, date_trunc('hour', ph.recorded_at, 'UTC')
+            ON CONFLICT (market_id, bucket) DO UPDATE SET
+                open_yes_price = EXCLUDED.open_yes_price,
+                high_yes_price = EXCLUDED.high_yes_price,
+                low_yes_price = EXCLUDED.low_yes_price,
+                close_yes_price = EXCLUDED.close_yes_price,
+                close_no_price = EXCLUDED.close_no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity,
+                sample_count = EXCLUDED.sample_count
+            WHERE $4
+              AND EXCLUDED.bucket >= date_trunc('hour', NOW() - INTERVAL '1 day' * $5, 'UTC')
+            "#,
+        )
+        .bind(market_ids)
+        .bind(from)
+        .bind(to)
+        .bind(replace)
+        .bind(self.raw_retention_days)
+        .execute(&self.pool)
+        .await?;
+
+        let daily = sqlx::query(
+            r#"
+            INSERT INTO price_history_daily (
+                market_id, bucket,
+                open_yes_price, high_yes_price, low_yes_price, close_yes_price, close_no_price,
+                volume, volume_24h, liquidity, sample_count
+            )
+            SELECT
+                h.market_id,
+                date_trunc('day', h.bucket, 'UTC') AS bucket,
+                (array_agg(h.open_yes_price ORDER BY h.bucket))[1],
+                MAX(h.high_yes_price),
+                MIN(h.low_yes_price),
+                (array_agg(h.close_yes_price ORDER BY h.bucket DESC))[1],
+                (array_agg(h.close_no_price ORDER BY h.bucket DESC))[1],
+                (array_agg(h.volume ORDER BY h.bucket DESC))[1],
+                (array_agg(h.volume_24h ORDER BY h.bucket DESC))[1],
+                (array_agg(h.liquidity ORDER BY h.bucket DESC))[1],
+                SUM(h.sample_count)
+            FROM price_history_hourly h
+            WHERE h.market_id = ANY($1)
+              AND h.bucket >= date_trunc('day', $2::TIMESTAMPTZ, 'UTC')
+              AND h.bucket <= $3
+              AND h.bucket < (SELECT rolled_through FROM rollup_watermarks WHERE rollup = 'daily')
+            GROUP BY h.market_id, date_trunc('day', h.bucket, 'UTC')
+            ON CONFLICT (market_id, bucket) DO UPDATE SET
+                open_yes_price = EXCLUDED.open_yes_price,
+                high_yes_price = EXCLUDED.high_yes_price,
+                low_yes_price = EXCLUDED.low_yes_price,
+                close_yes_price = EXCLUDED.close_yes_price,
+                close_no_price = EXCLUDED.close_no_price,
+                volume = EXCLUDED.volume,
+                volume_24h = EXCLUDED.volume_24h,
+                liquidity = EXCLUDED.liquidity,
+                sample_count = EXCLUDED.sample_count
+            WHERE $4
+              AND EXCLUDED.bucket >= date_trunc('day', NOW() - INTERVAL '1 day' * $5, 'UTC')
+            "#,
+        )
+        .bind(market_ids)
+        .bind(from)
+        .bind(to)
+        .bind(replace)
+        .bind(self.hourly_retention_days)
+        .execute(&self.pool)
+        .await?;
+
+        Ok((hourly.rows_affected(), daily.rows_affected()))
+    }
+}
+
+/// Record that `rollup` has rolled up everything before `through`, in the
+/// transaction that did the rolling up
+async fn advance_watermark(
+    tx: &mut Transaction<'_, Postgres>,
+    rollup: &str,
+    through: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO rollup_watermarks (rollup, rolled_through, updated_at)
+        VALUES ($1, $2, NOW())
+        ON CONFLICT (rollup) DO UPDATE
+        SET rolled_through = GREATEST(rollup_watermarks.rolled_through, EXCLUDED.rolled_through),
+            updated_at = EXCLUDED.updated_at
+        "#,
+    )
+    .bind(rollup)
+    .bind(through)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use pm_shared::store::{RunStore, SourceRun};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::scheduler::{CycleReport, SourceReport};
+
+/// Writes cycle and per-source outcomes to the `collection_runs` audit table
+pub struct RunLog {
+    store: Arc<dyn RunStore>,
+}
+
+impl RunLog {
+    pub fn new(store: Arc<dyn RunStore>) -> Self {
+        Self { store }
+    }
+
+    /// Open a cycle row up front so a worker that dies mid-cycle leaves a trace
+    pub async fn start_cycle(&self, started_at: DateTime<Utc>) -> Result<Uuid> {
+        Ok(self.store.start_cycle(started_at).await?)
+    }
+
+    pub async fn finish_cycle(&self, id: Uuid, report: &CycleReport) -> Result<()> {
+        let fetched: usize = report.sources.iter().map(|s| s.fetched).sum();
+        let recorded: usize = report.sources.iter().map(|s| s.recorded).sum();
+
+        let failed_sources: Vec<&str> = report
+            .sources
+            .iter()
+            .filter(|s| s.error.is_some())
+            .map(|s| s.source.as_str())
+            .collect();
+
+        let error = match &report.interrupted {
+            Some(reason) => Some(reason.clone()),
+            None if !failed_sources.is_empty() => {
+                Some(format!("failed sources: {}", failed_sources.join(", ")))
+            }
+            None => None,
+        };
+
+        self.store
+            .finish_cycle(id, fetched as i32, recorded as i32, error)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_source(&self, cycle_id: Uuid, report: &SourceReport) -> Result<()> {
+        let run = SourceRun {
+            source: report.source.as_str().to_string(),
+            started_at: report.started_at,
+            finished_at: report.finished_at,
+            markets_fetched: report.fetched as i32,
+            markets_recorded: report.recorded as i32,
+            error: report.error.clone(),
+            http_status: report.http_status.map(i32::from),
+        };
+
+        self.store.record_source(cycle_id, run).await?;
+        Ok(())
+    }
+}
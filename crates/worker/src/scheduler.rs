@@ -1,30 +1,89 @@
 use anyhow::Result;
-use sqlx::PgPool;
-use std::time::Duration;
+use chrono::{DateTime, SubsecRound, Utc};
+use pm_shared::MarketSource;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::time;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
 
-use crate::collectors::{KalshiCollector, PolymarketCollector};
+use crate::archive::RawArchive;
+use crate::collectors::{CollectError, Collected, KalshiCollector, PolymarketCollector};
 use crate::config::Config;
+use crate::metrics::Metrics;
 use crate::recorder::MarketRecorder;
+use crate::runs::RunLog;
+use crate::validate;
+
+/// Outcome of collecting a single source during a cycle
+#[derive(Debug)]
+pub struct SourceReport {
+    pub source: MarketSource,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub fetched: usize,
+    /// Fetched markets that failed validation and were quarantined
+    pub quarantined: usize,
+    pub recorded: usize,
+    pub error: Option<String>,
+    pub http_status: Option<u16>,
+}
+
+/// Outcome of a full collection cycle
+#[derive(Debug, Default)]
+pub struct CycleReport {
+    pub sources: Vec<SourceReport>,
+    /// Set when the cycle was cut short by shutdown or the max cycle duration
+    pub interrupted: Option<String>,
+}
+
+impl CycleReport {
+    /// A cycle succeeds when it ran to completion and every source was
+    /// fetched and recorded without error
+    pub fn is_success(&self) -> bool {
+        self.interrupted.is_none() && self.sources.iter().all(|s| s.error.is_none())
+    }
+}
 
 pub struct Scheduler {
     config: Config,
-    recorder: MarketRecorder,
+    /// `None` in dry-run mode: converted markets are printed instead of written
+    recorder: Option<MarketRecorder>,
+    run_log: Option<RunLog>,
+    /// Keeps each venue response for `pm-worker reprocess` when set
+    archive: Option<RawArchive>,
+    sources: Vec<MarketSource>,
+    shutdown: CancellationToken,
+    metrics: Arc<Metrics>,
     polymarket: PolymarketCollector,
     kalshi: KalshiCollector,
 }
 
 impl Scheduler {
-    pub fn new(config: Config, pool: PgPool) -> Self {
+    pub fn new(
+        config: Config,
+        recorder: Option<MarketRecorder>,
+        run_log: Option<RunLog>,
+        archive: Option<RawArchive>,
+        sources: Vec<MarketSource>,
+        shutdown: CancellationToken,
+        metrics: Arc<Metrics>,
+    ) -> Self {
         Self {
             config,
-            recorder: MarketRecorder::new(pool),
+            recorder,
+            run_log,
+            archive,
+            sources,
+            shutdown,
+            metrics,
             polymarket: PolymarketCollector::new(),
             kalshi: KalshiCollector::new(),
         }
     }
 
-    pub async fn run(&self) -> Result<()> {
+    /// Collect on the configured interval until `stop` is cancelled
+    pub async fn run(&self, stop: &CancellationToken) -> Result<()> {
         tracing::info!(
             "Starting scheduler with {}s interval, tracking {} markets",
             self.config.collection_interval_seconds,
@@ -36,51 +95,650 @@ impl Scheduler {
         ));
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = stop.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+
+            self.run_once(stop).await;
+        }
+
+        tracing::info!("Scheduler stopped");
+        Ok(())
+    }
+
+    /// Run a single collection cycle over all configured sources.
+    ///
+    /// Venue fetches still pending `MAX_CYCLE_SECONDS` after the cycle started
+    /// are abandoned; writes are never cut short by it. On shutdown, pending
+    /// fetches are dropped and writes already under way get
+    /// `SHUTDOWN_GRACE_SECONDS` to finish. When `stop` is cancelled without a
+    /// shutdown, leadership was lost and the cycle is dropped at once, so it
+    /// can't write alongside the new leader.
+    pub async fn run_once(&self, stop: &CancellationToken) -> CycleReport {
+        tracing::info!("Starting collection cycle");
 
-            tracing::info!("Starting collection cycle");
+        let started = Instant::now();
+        let deadline = time::Instant::now() + Duration::from_secs(self.config.max_cycle_seconds);
+        let grace = Duration::from_secs(self.config.shutdown_grace_seconds);
 
-            if let Err(e) = self.collect_and_record().await {
-                tracing::error!("Collection cycle failed: {}", e);
+        let cycle_id = match &self.run_log {
+            Some(run_log) => match run_log.start_cycle(Utc::now()).await {
+                Ok(id) => Some(id),
+                Err(e) => {
+                    tracing::error!("Failed to log collection cycle start: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let mut report = CycleReport::default();
+        let interrupted = {
+            let cycle = self.collect_cycle(&mut report, cycle_id, deadline, stop);
+            tokio::pin!(cycle);
+
+            // `stop` is cancelled along with shutdown, which must win to get its grace period
+            tokio::select! {
+                biased;
+                _ = &mut cycle => None,
+                _ = self.shutdown.cancelled() => {
+                    match time::timeout(grace, &mut cycle).await {
+                        Ok(()) => None,
+                        Err(_) => Some(format!(
+                            "shutdown grace period of {}s elapsed",
+                            grace.as_secs()
+                        )),
+                    }
+                }
+                _ = stop.cancelled() => Some("leadership lost".to_string()),
             }
+        };
+
+        if let Some(reason) = interrupted {
+            tracing::warn!("Collection cycle interrupted: {}", reason);
+            report.interrupted = Some(reason);
+        }
+
+        if report.interrupted.is_some() {
+            self.metrics.record_error("all", "interrupted");
+        }
+
+        self.metrics
+            .cycle_duration
+            .observe(started.elapsed().as_secs_f64());
+
+        if let (Some(run_log), Some(id)) = (&self.run_log, cycle_id) {
+            if let Err(e) = run_log.finish_cycle(id, &report).await {
+                tracing::error!("Failed to log collection cycle end: {}", e);
+            }
+        }
+
+        if report.is_success() {
+            self.metrics.mark_success();
+            tracing::info!("Collection cycle completed");
+        } else {
+            tracing::error!("Collection cycle completed with errors");
         }
+
+        report
     }
 
-    async fn collect_and_record(&self) -> Result<()> {
-        let markets_per_source = self.config.tracked_markets_limit / 2;
+    async fn collect_cycle(
+        &self,
+        report: &mut CycleReport,
+        cycle_id: Option<Uuid>,
+        deadline: time::Instant,
+        stop: &CancellationToken,
+    ) {
+        let markets_per_source = self.config.tracked_markets_limit / self.sources.len().max(1);
+
+        for (idx, source) in self.sources.iter().enumerate() {
+            if self.shutdown.is_cancelled() {
+                report.interrupted = Some("shutdown requested".to_string());
+                break;
+            }
+
+            if stop.is_cancelled() {
+                report.interrupted = Some("leadership lost".to_string());
+                break;
+            }
+
+            if time::Instant::now() >= deadline {
+                report.interrupted = Some(format!(
+                    "cycle exceeded {}s, remaining sources skipped",
+                    self.config.max_cycle_seconds
+                ));
+                break;
+            }
+
+            if idx > 0 {
+                // Small delay between sources
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+
+            let source_report = self
+                .collect_source(*source, markets_per_source, deadline, stop)
+                .await;
 
-        // Collect from Polymarket
-        match self.polymarket.fetch_markets(markets_per_source).await {
-            Ok(markets) => {
-                tracing::info!("Collected {} markets from Polymarket", markets.len());
-                match self.recorder.record_markets_batch(markets).await {
-                    Ok(count) => tracing::info!("Recorded {} Polymarket markets", count),
-                    Err(e) => tracing::error!("Failed to record Polymarket markets: {}", e),
+            if let (Some(run_log), Some(id)) = (&self.run_log, cycle_id) {
+                if let Err(e) = run_log.record_source(id, &source_report).await {
+                    tracing::error!("Failed to log {} collection step: {}", source, e);
                 }
             }
-            Err(e) => {
-                tracing::error!("Failed to fetch Polymarket markets: {}", e);
+
+            report.sources.push(source_report);
+        }
+    }
+
+    async fn collect_source(
+        &self,
+        source: MarketSource,
+        limit: usize,
+        deadline: time::Instant,
+        stop: &CancellationToken,
+    ) -> SourceReport {
+        let mut report = SourceReport {
+            source,
+            started_at: Utc::now(),
+            finished_at: Utc::now(),
+            fetched: 0,
+            quarantined: 0,
+            recorded: 0,
+            error: None,
+            http_status: None,
+        };
+
+        self.collect_source_into(&mut report, limit, deadline, stop).await;
+        report.finished_at = Utc::now();
+        report
+    }
+
+    async fn collect_source_into(
+        &self,
+        report: &mut SourceReport,
+        limit: usize,
+        deadline: time::Instant,
+        stop: &CancellationToken,
+    ) {
+        let source = report.source;
+
+        // A fetch in flight is abandoned on shutdown, on losing leadership or
+        // once the cycle runs out of time; nothing has been written yet
+        let fetch_timer = self
+            .metrics
+            .fetch_duration
+            .with_label_values(&[source.as_str()])
+            .start_timer();
+        let fetched = tokio::select! {
+            result = self.fetch(source, limit) => result,
+            _ = time::sleep_until(deadline) => {
+                fetch_timer.stop_and_discard();
+                tracing::error!("Abandoned {} fetch at the cycle deadline", source);
+                self.metrics.record_error(source.as_str(), "timeout");
+                report.error = Some(format!(
+                    "fetch abandoned after cycle exceeded {}s",
+                    self.config.max_cycle_seconds
+                ));
+                return;
+            }
+            _ = stop.cancelled() => {
+                fetch_timer.stop_and_discard();
+                report.error = Some(match self.shutdown.is_cancelled() {
+                    true => "fetch cancelled by shutdown".to_string(),
+                    false => "fetch cancelled, leadership lost".to_string(),
+                });
+                return;
+            }
+        };
+        fetch_timer.observe_duration();
+
+        // Snapshots are recorded at the fetch time (at database precision), so
+        // reprocessing an archived response rewrites exactly the rows it produced
+        let fetched_at = Utc::now().trunc_subsecs(6);
+
+        let body = match fetched {
+            Ok(body) => body,
+            Err(e) => return self.fetch_failed(report, e),
+        };
+
+        if let Some(archive) = &self.archive {
+            if let Err(e) = archive.store(source, fetched_at, &body).await {
+                tracing::error!("Failed to archive {} response: {}", source, e);
+                self.metrics.record_error(source.as_str(), "archive");
             }
         }
 
-        // Small delay between sources
-        tokio::time::sleep(Duration::from_millis(500)).await;
+        let collected = match self.parse(source, &body) {
+            Ok(collected) => collected,
+            Err(e) => return self.fetch_failed(report, e),
+        };
+
+        tracing::info!("Collected {} markets from {}", collected.len(), source);
+        self.record_fetched(report, collected, fetched_at, stop).await;
+    }
 
-        // Collect from Kalshi
-        match self.kalshi.fetch_markets(markets_per_source).await {
-            Ok(markets) => {
-                tracing::info!("Collected {} markets from Kalshi", markets.len());
-                match self.recorder.record_markets_batch(markets).await {
-                    Ok(count) => tracing::info!("Recorded {} Kalshi markets", count),
-                    Err(e) => tracing::error!("Failed to record Kalshi markets: {}", e),
+    fn fetch_failed(&self, report: &mut SourceReport, e: CollectError) {
+        tracing::error!("Failed to fetch {} markets: {}", report.source, e);
+        self.metrics.record_error(report.source.as_str(), e.kind());
+        report.http_status = e.http_status();
+        report.error = Some(e.to_string());
+    }
+
+    /// Keep this shard's markets, quarantine those failing validation and
+    /// record the rest, or print them in dry-run mode. Nothing is written once
+    /// `stop` signals lost leadership.
+    async fn record_fetched(
+        &self,
+        report: &mut SourceReport,
+        mut collected: Vec<Collected>,
+        fetched_at: DateTime<Utc>,
+        stop: &CancellationToken,
+    ) {
+        let source = report.source;
+
+        if self.config.shard.is_sharded() {
+            collected.retain(|c| {
+                let source_id = c.source_id.as_deref().unwrap_or_default();
+                self.config.shard.owns_market(source, source_id)
+            });
+        }
+        report.fetched = collected.len();
+        self.metrics
+            .markets_fetched
+            .with_label_values(&[source.as_str()])
+            .inc_by(collected.len() as u64);
+
+        let (markets, quarantined) = validate::screen(source, collected);
+        report.quarantined = quarantined.len();
+        self.metrics
+            .markets_quarantined
+            .with_label_values(&[source.as_str()])
+            .inc_by(quarantined.len() as u64);
+        for market in &quarantined {
+            tracing::warn!(
+                "Quarantined {} market {}: {}",
+                source,
+                market.source_id.as_deref().unwrap_or("<no id>"),
+                market.reason
+            );
+        }
+
+        let Some(recorder) = &self.recorder else {
+            for market in &markets {
+                match serde_json::to_string(market) {
+                    Ok(json) => println!("{}", json),
+                    Err(e) => tracing::error!("Failed to serialize market: {}", e),
                 }
             }
+            return;
+        };
+
+        // A shutdown lets this cycle's writes finish; lost leadership doesn't
+        if stop.is_cancelled() && !self.shutdown.is_cancelled() {
+            tracing::warn!("Leadership lost, not recording {} markets", source);
+            report.error = Some("leadership lost before recording".to_string());
+            return;
+        }
+
+        if let Err(e) = recorder.quarantine(&quarantined).await {
+            tracing::error!("Failed to quarantine {} markets: {}", source, e);
+            self.metrics.record_error(source.as_str(), "quarantine");
+        }
+
+        let valid = markets.len();
+
+        match recorder.record_markets_batch(markets, fetched_at).await {
+            Ok(count) => {
+                tracing::info!("Recorded {} {} markets", count, source);
+                self.metrics
+                    .markets_recorded
+                    .with_label_values(&[source.as_str()])
+                    .inc_by(count as u64);
+                // Individual market failures are logged by the recorder
+                self.metrics
+                    .errors
+                    .with_label_values(&[source.as_str(), "record"])
+                    .inc_by(valid.saturating_sub(count) as u64);
+                report.recorded = count;
+            }
             Err(e) => {
-                tracing::error!("Failed to fetch Kalshi markets: {}", e);
+                tracing::error!("Failed to record {} markets: {}", source, e);
+                self.metrics.record_error(source.as_str(), "record");
+                report.error = Some(e.to_string());
             }
         }
+    }
 
-        tracing::info!("Collection cycle completed");
-        Ok(())
+    async fn fetch(&self, source: MarketSource, limit: usize) -> Result<Vec<u8>, CollectError> {
+        match source {
+            MarketSource::Polymarket => self.polymarket.fetch(limit).await,
+            MarketSource::Kalshi => self.kalshi.fetch(limit).await,
+        }
+    }
+
+    fn parse(&self, source: MarketSource, body: &[u8]) -> Result<Vec<Collected>, CollectError> {
+        match source {
+            MarketSource::Polymarket => self.polymarket.parse(body),
+            MarketSource::Kalshi => self.kalshi.parse(body),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anomaly::AnomalyDetector;
+    use crate::partitions::PartitionExpiry;
+    use crate::shard::{Shard, ShardStrategy};
+    use crate::validate::Issue;
+    use chrono::Duration as ChronoDuration;
+    use pm_shared::store::{
+        MarketFilter, MarketSort, MarketStore, MemoryStore, QuarantineStore, SortOrder,
+    };
+    use pm_shared::{CreateMarket, Decimal, MarketStatus};
+
+    fn config(shard: Shard) -> Config {
+        Config {
+            database_url: None,
+            auto_migrate: false,
+            collection_interval_seconds: 60,
+            tracked_markets_limit: 10,
+            enabled: true,
+            max_cycle_seconds: 30,
+            shutdown_grace_seconds: 5,
+            shard,
+            leader_poll_seconds: 15,
+            http_host: "127.0.0.1".to_string(),
+            http_port: 0,
+            health_stale_intervals: 3,
+            retention_enabled: false,
+            raw_retention_days: 30,
+            hourly_retention_days: 365,
+            retention_interval_seconds: 3600,
+            partition_months_ahead: 2,
+            partition_expiry: PartitionExpiry::Drop,
+            raw_archive: None,
+            anomaly_detector: AnomalyDetector::default(),
+            market_metrics_enabled: false,
+            market_metrics_interval_seconds: 3600,
+        }
+    }
+
+    fn scheduler(shard: Shard, store: &Arc<MemoryStore>) -> Scheduler {
+        Scheduler::new(
+            config(shard),
+            Some(MarketRecorder::new(
+                store.clone(),
+                store.clone(),
+                store.clone(),
+                store.clone(),
+                AnomalyDetector::default(),
+            )),
+            None,
+            None,
+            vec![MarketSource::Kalshi],
+            CancellationToken::new(),
+            Arc::new(Metrics::new().unwrap()),
+        )
+    }
+
+    /// Scheduler whose Kalshi requests go to a server that accepts
+    /// connections and never answers
+    async fn hanging_scheduler(max_cycle_seconds: u64) -> Scheduler {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                open.push(socket);
+            }
+        });
+
+        let store = Arc::new(MemoryStore::new());
+        Scheduler {
+            config: Config {
+                max_cycle_seconds,
+                ..config(Shard::single())
+            },
+            kalshi: KalshiCollector::with_base_url(&format!("http://{}", addr)),
+            ..scheduler(Shard::single(), &store)
+        }
+    }
+
+    fn market(source_id: &str) -> Collected {
+        Collected {
+            source_id: Some(source_id.to_string()),
+            payload: serde_json::json!({ "ticker": source_id }),
+            market: Ok(create_market(source_id)),
+        }
+    }
+
+    fn create_market(source_id: &str) -> CreateMarket {
+        CreateMarket {
+            source_id: source_id.to_string(),
+            source: MarketSource::Kalshi,
+            title: format!("Market {}", source_id),
+            description: String::new(),
+            category: None,
+            source_category: None,
+            tags: None,
+            yes_price: Decimal::new(5, 1),
+            no_price: Decimal::new(5, 1),
+            volume: Decimal::from(100),
+            volume_24h: Decimal::from(10),
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    fn report() -> SourceReport {
+        SourceReport {
+            source: MarketSource::Kalshi,
+            started_at: Utc::now(),
+            finished_at: Utc::now(),
+            fetched: 0,
+            quarantined: 0,
+            recorded: 0,
+            error: None,
+            http_status: None,
+        }
+    }
+
+    #[test]
+    fn cycle_fails_on_any_source_error_or_interruption() {
+        let failed = SourceReport {
+            error: Some("HTTP 503".to_string()),
+            ..report()
+        };
+
+        assert!(CycleReport::default().is_success());
+        assert!(CycleReport {
+            sources: vec![report(), report()],
+            interrupted: None,
+        }
+        .is_success());
+        assert!(!CycleReport {
+            sources: vec![report(), failed],
+            interrupted: None,
+        }
+        .is_success());
+        assert!(!CycleReport {
+            sources: vec![report()],
+            interrupted: Some("shutdown requested".to_string()),
+        }
+        .is_success());
+    }
+
+    #[tokio::test]
+    async fn abandons_a_hanging_fetch_at_the_cycle_deadline() {
+        let scheduler = hanging_scheduler(1).await;
+
+        let started = Instant::now();
+        let report = scheduler.run_once(&scheduler.shutdown).await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!report.is_success());
+        assert_eq!(report.sources.len(), 1);
+        assert!(report.sources[0].error.as_deref().unwrap().contains("abandoned"));
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_a_pending_fetch() {
+        let scheduler = hanging_scheduler(300).await;
+        let shutdown = scheduler.shutdown.clone();
+        tokio::spawn(async move {
+            time::sleep(Duration::from_millis(200)).await;
+            shutdown.cancel();
+        });
+
+        let started = Instant::now();
+        let report = scheduler.run_once(&scheduler.shutdown).await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(
+            report.sources[0].error.as_deref(),
+            Some("fetch cancelled by shutdown")
+        );
+        assert!(!report.is_success());
+    }
+
+    #[tokio::test]
+    async fn lost_leadership_drops_the_cycle_at_once() {
+        let scheduler = hanging_scheduler(300).await;
+        let leadership = scheduler.shutdown.child_token();
+        let lost = leadership.clone();
+        tokio::spawn(async move {
+            time::sleep(Duration::from_millis(200)).await;
+            lost.cancel();
+        });
+
+        let started = Instant::now();
+        let report = scheduler.run_once(&leadership).await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!report.is_success());
+        assert_eq!(
+            report.sources[0].error.as_deref(),
+            Some("fetch cancelled, leadership lost")
+        );
+    }
+
+    #[tokio::test]
+    async fn records_nothing_after_losing_leadership() {
+        let store = Arc::new(MemoryStore::new());
+        let scheduler = scheduler(Shard::single(), &store);
+        let mut report = report();
+        let leadership = scheduler.shutdown.child_token();
+        leadership.cancel();
+
+        scheduler
+            .record_fetched(&mut report, vec![market("A")], Utc::now(), &leadership)
+            .await;
+
+        assert_eq!(report.recorded, 0);
+        assert!(report.error.is_some());
+        let listed = store
+            .list(10, 0, MarketSort::CreatedAt, SortOrder::Desc, &MarketFilter::default())
+            .await
+            .unwrap();
+        assert!(listed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn no_source_starts_after_shutdown() {
+        let store = Arc::new(MemoryStore::new());
+        let scheduler = scheduler(Shard::single(), &store);
+        scheduler.shutdown.cancel();
+
+        let report = scheduler.run_once(&scheduler.shutdown).await;
+
+        assert!(report.sources.is_empty());
+        assert_eq!(report.interrupted.as_deref(), Some("shutdown requested"));
+    }
+
+    #[tokio::test]
+    async fn records_fetched_markets() {
+        let store = Arc::new(MemoryStore::new());
+        let scheduler = scheduler(Shard::single(), &store);
+        let mut report = report();
+        let stop = CancellationToken::new();
+
+        scheduler
+            .record_fetched(&mut report, vec![market("A"), market("B")], Utc::now(), &stop)
+            .await;
+
+        assert_eq!((report.fetched, report.recorded), (2, 2));
+        assert!(report.error.is_none());
+        let listed = store
+            .list(10, 0, MarketSort::CreatedAt, SortOrder::Desc, &MarketFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn market_shards_record_disjoint_markets() {
+        let mut recorded = 0;
+
+        for index in 0..2 {
+            let store = Arc::new(MemoryStore::new());
+            let shard = Shard::parse(&format!("{}/2", index), ShardStrategy::Market).unwrap();
+            let scheduler = scheduler(shard, &store);
+            let mut report = report();
+            let stop = CancellationToken::new();
+
+            let markets = (0..20).map(|i| market(&i.to_string())).collect();
+            scheduler
+                .record_fetched(&mut report, markets, Utc::now(), &stop)
+                .await;
+
+            assert_eq!(report.fetched, report.recorded);
+            recorded += report.recorded;
+        }
+
+        assert_eq!(recorded, 20);
+    }
+
+    #[tokio::test]
+    async fn quarantines_invalid_markets_instead_of_recording() {
+        let store = Arc::new(MemoryStore::new());
+        let scheduler = scheduler(Shard::single(), &store);
+        let mut report = report();
+        let stop = CancellationToken::new();
+
+        let mut lopsided = create_market("B");
+        lopsided.no_price = Decimal::new(9, 1);
+        let unpriced = Collected {
+            source_id: Some("C".to_string()),
+            payload: serde_json::json!({ "ticker": "C" }),
+            market: Err(vec![Issue::new("missing_price", "no yes bid or ask")]),
+        };
+        let collected = vec![
+            market("A"),
+            Collected {
+                market: Ok(lopsided),
+                ..market("B")
+            },
+            unpriced,
+        ];
+
+        scheduler
+            .record_fetched(&mut report, collected, Utc::now(), &stop)
+            .await;
+
+        assert_eq!((report.fetched, report.quarantined, report.recorded), (3, 2, 1));
+        let listed = store
+            .list(10, 0, MarketSort::CreatedAt, SortOrder::Desc, &MarketFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+
+        let since = Utc::now() - ChronoDuration::hours(1);
+        let quarantined = store.quarantined(since, None, 10).await.unwrap();
+        assert_eq!(quarantined.len(), 2);
+        assert!(quarantined.iter().any(|q| q.checks == vec!["price_sum"]));
     }
 }
@@ -0,0 +1,63 @@
+use axum::{extract::State, http::StatusCode, routing::get, Router};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use crate::metrics::Metrics;
+
+#[derive(Clone)]
+struct ServerState {
+    metrics: Arc<Metrics>,
+    /// Age after which the last successful cycle counts as stalled
+    max_staleness_seconds: i64,
+}
+
+/// Serve `/healthz` and `/metrics` until `shutdown` is cancelled
+pub async fn serve(
+    addr: String,
+    metrics: Arc<Metrics>,
+    max_staleness_seconds: i64,
+    shutdown: CancellationToken,
+) -> anyhow::Result<()> {
+    let state = ServerState {
+        metrics,
+        max_staleness_seconds,
+    };
+
+    let app = Router::new()
+        .route("/healthz", get(healthz))
+        .route("/metrics", get(metrics_handler))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("Health and metrics listening on {}", addr);
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move { shutdown.cancelled().await })
+        .await?;
+
+    Ok(())
+}
+
+async fn healthz(State(state): State<ServerState>) -> (StatusCode, String) {
+    // Standby replicas are healthy by definition: they are waiting, not stalled
+    if state.metrics.leader.get() == 0 {
+        return (StatusCode::OK, "OK (standby)".to_string());
+    }
+
+    let age = state.metrics.seconds_since_success();
+    if age > state.max_staleness_seconds {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Last successful cycle {}s ago", age),
+        )
+    } else {
+        (StatusCode::OK, "OK".to_string())
+    }
+}
+
+async fn metrics_handler(State(state): State<ServerState>) -> (StatusCode, String) {
+    match state.metrics.encode() {
+        Ok(body) => (StatusCode::OK, body),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
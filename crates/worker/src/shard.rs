@@ -0,0 +1,167 @@
+use pm_shared::MarketSource;
+use std::str::FromStr;
+
+/// How work is divided between sharded replicas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardStrategy {
+    /// Each shard collects a disjoint subset of sources
+    Source,
+    /// Every shard fetches all sources but records a disjoint subset of markets
+    Market,
+}
+
+impl FromStr for ShardStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "source" => Ok(ShardStrategy::Source),
+            "market" => Ok(ShardStrategy::Market),
+            _ => Err(format!("Unknown shard strategy: {}", s)),
+        }
+    }
+}
+
+/// This replica's slice of the collection work, parsed from `WORKER_SHARD=index/count`.
+///
+/// Replicas configured with the same shard compete for the same advisory
+/// lock, so one collects and the others stand by.
+#[derive(Debug, Clone, Copy)]
+pub struct Shard {
+    pub index: u32,
+    pub count: u32,
+    pub strategy: ShardStrategy,
+}
+
+impl Shard {
+    pub fn single() -> Self {
+        Self {
+            index: 0,
+            count: 1,
+            strategy: ShardStrategy::Source,
+        }
+    }
+
+    pub fn parse(spec: &str, strategy: ShardStrategy) -> Result<Self, String> {
+        let (index, count) = spec
+            .split_once('/')
+            .ok_or_else(|| format!("Invalid shard '{}', expected index/count", spec))?;
+
+        let index: u32 = index
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid shard index in '{}'", spec))?;
+        let count: u32 = count
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid shard count in '{}'", spec))?;
+
+        if count == 0 || index >= count {
+            return Err(format!("Shard index must be below shard count in '{}'", spec));
+        }
+
+        Ok(Self {
+            index,
+            count,
+            strategy,
+        })
+    }
+
+    pub fn is_sharded(&self) -> bool {
+        self.count > 1
+    }
+
+    /// Sources this shard collects from
+    pub fn sources(&self, sources: &[MarketSource]) -> Vec<MarketSource> {
+        match self.strategy {
+            ShardStrategy::Source => sources
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i as u32 % self.count == self.index)
+                .map(|(_, s)| *s)
+                .collect(),
+            ShardStrategy::Market => sources.to_vec(),
+        }
+    }
+
+    /// Whether this shard is responsible for recording the venue market `source_id`
+    pub fn owns_market(&self, source: MarketSource, source_id: &str) -> bool {
+        match self.strategy {
+            ShardStrategy::Source => true,
+            ShardStrategy::Market => {
+                let key = format!("{}:{}", source, source_id);
+                (fnv1a(key.as_bytes()) % self.count as u64) as u32 == self.index
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// Stable hash so every replica assigns a market to the same shard
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_index_and_count() {
+        let shard = Shard::parse(" 1 / 3 ", ShardStrategy::Market).unwrap();
+        assert_eq!((shard.index, shard.count), (1, 3));
+        assert_eq!(shard.to_string(), "1/3");
+        assert!(shard.is_sharded());
+        assert!(!Shard::parse("0/1", ShardStrategy::Source).unwrap().is_sharded());
+
+        for bad in ["", "1", "a/2", "0/b", "0/0", "2/2", "-1/2"] {
+            assert!(Shard::parse(bad, ShardStrategy::Source).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn source_shards_split_the_venues() {
+        let shards: Vec<Shard> = (0..2)
+            .map(|i| Shard::parse(&format!("{}/2", i), ShardStrategy::Source).unwrap())
+            .collect();
+
+        assert_eq!(shards[0].sources(&MarketSource::ALL), vec![MarketSource::ALL[0]]);
+        assert_eq!(shards[1].sources(&MarketSource::ALL), vec![MarketSource::ALL[1]]);
+        // More shards than venues leaves some idle
+        let idle = Shard::parse("2/3", ShardStrategy::Source).unwrap();
+        assert!(idle.sources(&MarketSource::ALL).is_empty());
+        assert!(shards[1].owns_market(MarketSource::Kalshi, "ANY"));
+    }
+
+    #[test]
+    fn market_shards_own_each_market_exactly_once() {
+        let shards: Vec<Shard> = (0..3)
+            .map(|i| Shard::parse(&format!("{}/3", i), ShardStrategy::Market).unwrap())
+            .collect();
+
+        for shard in &shards {
+            assert_eq!(shard.sources(&MarketSource::ALL), MarketSource::ALL.to_vec());
+        }
+
+        let mut per_shard = [0; 3];
+        for i in 0..300 {
+            let id = format!("MKT-{}", i);
+            let owners: Vec<u32> = shards
+                .iter()
+                .filter(|s| s.owns_market(MarketSource::Kalshi, &id))
+                .map(|s| s.index)
+                .collect();
+            assert_eq!(owners.len(), 1, "{}", id);
+            per_shard[owners[0] as usize] += 1;
+        }
+        // Roughly even
+        assert!(per_shard.iter().all(|&n| n > 50), "{:?}", per_shard);
+    }
+}
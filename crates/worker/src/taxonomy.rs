@@ -0,0 +1,302 @@
+use anyhow::Result;
+use pm_shared::store::{MarketFilter, MarketSort, MarketStore, SortOrder};
+use pm_shared::{Category, MarketSource};
+use std::sync::Arc;
+
+/// Markets loaded per page when backfilling
+const PAGE_SIZE: i64 = 500;
+
+/// Kalshi's category names, lowercased
+const KALSHI_CATEGORIES: &[(&str, Category)] = &[
+    ("politics", Category::Politics),
+    ("elections", Category::Politics),
+    ("economics", Category::Economics),
+    ("financials", Category::Finance),
+    ("companies", Category::Finance),
+    ("crypto", Category::Crypto),
+    ("sports", Category::Sports),
+    ("science and technology", Category::Science),
+    ("climate and weather", Category::Climate),
+    ("health", Category::Health),
+    ("entertainment", Category::Culture),
+    ("social", Category::Culture),
+    ("mentions", Category::Culture),
+    ("world", Category::World),
+    ("transportation", Category::Other),
+];
+
+/// Polymarket's category names, lowercased
+const POLYMARKET_CATEGORIES: &[(&str, Category)] = &[
+    ("politics", Category::Politics),
+    ("us-current-affairs", Category::Politics),
+    ("elections", Category::Politics),
+    ("global politics", Category::World),
+    ("world", Category::World),
+    ("economy", Category::Economics),
+    ("business", Category::Finance),
+    ("crypto", Category::Crypto),
+    ("sports", Category::Sports),
+    ("nba playoffs", Category::Sports),
+    ("science", Category::Science),
+    ("tech", Category::Technology),
+    ("weather", Category::Climate),
+    ("coronavirus", Category::Health),
+    ("pop culture", Category::Culture),
+    ("pop-culture", Category::Culture),
+];
+
+/// An entity recognized in market titles
+struct TagRule {
+    /// `kind:name`, e.g. `country:us`
+    tag: &'static str,
+    /// Lowercase words or phrases naming it, matched on word boundaries
+    names: &'static [&'static str],
+    /// Category assumed when the venue gives none
+    implies: Option<Category>,
+}
+
+const fn rule(
+    tag: &'static str,
+    names: &'static [&'static str],
+    implies: Option<Category>,
+) -> TagRule {
+    TagRule { tag, names, implies }
+}
+
+const POLITICS: Option<Category> = Some(Category::Politics);
+const CRYPTO: Option<Category> = Some(Category::Crypto);
+const FINANCE: Option<Category> = Some(Category::Finance);
+
+const TAG_RULES: &[TagRule] = &[
+    // Countries
+    rule("country:us", &["united states", "u s", "usa"], None),
+    rule("country:uk", &["uk", "u k", "united kingdom", "britain", "british"], None),
+    rule("country:china", &["china", "chinese"], None),
+    rule("country:russia", &["russia", "russian"], None),
+    rule("country:ukraine", &["ukraine", "ukrainian"], None),
+    rule("country:israel", &["israel", "israeli"], None),
+    rule("country:iran", &["iran", "iranian"], None),
+    rule("country:india", &["india"], None),
+    rule("country:japan", &["japan", "japanese"], None),
+    rule("country:germany", &["germany", "german"], None),
+    rule("country:france", &["france", "french"], None),
+    rule("country:canada", &["canada", "canadian"], None),
+    rule("country:mexico", &["mexico", "mexican"], None),
+    rule("country:brazil", &["brazil", "brazilian"], None),
+    rule("country:taiwan", &["taiwan"], None),
+    rule("country:north-korea", &["north korea"], None),
+    rule("country:venezuela", &["venezuela"], None),
+    // People
+    rule("person:trump", &["trump"], POLITICS),
+    rule("person:biden", &["biden"], POLITICS),
+    rule("person:harris", &["kamala", "kamala harris"], POLITICS),
+    rule("person:vance", &["jd vance", "j d vance"], POLITICS),
+    rule("person:newsom", &["newsom"], POLITICS),
+    rule("person:desantis", &["desantis"], POLITICS),
+    rule("person:putin", &["putin"], POLITICS),
+    rule("person:zelensky", &["zelensky", "zelenskyy"], POLITICS),
+    rule("person:xi", &["xi jinping"], POLITICS),
+    rule("person:netanyahu", &["netanyahu"], POLITICS),
+    rule("person:modi", &["modi"], POLITICS),
+    rule("person:macron", &["macron"], POLITICS),
+    rule("person:starmer", &["starmer"], POLITICS),
+    rule("person:powell", &["powell"], Some(Category::Economics)),
+    rule("person:musk", &["musk"], None),
+    // Assets
+    rule("asset:btc", &["bitcoin", "btc"], CRYPTO),
+    rule("asset:eth", &["ethereum", "eth", "ether"], CRYPTO),
+    rule("asset:sol", &["solana"], CRYPTO),
+    rule("asset:xrp", &["xrp", "ripple"], CRYPTO),
+    rule("asset:doge", &["dogecoin"], CRYPTO),
+    rule("asset:spx", &["s p", "sp500", "spx"], FINANCE),
+    rule("asset:ndx", &["nasdaq"], FINANCE),
+    rule("asset:gold", &["gold"], FINANCE),
+    rule("asset:oil", &["oil", "crude", "wti", "brent"], FINANCE),
+    rule("asset:tsla", &["tesla", "tsla"], FINANCE),
+    rule("asset:nvda", &["nvidia", "nvda"], FINANCE),
+];
+
+/// Canonical category and tags derived for a market
+#[derive(Debug, Clone, PartialEq)]
+pub struct Classification {
+    pub category: Option<Category>,
+    /// `None` when the title names no known entity
+    pub tags: Option<Vec<String>>,
+}
+
+/// Classify a market from the category its venue sent and its title.
+///
+/// The venue's category is looked up in that venue's table, then taken as is
+/// if it already names a canonical category. Failing that, the first tagged
+/// entity that implies a category decides, and a venue category nothing
+/// recognizes becomes `other`.
+pub fn classify(
+    source: MarketSource,
+    source_category: Option<&str>,
+    title: &str,
+) -> Classification {
+    let rules = matching_rules(title);
+
+    let category = source_category
+        .and_then(|raw| venue_category(source, raw))
+        .or_else(|| rules.iter().find_map(|r| r.implies))
+        .or(source_category.map(|_| Category::Other));
+
+    let tags: Vec<String> = rules.iter().map(|r| r.tag.to_string()).collect();
+
+    Classification {
+        category,
+        tags: (!tags.is_empty()).then_some(tags),
+    }
+}
+
+/// Map a venue's category onto the canonical set
+fn venue_category(source: MarketSource, raw: &str) -> Option<Category> {
+    let table = match source {
+        MarketSource::Kalshi => KALSHI_CATEGORIES,
+        MarketSource::Polymarket => POLYMARKET_CATEGORIES,
+    };
+    let raw = raw.trim().to_lowercase();
+
+    table
+        .iter()
+        .find(|(name, _)| *name == raw)
+        .map(|(_, category)| *category)
+        .or_else(|| raw.parse().ok())
+}
+
+/// Rules naming an entity in `title`, in table order
+fn matching_rules(title: &str) -> Vec<&'static TagRule> {
+    // " word word ... " so names only match whole words
+    let words: Vec<String> = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect();
+    let text = format!(" {} ", words.join(" "));
+
+    TAG_RULES
+        .iter()
+        .filter(|rule| {
+            rule.names
+                .iter()
+                .any(|name| text.contains(&format!(" {} ", name)))
+        })
+        .collect()
+}
+
+/// Re-derive every market's category and tags from its stored venue category
+/// and title, returning how many changed. With `dry_run` nothing is written.
+pub async fn backfill(markets: Arc<dyn MarketStore>, dry_run: bool) -> Result<usize> {
+    let everything = MarketFilter::default();
+    let mut offset = 0;
+    let mut changed = 0;
+
+    loop {
+        let page = markets
+            .list(PAGE_SIZE, offset, MarketSort::CreatedAt, SortOrder::Asc, &everything)
+            .await?;
+
+        for market in &page {
+            let source_category = market.source_category.as_deref();
+            let derived = classify(market.source, source_category, &market.title);
+            if derived.category == market.category && derived.tags == market.tags {
+                continue;
+            }
+
+            changed += 1;
+            if !dry_run {
+                markets
+                    .set_taxonomy(market.id, derived.category, derived.tags.as_deref())
+                    .await?;
+            }
+        }
+
+        if (page.len() as i64) < PAGE_SIZE {
+            break;
+        }
+        offset += PAGE_SIZE;
+    }
+
+    tracing::info!("Taxonomy: {} markets re-tagged", changed);
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::store::MemoryStore;
+    use pm_shared::{CreateMarket, Decimal, MarketStatus};
+
+    #[test]
+    fn maps_venue_categories_onto_the_canonical_set() {
+        let kalshi = classify(MarketSource::Kalshi, Some("Elections"), "Who wins the Senate?");
+        let polymarket = classify(MarketSource::Polymarket, Some("politics"), "Senate control");
+        assert_eq!(kalshi.category, Some(Category::Politics));
+        assert_eq!(polymarket.category, Some(Category::Politics));
+
+        let unknown = classify(MarketSource::Kalshi, Some("Miscellany"), "Anything");
+        assert_eq!(unknown.category, Some(Category::Other));
+        assert_eq!(classify(MarketSource::Kalshi, None, "Anything").category, None);
+    }
+
+    #[test]
+    fn tags_entities_named_in_the_title() {
+        let tagged = classify(
+            MarketSource::Polymarket,
+            None,
+            "Will Bitcoin hit $150k before Trump's tariffs on China?",
+        );
+        assert_eq!(
+            tagged.tags,
+            Some(vec![
+                "country:china".to_string(),
+                "person:trump".to_string(),
+                "asset:btc".to_string(),
+            ])
+        );
+        // No venue category, so the first entity implying one decides
+        assert_eq!(tagged.category, Some(Category::Politics));
+
+        // Whole words only
+        let untagged = classify(MarketSource::Kalshi, Some("Sports"), "Golden State win the title");
+        assert_eq!(untagged.tags, None);
+        assert_eq!(untagged.category, Some(Category::Sports));
+    }
+
+    #[tokio::test]
+    async fn backfills_markets_stored_before_classification() {
+        let store = Arc::new(MemoryStore::new());
+        let market = store
+            .upsert(CreateMarket {
+                source_id: "KXBTC".into(),
+                source: MarketSource::Kalshi,
+                title: "Bitcoin above $100k on Friday?".into(),
+                description: String::new(),
+                category: None,
+                source_category: Some("Crypto".into()),
+                tags: None,
+                yes_price: Decimal::new(5, 1),
+                no_price: Decimal::new(5, 1),
+                volume: Decimal::ZERO,
+                volume_24h: Decimal::ZERO,
+                liquidity: None,
+                status: MarketStatus::Open,
+                close_at: None,
+                url: String::new(),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(backfill(store.clone(), true).await.unwrap(), 1);
+        assert_eq!(store.get_by_id(market.id).await.unwrap().category, None);
+
+        assert_eq!(backfill(store.clone(), false).await.unwrap(), 1);
+        let market = store.get_by_id(market.id).await.unwrap();
+        assert_eq!(market.category, Some(Category::Crypto));
+        assert_eq!(market.tags, Some(vec!["asset:btc".to_string()]));
+
+        assert_eq!(backfill(store, false).await.unwrap(), 0);
+    }
+}
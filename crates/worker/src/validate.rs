@@ -0,0 +1,202 @@
+use chrono::Utc;
+use pm_shared::{CreateMarket, Decimal, MarketSource, QuarantinedMarket};
+use uuid::Uuid;
+
+use crate::collectors::Collected;
+
+/// How far `yes_price + no_price` may drift from 1 (spreads, stale quotes)
+const PRICE_SUM_TOLERANCE: Decimal = Decimal::from_parts(5, 0, 0, false, 2);
+
+/// A failed validation check
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Stable name used to group rejections, e.g. `price_range`
+    pub check: &'static str,
+    pub detail: String,
+}
+
+impl Issue {
+    pub fn new(check: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            check,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Per-field and cross-field checks a converted market must pass to be recorded
+pub fn validate(market: &CreateMarket) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    if market.source_id.trim().is_empty() {
+        issues.push(Issue::new("missing_field", "source_id is empty"));
+    }
+    if market.title.trim().is_empty() {
+        issues.push(Issue::new("missing_field", "title is empty"));
+    }
+
+    for (field, price) in [("yes_price", market.yes_price), ("no_price", market.no_price)] {
+        if price < Decimal::ZERO || price > Decimal::ONE {
+            issues.push(Issue::new(
+                "price_range",
+                format!("{} {} is outside [0, 1]", field, price),
+            ));
+        }
+    }
+
+    let amounts = [
+        ("volume", Some(market.volume)),
+        ("volume_24h", Some(market.volume_24h)),
+        ("liquidity", market.liquidity),
+    ];
+    for (field, amount) in amounts {
+        if let Some(amount) = amount.filter(|a| *a < Decimal::ZERO) {
+            issues.push(Issue::new(
+                "negative_amount",
+                format!("{} {} is negative", field, amount),
+            ));
+        }
+    }
+
+    let sum = market.yes_price + market.no_price;
+    if (sum - Decimal::ONE).abs() > PRICE_SUM_TOLERANCE {
+        issues.push(Issue::new(
+            "price_sum",
+            format!(
+                "yes {} + no {} = {}, more than {} from 1",
+                market.yes_price, market.no_price, sum, PRICE_SUM_TOLERANCE
+            ),
+        ));
+    }
+
+    if market.volume_24h > market.volume {
+        issues.push(Issue::new(
+            "volume_24h_exceeds_total",
+            format!(
+                "24h volume {} exceeds total volume {}",
+                market.volume_24h, market.volume
+            ),
+        ));
+    }
+
+    issues
+}
+
+/// Split collected markets into those fit to record and those to quarantine
+pub fn screen(
+    source: MarketSource,
+    collected: Vec<Collected>,
+) -> (Vec<CreateMarket>, Vec<QuarantinedMarket>) {
+    let mut markets = Vec::new();
+    let mut quarantined = Vec::new();
+    let now = Utc::now();
+
+    for item in collected {
+        let issues = match &item.market {
+            Ok(market) => validate(market),
+            Err(issues) => issues.clone(),
+        };
+
+        match item.market {
+            Ok(market) if issues.is_empty() => markets.push(market),
+            _ => quarantined.push(QuarantinedMarket {
+                id: Uuid::new_v4(),
+                source,
+                source_id: item.source_id,
+                checks: issues.iter().map(|i| i.check.to_string()).collect(),
+                reason: issues
+                    .iter()
+                    .map(|i| i.detail.as_str())
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                payload: item.payload,
+                quarantined_at: now,
+                last_seen_at: now,
+            }),
+        }
+    }
+
+    (markets, quarantined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pm_shared::MarketStatus;
+
+    fn market(yes: Decimal, no: Decimal) -> CreateMarket {
+        CreateMarket {
+            source_id: "FED".to_string(),
+            source: MarketSource::Kalshi,
+            title: "Fed cuts rates".to_string(),
+            description: String::new(),
+            category: None,
+            source_category: None,
+            tags: None,
+            yes_price: yes,
+            no_price: no,
+            volume: Decimal::from(100),
+            volume_24h: Decimal::from(10),
+            liquidity: None,
+            status: MarketStatus::Open,
+            close_at: None,
+            url: String::new(),
+        }
+    }
+
+    fn checks(market: &CreateMarket) -> Vec<&'static str> {
+        validate(market).into_iter().map(|i| i.check).collect()
+    }
+
+    #[test]
+    fn accepts_consistent_market() {
+        // A 2 cent spread is within tolerance
+        assert!(validate(&market(Decimal::new(51, 2), Decimal::new(51, 2))).is_empty());
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_inconsistent_prices() {
+        assert_eq!(
+            checks(&market(Decimal::new(15, 1), Decimal::new(-5, 1))),
+            vec!["price_range", "price_range"]
+        );
+        assert_eq!(
+            checks(&market(Decimal::new(8, 1), Decimal::new(8, 1))),
+            vec!["price_sum"]
+        );
+    }
+
+    #[test]
+    fn rejects_negative_and_inconsistent_volumes() {
+        let mut negative = market(Decimal::new(5, 1), Decimal::new(5, 1));
+        negative.volume = Decimal::from(-1);
+        assert_eq!(
+            checks(&negative),
+            vec!["negative_amount", "volume_24h_exceeds_total"]
+        );
+    }
+
+    #[test]
+    fn screen_quarantines_failures_with_payload() {
+        let payload = serde_json::json!({ "ticker": "BAD" });
+        let collected = vec![
+            Collected {
+                source_id: Some("FED".to_string()),
+                payload: serde_json::json!({ "ticker": "FED" }),
+                market: Ok(market(Decimal::new(5, 1), Decimal::new(5, 1))),
+            },
+            Collected {
+                source_id: Some("BAD".to_string()),
+                payload: payload.clone(),
+                market: Err(vec![Issue::new("missing_price", "no yes bid or ask")]),
+            },
+        ];
+
+        let (markets, quarantined) = screen(MarketSource::Kalshi, collected);
+
+        assert_eq!(markets.len(), 1);
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(quarantined[0].checks, vec!["missing_price"]);
+        assert_eq!(quarantined[0].payload, payload);
+    }
+}
@@ -41,14 +41,14 @@ CREATE TABLE IF NOT EXISTS markets (
 );
 
 -- Performance indexes
-CREATE INDEX idx_markets_source ON markets(source);
-CREATE INDEX idx_markets_status ON markets(status);
-CREATE INDEX idx_markets_volume ON markets(volume DESC);
-CREATE INDEX idx_markets_created ON markets(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_markets_source ON markets(source);
+CREATE INDEX IF NOT EXISTS idx_markets_status ON markets(status);
+CREATE INDEX IF NOT EXISTS idx_markets_volume ON markets(volume DESC);
+CREATE INDEX IF NOT EXISTS idx_markets_created ON markets(created_at DESC);
 
 -- Full-text search index for fuzzy matching
-CREATE INDEX idx_markets_title_search ON markets USING GIN(to_tsvector('english', title));
-CREATE INDEX idx_markets_desc_search ON markets USING GIN(to_tsvector('english', description));
+CREATE INDEX IF NOT EXISTS idx_markets_title_search ON markets USING GIN(to_tsvector('english', title));
+CREATE INDEX IF NOT EXISTS idx_markets_desc_search ON markets USING GIN(to_tsvector('english', description));
 
 -- Function to update updated_at timestamp
 CREATE OR REPLACE FUNCTION update_updated_at_column()
@@ -60,6 +60,7 @@ END;
 $$ language 'plpgsql';
 
 -- Trigger to automatically update updated_at
+DROP TRIGGER IF EXISTS update_markets_updated_at ON markets;
 CREATE TRIGGER update_markets_updated_at
     BEFORE UPDATE ON markets
     FOR EACH ROW
@@ -27,14 +27,10 @@ CREATE TABLE IF NOT EXISTS price_history (
 
 -- Critical time-series indexes
 -- Primary index for querying a market's history
-CREATE INDEX idx_price_history_market_time ON price_history(market_id, recorded_at DESC);
-
--- Partial index for recent data (30 days) - most common queries
-CREATE INDEX idx_price_history_recent ON price_history(market_id, recorded_at DESC)
-    WHERE recorded_at > NOW() - INTERVAL '30 days';
+CREATE INDEX IF NOT EXISTS idx_price_history_market_time ON price_history(market_id, recorded_at DESC);
 
 -- Index for cleanup queries (finding old records)
-CREATE INDEX idx_price_history_recorded ON price_history(recorded_at DESC);
+CREATE INDEX IF NOT EXISTS idx_price_history_recorded ON price_history(recorded_at DESC);
 
 -- Comments for documentation
 COMMENT ON TABLE price_history IS 'Time-series snapshots of market prices and metrics';
@@ -0,0 +1,36 @@
+-- Migration: Create collection_runs table
+-- Audit log of worker collection cycles and their per-source steps
+-- Used to find and explain coverage gaps in price_history
+
+CREATE TABLE IF NOT EXISTS collection_runs (
+    -- Primary key
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- 'cycle' rows have one 'source' row per venue collected in that cycle
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('cycle', 'source')),
+    parent_id UUID REFERENCES collection_runs(id) ON DELETE CASCADE,
+    source VARCHAR(50),
+
+    -- Timing (finished_at stays NULL if the worker died mid-cycle)
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMPTZ,
+
+    -- Outcome
+    markets_fetched INTEGER NOT NULL DEFAULT 0,
+    markets_recorded INTEGER NOT NULL DEFAULT 0,
+    markets_failed INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    http_status INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_runs_started ON collection_runs(started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_collection_runs_parent ON collection_runs(parent_id);
+
+-- Comments for documentation
+COMMENT ON TABLE collection_runs IS 'Audit log of worker collection cycles and per-source steps';
+COMMENT ON COLUMN collection_runs.kind IS 'cycle: whole collection cycle, source: one venue within a cycle';
+COMMENT ON COLUMN collection_runs.parent_id IS 'Cycle this source step belongs to (NULL for cycles)';
+COMMENT ON COLUMN collection_runs.source IS 'Platform collected in this step (NULL for cycles)';
+COMMENT ON COLUMN collection_runs.markets_failed IS 'Markets fetched but not recorded';
+COMMENT ON COLUMN collection_runs.error IS 'Error text, or why the cycle was interrupted';
+COMMENT ON COLUMN collection_runs.http_status IS 'Venue HTTP status when the fetch failed with one';
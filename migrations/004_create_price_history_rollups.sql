@@ -0,0 +1,61 @@
+-- Migration: Create price_history rollup tables
+-- Hourly and daily OHLC rollups of price_history, filled by the worker's
+-- retention job so raw snapshots can be deleted past a retention window
+
+CREATE TABLE IF NOT EXISTS price_history_hourly (
+    -- Stable identifier so rollup rows can be served as history snapshots
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+
+    market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+    bucket TIMESTAMPTZ NOT NULL,
+
+    -- YES price OHLC, NO price at close
+    open_yes_price REAL NOT NULL,
+    high_yes_price REAL NOT NULL,
+    low_yes_price REAL NOT NULL,
+    close_yes_price REAL NOT NULL,
+    close_no_price REAL NOT NULL,
+
+    -- Last observed volume and liquidity in the bucket
+    volume REAL NOT NULL DEFAULT 0,
+    volume_24h REAL NOT NULL DEFAULT 0,
+    liquidity REAL,
+
+    sample_count INTEGER NOT NULL,
+
+    PRIMARY KEY (market_id, bucket)
+);
+
+CREATE TABLE IF NOT EXISTS price_history_daily (
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+
+    market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+    bucket TIMESTAMPTZ NOT NULL,
+
+    open_yes_price REAL NOT NULL,
+    high_yes_price REAL NOT NULL,
+    low_yes_price REAL NOT NULL,
+    close_yes_price REAL NOT NULL,
+    close_no_price REAL NOT NULL,
+
+    volume REAL NOT NULL DEFAULT 0,
+    volume_24h REAL NOT NULL DEFAULT 0,
+    liquidity REAL,
+
+    sample_count INTEGER NOT NULL,
+
+    PRIMARY KEY (market_id, bucket)
+);
+
+-- Used by the retention job to find the rollup watermark and expired buckets
+CREATE INDEX IF NOT EXISTS idx_price_history_hourly_bucket ON price_history_hourly(bucket DESC);
+CREATE INDEX IF NOT EXISTS idx_price_history_daily_bucket ON price_history_daily(bucket DESC);
+
+-- Comments for documentation
+COMMENT ON TABLE price_history_hourly IS 'Hourly OHLC rollup of price_history';
+COMMENT ON TABLE price_history_daily IS 'Daily OHLC rollup of price_history_hourly';
+COMMENT ON COLUMN price_history_hourly.bucket IS 'Start of the hour (UTC)';
+COMMENT ON COLUMN price_history_daily.bucket IS 'Start of the day (UTC)';
+COMMENT ON COLUMN price_history_hourly.volume IS 'Last total volume observed in the bucket';
+COMMENT ON COLUMN price_history_hourly.sample_count IS 'Raw snapshots rolled into this bucket';
+COMMENT ON COLUMN price_history_daily.sample_count IS 'Raw snapshots rolled into this bucket';
@@ -0,0 +1,114 @@
+-- Migration: Partition price_history by month
+-- Converts price_history into a table range-partitioned on recorded_at with one
+-- partition per calendar month (UTC). The worker creates partitions ahead of
+-- time and detaches or drops expired ones.
+
+-- The view depends on price_history and is recreated below
+DROP VIEW IF EXISTS market_price_changes;
+
+-- Move the existing table and its constraint/index names out of the way
+ALTER TABLE price_history RENAME TO price_history_legacy;
+ALTER INDEX price_history_pkey RENAME TO price_history_legacy_pkey;
+ALTER INDEX price_history_market_id_recorded_at_key RENAME TO price_history_legacy_market_id_recorded_at_key;
+DROP INDEX IF EXISTS idx_price_history_market_time;
+DROP INDEX IF EXISTS idx_price_history_recent;
+DROP INDEX IF EXISTS idx_price_history_recorded;
+
+CREATE TABLE price_history (
+    -- Primary key (must include the partition key)
+    id UUID NOT NULL DEFAULT gen_random_uuid(),
+
+    -- Foreign key to markets
+    market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+
+    -- Price snapshot
+    yes_price REAL NOT NULL,
+    no_price REAL NOT NULL,
+
+    -- Volume and liquidity at snapshot time
+    volume REAL NOT NULL DEFAULT 0,
+    volume_24h REAL NOT NULL DEFAULT 0,
+    liquidity REAL,
+
+    -- Snapshot timestamp (partition key)
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (id, recorded_at),
+
+    -- Ensure no duplicate snapshots for same market at same time
+    -- (also serves a market's history queries)
+    UNIQUE (market_id, recorded_at)
+) PARTITION BY RANGE (recorded_at);
+
+-- Used by the retention job's rollups
+CREATE INDEX IF NOT EXISTS idx_price_history_recorded ON price_history(recorded_at DESC);
+
+-- Catches rows outside every monthly partition so inserts never fail
+CREATE TABLE IF NOT EXISTS price_history_default PARTITION OF price_history DEFAULT;
+
+-- Create the partition holding `month` (any date within it), named price_history_YYYY_MM
+CREATE OR REPLACE FUNCTION create_price_history_partition(month DATE)
+RETURNS TEXT AS $$
+DECLARE
+    start_at TIMESTAMPTZ := date_trunc('month', month::TIMESTAMP) AT TIME ZONE 'UTC';
+    end_at TIMESTAMPTZ := (date_trunc('month', month::TIMESTAMP) + INTERVAL '1 month') AT TIME ZONE 'UTC';
+    partition_name TEXT := 'price_history_' || to_char(month, 'YYYY_MM');
+BEGIN
+    IF to_regclass(partition_name) IS NULL THEN
+        EXECUTE format(
+            'CREATE TABLE %I PARTITION OF price_history FOR VALUES FROM (%L) TO (%L)',
+            partition_name, start_at, end_at
+        );
+    END IF;
+    RETURN partition_name;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Partitions for existing data through two months ahead
+SELECT create_price_history_partition(month::DATE)
+FROM generate_series(
+    date_trunc('month', COALESCE((SELECT MIN(recorded_at) FROM price_history_legacy), NOW()) AT TIME ZONE 'UTC'),
+    date_trunc('month', NOW() AT TIME ZONE 'UTC') + INTERVAL '2 months',
+    INTERVAL '1 month'
+) AS month;
+
+INSERT INTO price_history (id, market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at)
+SELECT id, market_id, yes_price, no_price, volume, volume_24h, liquidity, recorded_at
+FROM price_history_legacy;
+
+DROP TABLE price_history_legacy;
+
+-- Comments for documentation
+COMMENT ON TABLE price_history IS 'Time-series snapshots of market prices and metrics, partitioned monthly';
+COMMENT ON COLUMN price_history.market_id IS 'Reference to the market being tracked';
+COMMENT ON COLUMN price_history.yes_price IS 'YES outcome price at snapshot time (0.0-1.0)';
+COMMENT ON COLUMN price_history.no_price IS 'NO outcome price at snapshot time (0.0-1.0)';
+COMMENT ON COLUMN price_history.volume IS 'Total volume at snapshot time in USD';
+COMMENT ON COLUMN price_history.volume_24h IS '24-hour volume at snapshot time in USD';
+COMMENT ON COLUMN price_history.liquidity IS 'Liquidity at snapshot time in USD';
+COMMENT ON COLUMN price_history.recorded_at IS 'When this snapshot was taken (partition key)';
+COMMENT ON FUNCTION create_price_history_partition(DATE) IS 'Create the monthly price_history partition containing the given date';
+
+-- View for recent price changes
+CREATE OR REPLACE VIEW market_price_changes AS
+SELECT
+    m.id,
+    m.title,
+    m.source,
+    m.yes_price as current_yes_price,
+    m.no_price as current_no_price,
+    ph_24h.yes_price as yes_price_24h_ago,
+    ph_24h.no_price as no_price_24h_ago,
+    (m.yes_price - ph_24h.yes_price) as yes_price_change_24h,
+    (m.no_price - ph_24h.no_price) as no_price_change_24h
+FROM markets m
+LEFT JOIN LATERAL (
+    SELECT yes_price, no_price
+    FROM price_history
+    WHERE market_id = m.id
+      AND recorded_at <= NOW() - INTERVAL '24 hours'
+    ORDER BY recorded_at DESC
+    LIMIT 1
+) ph_24h ON true;
+
+COMMENT ON VIEW market_price_changes IS '24-hour price change summary for all markets';
@@ -0,0 +1,28 @@
+-- Migration: Create market_revisions table
+-- Field-level history of market metadata overwritten by collector upserts
+-- Used to reconstruct a market as it was at a past moment (as_of queries)
+
+CREATE TABLE IF NOT EXISTS market_revisions (
+    -- Primary key
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- Foreign key to markets
+    market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+
+    -- Which field changed, and its value before and after (NULL when unset)
+    field VARCHAR(50) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+
+    -- When the change was observed
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_revisions_market_time ON market_revisions(market_id, changed_at);
+
+-- Comments for documentation
+COMMENT ON TABLE market_revisions IS 'Field-level changes to market metadata, one row per changed field';
+COMMENT ON COLUMN market_revisions.field IS 'Changed markets column (title, status, close_at)';
+COMMENT ON COLUMN market_revisions.old_value IS 'Value before the change, as text (timestamps in RFC 3339)';
+COMMENT ON COLUMN market_revisions.new_value IS 'Value after the change, as text (timestamps in RFC 3339)';
+COMMENT ON COLUMN market_revisions.changed_at IS 'When the collector observed the change';
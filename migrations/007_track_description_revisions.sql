@@ -0,0 +1,4 @@
+-- Migration: Track description changes in market_revisions
+-- Venues edit resolution criteria in place; the worker now records those too
+
+COMMENT ON COLUMN market_revisions.field IS 'Changed markets column (title, description, status, close_at)';
@@ -0,0 +1,38 @@
+-- Migration: Canonical market statuses and sources
+-- Collectors used to store venue statuses verbatim (Kalshi "active",
+-- "finalized", ...). Map them onto open/closed/resolved, including the values
+-- already recorded in market_revisions, and reject anything else from now on.
+
+UPDATE markets SET source = lower(source) WHERE source <> lower(source);
+
+UPDATE markets
+SET status = CASE
+    WHEN lower(status) IN ('open', 'active') THEN 'open'
+    WHEN lower(status) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+    ELSE 'closed'
+END
+WHERE status NOT IN ('open', 'closed', 'resolved');
+
+UPDATE market_revisions
+SET old_value = CASE
+        WHEN lower(old_value) IN ('open', 'active') THEN 'open'
+        WHEN lower(old_value) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+        ELSE 'closed'
+    END
+WHERE field = 'status' AND old_value NOT IN ('open', 'closed', 'resolved');
+
+UPDATE market_revisions
+SET new_value = CASE
+        WHEN lower(new_value) IN ('open', 'active') THEN 'open'
+        WHEN lower(new_value) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+        ELSE 'closed'
+    END
+WHERE field = 'status' AND new_value NOT IN ('open', 'closed', 'resolved');
+
+-- Revisions that only renamed a venue status no longer change anything
+DELETE FROM market_revisions
+WHERE field = 'status' AND old_value IS NOT DISTINCT FROM new_value;
+
+ALTER TABLE markets
+    ADD CONSTRAINT markets_status_check CHECK (status IN ('open', 'closed', 'resolved')),
+    ADD CONSTRAINT markets_source_check CHECK (source IN ('polymarket', 'kalshi'));
@@ -0,0 +1,66 @@
+-- Migration: Exact prices and volumes
+-- REAL (float4) kept ~7 significant digits: volumes in the hundreds of
+-- millions lost whole dollars and cent prices came back as 0.6499999.
+-- Prices become NUMERIC(7, 6) and USD amounts NUMERIC(20, 2). Existing values
+-- go through float8 so the stored float is converted exactly before rounding.
+
+-- The view depends on the price columns; recreated below unchanged
+DROP VIEW IF EXISTS market_price_changes;
+
+ALTER TABLE markets
+    ALTER COLUMN yes_price TYPE NUMERIC(7, 6) USING round(yes_price::float8::numeric, 6),
+    ALTER COLUMN no_price TYPE NUMERIC(7, 6) USING round(no_price::float8::numeric, 6),
+    ALTER COLUMN volume TYPE NUMERIC(20, 2) USING round(volume::float8::numeric, 2),
+    ALTER COLUMN volume_24h TYPE NUMERIC(20, 2) USING round(volume_24h::float8::numeric, 2),
+    ALTER COLUMN liquidity TYPE NUMERIC(20, 2) USING round(liquidity::float8::numeric, 2);
+
+-- Altering the partitioned parent rewrites every partition
+ALTER TABLE price_history
+    ALTER COLUMN yes_price TYPE NUMERIC(7, 6) USING round(yes_price::float8::numeric, 6),
+    ALTER COLUMN no_price TYPE NUMERIC(7, 6) USING round(no_price::float8::numeric, 6),
+    ALTER COLUMN volume TYPE NUMERIC(20, 2) USING round(volume::float8::numeric, 2),
+    ALTER COLUMN volume_24h TYPE NUMERIC(20, 2) USING round(volume_24h::float8::numeric, 2),
+    ALTER COLUMN liquidity TYPE NUMERIC(20, 2) USING round(liquidity::float8::numeric, 2);
+
+ALTER TABLE price_history_hourly
+    ALTER COLUMN open_yes_price TYPE NUMERIC(7, 6) USING round(open_yes_price::float8::numeric, 6),
+    ALTER COLUMN high_yes_price TYPE NUMERIC(7, 6) USING round(high_yes_price::float8::numeric, 6),
+    ALTER COLUMN low_yes_price TYPE NUMERIC(7, 6) USING round(low_yes_price::float8::numeric, 6),
+    ALTER COLUMN close_yes_price TYPE NUMERIC(7, 6) USING round(close_yes_price::float8::numeric, 6),
+    ALTER COLUMN close_no_price TYPE NUMERIC(7, 6) USING round(close_no_price::float8::numeric, 6),
+    ALTER COLUMN volume TYPE NUMERIC(20, 2) USING round(volume::float8::numeric, 2),
+    ALTER COLUMN volume_24h TYPE NUMERIC(20, 2) USING round(volume_24h::float8::numeric, 2),
+    ALTER COLUMN liquidity TYPE NUMERIC(20, 2) USING round(liquidity::float8::numeric, 2);
+
+ALTER TABLE price_history_daily
+    ALTER COLUMN open_yes_price TYPE NUMERIC(7, 6) USING round(open_yes_price::float8::numeric, 6),
+    ALTER COLUMN high_yes_price TYPE NUMERIC(7, 6) USING round(high_yes_price::float8::numeric, 6),
+    ALTER COLUMN low_yes_price TYPE NUMERIC(7, 6) USING round(low_yes_price::float8::numeric, 6),
+    ALTER COLUMN close_yes_price TYPE NUMERIC(7, 6) USING round(close_yes_price::float8::numeric, 6),
+    ALTER COLUMN close_no_price TYPE NUMERIC(7, 6) USING round(close_no_price::float8::numeric, 6),
+    ALTER COLUMN volume TYPE NUMERIC(20, 2) USING round(volume::float8::numeric, 2),
+    ALTER COLUMN volume_24h TYPE NUMERIC(20, 2) USING round(volume_24h::float8::numeric, 2),
+    ALTER COLUMN liquidity TYPE NUMERIC(20, 2) USING round(liquidity::float8::numeric, 2);
+
+CREATE OR REPLACE VIEW market_price_changes AS
+SELECT
+    m.id,
+    m.title,
+    m.source,
+    m.yes_price as current_yes_price,
+    m.no_price as current_no_price,
+    ph_24h.yes_price as yes_price_24h_ago,
+    ph_24h.no_price as no_price_24h_ago,
+    (m.yes_price - ph_24h.yes_price) as yes_price_change_24h,
+    (m.no_price - ph_24h.no_price) as no_price_change_24h
+FROM markets m
+LEFT JOIN LATERAL (
+    SELECT yes_price, no_price
+    FROM price_history
+    WHERE market_id = m.id
+      AND recorded_at <= NOW() - INTERVAL '24 hours'
+    ORDER BY recorded_at DESC
+    LIMIT 1
+) ph_24h ON true;
+
+COMMENT ON VIEW market_price_changes IS '24-hour price change summary for all markets';
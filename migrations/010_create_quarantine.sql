@@ -0,0 +1,27 @@
+-- Migration: Create quarantine table
+-- Collected markets that failed validation are held here with the venue
+-- payload instead of being written to markets and price_history
+
+CREATE TABLE IF NOT EXISTS quarantine (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    source VARCHAR(50) NOT NULL CHECK (source IN ('polymarket', 'kalshi')),
+    source_id VARCHAR(255),
+
+    -- Why the market was rejected
+    checks TEXT[] NOT NULL,
+    reason TEXT NOT NULL,
+
+    payload JSONB NOT NULL,
+    quarantined_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantine_quarantined ON quarantine(quarantined_at DESC);
+CREATE INDEX IF NOT EXISTS idx_quarantine_source ON quarantine(source, quarantined_at DESC);
+
+-- Comments for documentation
+COMMENT ON TABLE quarantine IS 'Collected markets rejected by validation, with the raw venue payload';
+COMMENT ON COLUMN quarantine.source_id IS 'Venue market id (NULL when the payload had none)';
+COMMENT ON COLUMN quarantine.checks IS 'Names of the failed checks, e.g. price_range';
+COMMENT ON COLUMN quarantine.reason IS 'Every failure, human readable';
+COMMENT ON COLUMN quarantine.payload IS 'Market object exactly as returned by the venue API';
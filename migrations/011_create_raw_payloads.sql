@@ -0,0 +1,20 @@
+-- Migration: Create raw_payloads table
+-- Venue API responses archived by the worker (RAW_ARCHIVE=table), gzip
+-- compressed, so conversion fixes can be replayed with `pm-worker reprocess`
+
+CREATE TABLE IF NOT EXISTS raw_payloads (
+    source VARCHAR(50) NOT NULL CHECK (source IN ('polymarket', 'kalshi')),
+    fetched_at TIMESTAMPTZ NOT NULL,
+
+    -- Response body exactly as received, gzip compressed
+    body BYTEA NOT NULL,
+
+    PRIMARY KEY (source, fetched_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_raw_payloads_fetched ON raw_payloads(fetched_at);
+
+-- Comments for documentation
+COMMENT ON TABLE raw_payloads IS 'Archived venue API responses, replayable with pm-worker reprocess';
+COMMENT ON COLUMN raw_payloads.fetched_at IS 'When the response was fetched; snapshots from it are recorded at this time';
+COMMENT ON COLUMN raw_payloads.body IS 'Gzip-compressed response body';
@@ -0,0 +1,25 @@
+-- Migration: Create anomalies table
+-- Snapshots the worker scored as implausible against the market's recent
+-- history. Flagged points stay in price_history but are left out of the
+-- hourly/daily rollups and of history reads unless asked for.
+
+CREATE TABLE IF NOT EXISTS anomalies (
+    market_id UUID NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+    recorded_at TIMESTAMPTZ NOT NULL,
+
+    kind VARCHAR(50) NOT NULL CHECK (kind IN ('price_spike', 'volume_decrease', 'stale')),
+    score DOUBLE PRECISION,
+    detail TEXT NOT NULL,
+
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (market_id, recorded_at, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected ON anomalies(detected_at DESC);
+
+-- Comments for documentation
+COMMENT ON TABLE anomalies IS 'Price history snapshots flagged as anomalous, excluded from rollups';
+COMMENT ON COLUMN anomalies.recorded_at IS 'recorded_at of the flagged price_history row';
+COMMENT ON COLUMN anomalies.kind IS 'price_spike, volume_decrease or stale';
+COMMENT ON COLUMN anomalies.score IS 'z-score of the price change for price_spike, NULL otherwise';
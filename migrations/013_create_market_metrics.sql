@@ -0,0 +1,26 @@
+-- Migration: Create market_metrics table
+-- Volatility, momentum and volume metrics per market, recomputed
+-- periodically by the worker from hourly price history.
+
+CREATE TABLE IF NOT EXISTS market_metrics (
+    market_id UUID PRIMARY KEY REFERENCES markets(id) ON DELETE CASCADE,
+
+    volatility_24h DOUBLE PRECISION,
+    volatility_7d DOUBLE PRECISION,
+    volatility_30d DOUBLE PRECISION,
+    momentum_24h NUMERIC(7, 6),
+    momentum_7d NUMERIC(7, 6),
+    max_drawdown_30d NUMERIC(7, 6),
+    last_move_at TIMESTAMPTZ,
+    volume_acceleration DOUBLE PRECISION,
+
+    samples INTEGER NOT NULL DEFAULT 0,
+    computed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Comments for documentation
+COMMENT ON TABLE market_metrics IS 'Derived per-market metrics, one row per market';
+COMMENT ON COLUMN market_metrics.volatility_24h IS 'Realized volatility of hourly log-odds returns of the yes price';
+COMMENT ON COLUMN market_metrics.last_move_at IS 'When the yes price settled after its last move of 5 points or more';
+COMMENT ON COLUMN market_metrics.volume_acceleration IS 'Volume traded in the last 24h over the 24h before, minus one';
+COMMENT ON COLUMN market_metrics.samples IS 'Hourly closes the metrics were computed from';
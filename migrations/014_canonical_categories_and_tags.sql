@@ -0,0 +1,34 @@
+-- Migration: Canonical market categories and tags
+-- Collectors used to store each venue's category verbatim ("Politics",
+-- "Elections", "Financials", ...), so the same topic never lined up across
+-- venues. Keep the venue's value in source_category and hold category to the
+-- canonical set. Values that are not already canonical are cleared here;
+-- `pm-worker taxonomy` re-derives them, and title tags, from source_category.
+
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS source_category VARCHAR(255);
+
+UPDATE markets SET source_category = category WHERE source_category IS NULL;
+
+UPDATE markets
+SET category = CASE
+    WHEN lower(category) IN (
+        'politics', 'economics', 'finance', 'crypto', 'sports', 'science',
+        'technology', 'climate', 'health', 'culture', 'world', 'other'
+    ) THEN lower(category)
+    ELSE NULL
+END
+WHERE category IS NOT NULL;
+
+ALTER TABLE markets
+    ADD CONSTRAINT markets_category_check CHECK (category IN (
+        'politics', 'economics', 'finance', 'crypto', 'sports', 'science',
+        'technology', 'climate', 'health', 'culture', 'world', 'other'
+    ));
+
+CREATE INDEX IF NOT EXISTS idx_markets_category ON markets(category, volume DESC);
+CREATE INDEX IF NOT EXISTS idx_markets_tags ON markets USING GIN(tags);
+
+-- Comments for documentation
+COMMENT ON COLUMN markets.category IS 'Canonical category shared across venues';
+COMMENT ON COLUMN markets.source_category IS 'Category exactly as the venue sent it';
+COMMENT ON COLUMN markets.tags IS 'Entities found in the title, as kind:name (country:us, asset:btc)';
@@ -0,0 +1,47 @@
+-- Migration: One quarantine row per market and failed checks
+-- The worker used to insert a row, payload included, every cycle a market
+-- kept failing validation. Now a market failing the same checks again
+-- refreshes its row: quarantined_at stays the first rejection and
+-- last_seen_at moves to the latest. Payloads without a venue id share a
+-- row per set of checks.
+
+ALTER TABLE quarantine ADD COLUMN IF NOT EXISTS last_seen_at TIMESTAMPTZ;
+
+-- Keep the newest row of each group, spanning every earlier sighting
+WITH ranked AS (
+    SELECT
+        id,
+        ROW_NUMBER() OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+            ORDER BY quarantined_at DESC, id
+        ) AS position,
+        MIN(quarantined_at) OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+        ) AS first_at,
+        MAX(quarantined_at) OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+        ) AS last_at
+    FROM quarantine
+)
+UPDATE quarantine q
+SET quarantined_at = r.first_at, last_seen_at = r.last_at
+FROM ranked r
+WHERE q.id = r.id AND r.position = 1;
+
+DELETE FROM quarantine WHERE last_seen_at IS NULL;
+
+ALTER TABLE quarantine
+    ALTER COLUMN last_seen_at SET NOT NULL,
+    ALTER COLUMN last_seen_at SET DEFAULT NOW();
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quarantine_market_checks
+    ON quarantine(source, (COALESCE(source_id, '')), checks);
+
+-- Reads filter on when a market was last seen failing
+DROP INDEX IF EXISTS idx_quarantine_quarantined;
+DROP INDEX IF EXISTS idx_quarantine_source;
+CREATE INDEX IF NOT EXISTS idx_quarantine_last_seen ON quarantine(last_seen_at DESC);
+CREATE INDEX IF NOT EXISTS idx_quarantine_source_last_seen ON quarantine(source, last_seen_at DESC);
+
+COMMENT ON COLUMN quarantine.quarantined_at IS 'First time the market failed these checks';
+COMMENT ON COLUMN quarantine.last_seen_at IS 'Latest time the market failed these checks; payload and reason are from then';
@@ -0,0 +1,11 @@
+-- Migration: Placeholder markets created by history imports
+-- `pm-worker import` creates markets it has never collected from the import
+-- file alone, with a made-up status and no url. The first real collection
+-- fills them in without recording those differences as revisions.
+-- Collectors always set a url, so markets without one came from an import.
+
+ALTER TABLE markets ADD COLUMN IF NOT EXISTS placeholder BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE markets SET placeholder = TRUE WHERE url = '';
+
+COMMENT ON COLUMN markets.placeholder IS 'Created by an import, not yet seen by a collector';
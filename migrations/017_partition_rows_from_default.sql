@@ -0,0 +1,57 @@
+-- Migration: Move stray rows out of the default partition
+-- Rows recorded for a month without a partition land in
+-- price_history_default, after which CREATE TABLE ... PARTITION OF for that
+-- month fails for good. Now the partition is built beside the table, the
+-- month's rows are moved into it from the default partition and it is
+-- attached, all within the calling transaction.
+
+CREATE OR REPLACE FUNCTION create_price_history_partition(month DATE)
+RETURNS TEXT AS $$
+DECLARE
+    start_at TIMESTAMPTZ := date_trunc('month', month::TIMESTAMP) AT TIME ZONE 'UTC';
+    end_at TIMESTAMPTZ := (date_trunc('month', month::TIMESTAMP) + INTERVAL '1 month') AT TIME ZONE 'UTC';
+    partition_name TEXT := 'price_history_' || to_char(month, 'YYYY_MM');
+BEGIN
+    IF to_regclass(partition_name) IS NOT NULL THEN
+        RETURN partition_name;
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM price_history_default WHERE recorded_at >= start_at AND recorded_at < end_at
+    ) THEN
+        EXECUTE format(
+            'CREATE TABLE %I PARTITION OF price_history FOR VALUES FROM (%L) TO (%L)',
+            partition_name, start_at, end_at
+        );
+        RETURN partition_name;
+    END IF;
+
+    EXECUTE format(
+        'CREATE TABLE %I (LIKE price_history INCLUDING DEFAULTS INCLUDING CONSTRAINTS)',
+        partition_name
+    );
+    EXECUTE format(
+        'WITH moved AS (
+            DELETE FROM price_history_default
+            WHERE recorded_at >= %L AND recorded_at < %L
+            RETURNING *
+        )
+        INSERT INTO %I SELECT * FROM moved',
+        start_at, end_at, partition_name
+    );
+    EXECUTE format(
+        'ALTER TABLE price_history ATTACH PARTITION %I FOR VALUES FROM (%L) TO (%L)',
+        partition_name, start_at, end_at
+    );
+    RETURN partition_name;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Give months already stranded in the default partition their own
+SELECT create_price_history_partition(month::DATE)
+FROM (
+    SELECT DISTINCT date_trunc('month', recorded_at AT TIME ZONE 'UTC') AS month
+    FROM price_history_default
+) stranded;
+
+COMMENT ON FUNCTION create_price_history_partition(DATE) IS 'Create the monthly price_history partition containing the given date, moving its rows out of the default partition';
@@ -0,0 +1,29 @@
+-- Migration: Explicit rollup watermarks
+-- The retention job used to take MAX(bucket) of each rollup table as the
+-- point everything before had been rolled up. Backfilled or late buckets
+-- can sit past that point, and then moved it for every market: raw rows and
+-- partitions were expired, and later passes started, beyond what the
+-- regular pass had actually covered. The pass now records how far it got
+-- here, only when its rollup commits.
+
+CREATE TABLE IF NOT EXISTS rollup_watermarks (
+    -- hourly or daily
+    rollup VARCHAR(10) PRIMARY KEY,
+
+    -- Source rows before this time are rolled up
+    rolled_through TIMESTAMPTZ NOT NULL,
+
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Start from the old watermark; the next pass recomputes from there
+INSERT INTO rollup_watermarks (rollup, rolled_through)
+SELECT 'hourly', MAX(bucket) FROM price_history_hourly HAVING MAX(bucket) IS NOT NULL
+ON CONFLICT (rollup) DO NOTHING;
+
+INSERT INTO rollup_watermarks (rollup, rolled_through)
+SELECT 'daily', MAX(bucket) FROM price_history_daily HAVING MAX(bucket) IS NOT NULL
+ON CONFLICT (rollup) DO NOTHING;
+
+COMMENT ON TABLE rollup_watermarks IS 'How far the regular retention pass has rolled up each rollup table';
+COMMENT ON COLUMN rollup_watermarks.rolled_through IS 'Rows before this time are in the rollup; raw deletes and partition expiry stop here';
@@ -0,0 +1,72 @@
+-- Migration: Create markets table (SQLite)
+-- Same shape as the Postgres schema: UUIDs are hyphenated TEXT, timestamps are
+-- RFC 3339 TEXT written by the application, and tags are a JSON array
+
+CREATE TABLE IF NOT EXISTS markets (
+    id TEXT PRIMARY KEY,
+
+    -- Source identification
+    source_id TEXT NOT NULL,
+    source TEXT NOT NULL,
+
+    -- Market content
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    category TEXT,
+    tags TEXT,
+
+    -- Current pricing
+    yes_price REAL NOT NULL,
+    no_price REAL NOT NULL,
+
+    -- Volume and liquidity
+    volume REAL NOT NULL DEFAULT 0,
+    volume_24h REAL NOT NULL DEFAULT 0,
+    liquidity REAL,
+
+    -- Status
+    status TEXT NOT NULL DEFAULT 'open',
+
+    -- Timestamps
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    close_at TEXT,
+
+    -- External link
+    url TEXT NOT NULL,
+
+    -- Ensure unique markets per source
+    UNIQUE(source, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_markets_source ON markets(source);
+CREATE INDEX IF NOT EXISTS idx_markets_status ON markets(status);
+CREATE INDEX IF NOT EXISTS idx_markets_volume ON markets(volume DESC);
+CREATE INDEX IF NOT EXISTS idx_markets_created ON markets(created_at DESC);
+
+-- Full-text search over title and description (replaces to_tsvector)
+CREATE VIRTUAL TABLE IF NOT EXISTS markets_fts USING fts5(
+    title,
+    description,
+    content = 'markets',
+    content_rowid = 'rowid',
+    tokenize = 'porter unicode61'
+);
+
+-- Keep the FTS index in sync with markets
+CREATE TRIGGER IF NOT EXISTS markets_fts_insert AFTER INSERT ON markets BEGIN
+    INSERT INTO markets_fts (rowid, title, description)
+    VALUES (new.rowid, new.title, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS markets_fts_delete AFTER DELETE ON markets BEGIN
+    INSERT INTO markets_fts (markets_fts, rowid, title, description)
+    VALUES ('delete', old.rowid, old.title, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS markets_fts_update AFTER UPDATE OF title, description ON markets BEGIN
+    INSERT INTO markets_fts (markets_fts, rowid, title, description)
+    VALUES ('delete', old.rowid, old.title, old.description);
+    INSERT INTO markets_fts (rowid, title, description)
+    VALUES (new.rowid, new.title, new.description);
+END;
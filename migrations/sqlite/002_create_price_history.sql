@@ -0,0 +1,27 @@
+-- Migration: Create price_history table (SQLite)
+-- Raw snapshots only: hourly and daily resolutions are downsampled on read,
+-- and there is no retention job or partitioning
+
+CREATE TABLE IF NOT EXISTS price_history (
+    id TEXT PRIMARY KEY,
+
+    -- Foreign key to markets
+    market_id TEXT NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+
+    -- Price snapshot
+    yes_price REAL NOT NULL,
+    no_price REAL NOT NULL,
+
+    -- Volume and liquidity at snapshot time
+    volume REAL NOT NULL DEFAULT 0,
+    volume_24h REAL NOT NULL DEFAULT 0,
+    liquidity REAL,
+
+    -- Snapshot timestamp
+    recorded_at TEXT NOT NULL,
+
+    -- Ensure no duplicate snapshots for same market at same time
+    UNIQUE(market_id, recorded_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_price_history_recorded ON price_history(recorded_at DESC);
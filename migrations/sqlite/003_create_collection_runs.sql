@@ -0,0 +1,25 @@
+-- Migration: Create collection_runs table (SQLite)
+-- Audit log of worker collection cycles and their per-source steps
+
+CREATE TABLE IF NOT EXISTS collection_runs (
+    id TEXT PRIMARY KEY,
+
+    -- 'cycle' rows have one 'source' row per venue collected in that cycle
+    kind TEXT NOT NULL CHECK (kind IN ('cycle', 'source')),
+    parent_id TEXT REFERENCES collection_runs(id) ON DELETE CASCADE,
+    source TEXT,
+
+    -- Timing (finished_at stays NULL if the worker died mid-cycle)
+    started_at TEXT NOT NULL,
+    finished_at TEXT,
+
+    -- Outcome
+    markets_fetched INTEGER NOT NULL DEFAULT 0,
+    markets_recorded INTEGER NOT NULL DEFAULT 0,
+    markets_failed INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    http_status INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_collection_runs_started ON collection_runs(started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_collection_runs_parent ON collection_runs(parent_id);
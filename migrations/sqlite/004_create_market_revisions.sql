@@ -0,0 +1,19 @@
+-- Migration: Create market_revisions table (SQLite)
+-- Field-level history of market metadata overwritten by collector upserts
+
+CREATE TABLE IF NOT EXISTS market_revisions (
+    id TEXT PRIMARY KEY,
+
+    -- Foreign key to markets
+    market_id TEXT NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+
+    -- Which field changed, and its value before and after (NULL when unset)
+    field TEXT NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+
+    -- When the change was observed
+    changed_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_revisions_market_time ON market_revisions(market_id, changed_at);
@@ -0,0 +1,46 @@
+-- Migration: Canonical market statuses and sources (SQLite)
+-- Same normalization as the Postgres migration. SQLite cannot add a CHECK
+-- constraint to an existing table, so triggers reject unknown values instead.
+
+UPDATE markets SET source = lower(source) WHERE source <> lower(source);
+
+UPDATE markets
+SET status = CASE
+    WHEN lower(status) IN ('open', 'active') THEN 'open'
+    WHEN lower(status) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+    ELSE 'closed'
+END
+WHERE status NOT IN ('open', 'closed', 'resolved');
+
+UPDATE market_revisions
+SET old_value = CASE
+        WHEN lower(old_value) IN ('open', 'active') THEN 'open'
+        WHEN lower(old_value) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+        ELSE 'closed'
+    END
+WHERE field = 'status' AND old_value NOT IN ('open', 'closed', 'resolved');
+
+UPDATE market_revisions
+SET new_value = CASE
+        WHEN lower(new_value) IN ('open', 'active') THEN 'open'
+        WHEN lower(new_value) IN ('resolved', 'settled', 'determined', 'amended', 'finalized') THEN 'resolved'
+        ELSE 'closed'
+    END
+WHERE field = 'status' AND new_value NOT IN ('open', 'closed', 'resolved');
+
+DELETE FROM market_revisions
+WHERE field = 'status' AND old_value IS new_value;
+
+CREATE TRIGGER IF NOT EXISTS markets_check_insert BEFORE INSERT ON markets
+WHEN new.status NOT IN ('open', 'closed', 'resolved')
+    OR new.source NOT IN ('polymarket', 'kalshi')
+BEGIN
+    SELECT RAISE(ABORT, 'markets: unknown status or source');
+END;
+
+CREATE TRIGGER IF NOT EXISTS markets_check_update BEFORE UPDATE OF status, source ON markets
+WHEN new.status NOT IN ('open', 'closed', 'resolved')
+    OR new.source NOT IN ('polymarket', 'kalshi')
+BEGIN
+    SELECT RAISE(ABORT, 'markets: unknown status or source');
+END;
@@ -0,0 +1,18 @@
+-- Migration: Round prices and volumes to their fixed scale (SQLite)
+-- Values written as single-precision floats carry noise such as
+-- 0.6499999761581421. Prices keep 6 decimal places and USD amounts 2, the
+-- same scale as the Postgres NUMERIC columns; the store rounds on read too.
+
+UPDATE markets SET
+    yes_price = round(yes_price, 6),
+    no_price = round(no_price, 6),
+    volume = round(volume, 2),
+    volume_24h = round(volume_24h, 2),
+    liquidity = round(liquidity, 2);
+
+UPDATE price_history SET
+    yes_price = round(yes_price, 6),
+    no_price = round(no_price, 6),
+    volume = round(volume, 2),
+    volume_24h = round(volume_24h, 2),
+    liquidity = round(liquidity, 2);
@@ -0,0 +1,20 @@
+-- Migration: Create quarantine table (SQLite)
+-- Collected markets that failed validation, with the venue payload
+
+CREATE TABLE IF NOT EXISTS quarantine (
+    id TEXT PRIMARY KEY,
+
+    source TEXT NOT NULL CHECK (source IN ('polymarket', 'kalshi')),
+    source_id TEXT,
+
+    -- JSON array of failed check names
+    checks TEXT NOT NULL,
+    reason TEXT NOT NULL,
+
+    -- JSON object as returned by the venue API
+    payload TEXT NOT NULL,
+    quarantined_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_quarantine_quarantined ON quarantine(quarantined_at DESC);
+CREATE INDEX IF NOT EXISTS idx_quarantine_source ON quarantine(source, quarantined_at DESC);
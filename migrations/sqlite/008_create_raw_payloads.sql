@@ -0,0 +1,14 @@
+-- Migration: Create raw_payloads table (SQLite)
+-- Venue API responses archived by the worker, gzip compressed
+
+CREATE TABLE IF NOT EXISTS raw_payloads (
+    source TEXT NOT NULL CHECK (source IN ('polymarket', 'kalshi')),
+    fetched_at TEXT NOT NULL,
+
+    -- Response body exactly as received, gzip compressed
+    body BLOB NOT NULL,
+
+    PRIMARY KEY (source, fetched_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_raw_payloads_fetched ON raw_payloads(fetched_at);
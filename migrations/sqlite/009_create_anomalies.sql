@@ -0,0 +1,17 @@
+-- Migration: Create anomalies table (SQLite)
+-- Snapshots flagged as implausible against the market's recent history
+
+CREATE TABLE IF NOT EXISTS anomalies (
+    market_id TEXT NOT NULL REFERENCES markets(id) ON DELETE CASCADE,
+    recorded_at TEXT NOT NULL,
+
+    kind TEXT NOT NULL CHECK (kind IN ('price_spike', 'volume_decrease', 'stale')),
+    score REAL,
+    detail TEXT NOT NULL,
+
+    detected_at TEXT NOT NULL,
+
+    PRIMARY KEY (market_id, recorded_at, kind)
+);
+
+CREATE INDEX IF NOT EXISTS idx_anomalies_detected ON anomalies(detected_at DESC);
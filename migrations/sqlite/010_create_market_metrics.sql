@@ -0,0 +1,18 @@
+-- Migration: Create market_metrics table (SQLite)
+-- Derived per-market metrics, recomputed periodically by the worker
+
+CREATE TABLE IF NOT EXISTS market_metrics (
+    market_id TEXT PRIMARY KEY REFERENCES markets(id) ON DELETE CASCADE,
+
+    volatility_24h REAL,
+    volatility_7d REAL,
+    volatility_30d REAL,
+    momentum_24h REAL,
+    momentum_7d REAL,
+    max_drawdown_30d REAL,
+    last_move_at TEXT,
+    volume_acceleration REAL,
+
+    samples INTEGER NOT NULL DEFAULT 0,
+    computed_at TEXT NOT NULL
+);
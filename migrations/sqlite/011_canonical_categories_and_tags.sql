@@ -0,0 +1,37 @@
+-- Migration: Canonical market categories and tags (SQLite)
+-- Same normalization as the Postgres migration. SQLite cannot add a CHECK
+-- constraint to an existing table, so triggers reject unknown categories.
+
+ALTER TABLE markets ADD COLUMN source_category TEXT;
+
+UPDATE markets SET source_category = category WHERE source_category IS NULL;
+
+UPDATE markets
+SET category = CASE
+    WHEN lower(category) IN (
+        'politics', 'economics', 'finance', 'crypto', 'sports', 'science',
+        'technology', 'climate', 'health', 'culture', 'world', 'other'
+    ) THEN lower(category)
+    ELSE NULL
+END
+WHERE category IS NOT NULL;
+
+CREATE TRIGGER IF NOT EXISTS markets_category_check_insert BEFORE INSERT ON markets
+WHEN new.category NOT IN (
+    'politics', 'economics', 'finance', 'crypto', 'sports', 'science',
+    'technology', 'climate', 'health', 'culture', 'world', 'other'
+)
+BEGIN
+    SELECT RAISE(ABORT, 'markets: unknown category');
+END;
+
+CREATE TRIGGER IF NOT EXISTS markets_category_check_update BEFORE UPDATE OF category ON markets
+WHEN new.category NOT IN (
+    'politics', 'economics', 'finance', 'crypto', 'sports', 'science',
+    'technology', 'climate', 'health', 'culture', 'world', 'other'
+)
+BEGIN
+    SELECT RAISE(ABORT, 'markets: unknown category');
+END;
+
+CREATE INDEX IF NOT EXISTS idx_markets_category ON markets(category, volume DESC);
@@ -0,0 +1,35 @@
+-- Migration: One quarantine row per market and failed checks (SQLite)
+-- Same de-duplication as the Postgres migration.
+
+ALTER TABLE quarantine ADD COLUMN last_seen_at TEXT;
+
+-- Keep the newest row of each group, spanning every earlier sighting
+WITH ranked AS (
+    SELECT
+        id,
+        ROW_NUMBER() OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+            ORDER BY quarantined_at DESC, id
+        ) AS position,
+        MIN(quarantined_at) OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+        ) AS first_at,
+        MAX(quarantined_at) OVER (
+            PARTITION BY source, COALESCE(source_id, ''), checks
+        ) AS last_at
+    FROM quarantine
+)
+UPDATE quarantine
+SET quarantined_at = ranked.first_at, last_seen_at = ranked.last_at
+FROM ranked
+WHERE quarantine.id = ranked.id AND ranked.position = 1;
+
+DELETE FROM quarantine WHERE last_seen_at IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_quarantine_market_checks
+    ON quarantine(source, COALESCE(source_id, ''), checks);
+
+DROP INDEX IF EXISTS idx_quarantine_quarantined;
+DROP INDEX IF EXISTS idx_quarantine_source;
+CREATE INDEX IF NOT EXISTS idx_quarantine_last_seen ON quarantine(last_seen_at DESC);
+CREATE INDEX IF NOT EXISTS idx_quarantine_source_last_seen ON quarantine(source, last_seen_at DESC);
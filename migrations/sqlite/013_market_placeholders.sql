@@ -0,0 +1,6 @@
+-- Migration: Placeholder markets created by history imports (SQLite)
+-- Same as the Postgres migration: markets without a url came from an import.
+
+ALTER TABLE markets ADD COLUMN placeholder BOOLEAN NOT NULL DEFAULT FALSE;
+
+UPDATE markets SET placeholder = TRUE WHERE url = '';
@@ -26,7 +26,7 @@ for i in $(seq 1 $ITERATIONS); do
     echo "[$i/$ITERATIONS] Running collection cycle..."
 
     # Run worker for one cycle
-    timeout 60s ./target/release/pm-worker || true
+    ./target/release/pm-worker once || echo "  ⚠️  Cycle finished with errors"
 
     # Check database growth
     COUNT=$(psql "$DATABASE_URL" -t -c "SELECT COUNT(*) FROM price_history;")
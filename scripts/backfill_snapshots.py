@@ -28,7 +28,7 @@ def get_markets_from_db(conn, limit=100):
             SELECT m.id, m.source_id, m.source
             FROM markets m
             WHERE m.source = 'kalshi'
-            AND m.status = 'active'
+            AND m.status = 'open'
             ORDER BY m.created_at DESC
             LIMIT %s
         """, (limit,))
@@ -14,12 +14,9 @@ fi
 echo "🗄️  Running database migrations..."
 echo "===================================="
 
-for migration in migrations/*.sql; do
-    echo ""
-    echo "Running $(basename $migration)..."
-    psql "$DATABASE_URL" -f "$migration"
-    echo "✓ $(basename $migration) completed"
-done
+# Migrations are embedded in the binaries and tracked in _sqlx_migrations,
+# so re-running this only applies what is pending
+cargo run --release --bin pm-worker -- migrate
 
 echo ""
 echo "===================================="
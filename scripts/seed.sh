@@ -17,8 +17,6 @@ if [ -z "$DATABASE_URL" ]; then
     exit 1
 fi
 
-# Temporarily set collection interval to 0 to run once
-export COLLECTION_INTERVAL_SECONDS=60
 export TRACKED_MARKETS=10
 export WORKER_ENABLED=true
 
@@ -27,8 +25,8 @@ echo "Starting worker to collect initial markets..."
 echo "This will fetch 10 markets from Polymarket and Kalshi"
 echo ""
 
-# Run worker for one cycle (it will run indefinitely, so we'll need to kill it)
-timeout 90s cargo run --release --bin pm-worker || true
+# Run worker for a single cycle
+cargo run --release --bin pm-worker -- once || true
 
 echo ""
 echo "===================================="
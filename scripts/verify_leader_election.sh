@@ -0,0 +1,86 @@
+#!/bin/bash
+set -e
+
+# This script checks leader election against a local Postgres: it starts two
+# workers for the same shard, expects exactly one to lead, kills the leader
+# and expects the standby to take over
+
+# Load environment variables
+if [ -f .env ]; then
+    export $(grep -v '^#' .env | xargs)
+fi
+
+if [ -z "$DATABASE_URL" ]; then
+    echo "❌ DATABASE_URL not set. Please configure .env file."
+    exit 1
+fi
+
+PM_WORKER=${PM_WORKER:-./target/release/pm-worker}
+PORT_A=${PORT_A:-9191}
+PORT_B=${PORT_B:-9192}
+LOG_DIR=$(mktemp -d)
+
+export LEADER_POLL_SECONDS=1
+export TRACKED_MARKETS=${TRACKED_MARKETS:-4}
+export WORKER_ENABLED=true
+
+if [ ! -x "$PM_WORKER" ]; then
+    cargo build --release --bin pm-worker
+fi
+
+echo "🗳️  Verifying leader election"
+echo "============================="
+
+leader() {
+    curl -sf "http://127.0.0.1:$1/metrics" | awk '/^pm_worker_leader /{print $2}'
+}
+
+wait_for_leader() {
+    # Prints the port of the single leader, failing if there is none or two
+    for _ in $(seq 1 30); do
+        a=$(leader "$PORT_A" || true)
+        b=$(leader "$PORT_B" || true)
+        if [ "$a" = "1" ] && [ "$b" = "1" ]; then
+            echo "❌ Both workers report leadership" >&2
+            return 1
+        fi
+        if [ "$a" = "1" ]; then echo "$PORT_A"; return 0; fi
+        if [ "$b" = "1" ]; then echo "$PORT_B"; return 0; fi
+        sleep 1
+    done
+    echo "❌ No worker became leader" >&2
+    return 1
+}
+
+WORKER_HTTP_PORT=$PORT_A "$PM_WORKER" run > "$LOG_DIR/a.log" 2>&1 &
+PID_A=$!
+WORKER_HTTP_PORT=$PORT_B "$PM_WORKER" run > "$LOG_DIR/b.log" 2>&1 &
+PID_B=$!
+trap 'kill $PID_A $PID_B 2>/dev/null || true' EXIT
+
+FIRST=$(wait_for_leader)
+echo "Leader serves :$FIRST, the other worker stands by"
+
+# Still exactly one leader after a few heartbeats
+sleep 3
+[ "$(wait_for_leader)" = "$FIRST" ]
+
+if [ "$FIRST" = "$PORT_A" ]; then
+    kill -9 $PID_A
+    STANDBY=$PORT_B
+else
+    kill -9 $PID_B
+    STANDBY=$PORT_A
+fi
+echo "Killed the leader, waiting for the standby to take over..."
+
+SECOND=$(wait_for_leader)
+if [ "$SECOND" != "$STANDBY" ]; then
+    echo "❌ Standby did not take over"
+    exit 1
+fi
+
+echo ""
+echo "============================="
+echo "✅ Standby on :$SECOND took over"
+echo "Worker logs: $LOG_DIR"